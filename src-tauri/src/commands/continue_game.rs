@@ -1,43 +1,71 @@
 // Commands and helper functions that have to do with continuing the game.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use rand::rngs::ThreadRng;
 use time::Date;
 
-use crate::{competition::season::Season, database::{COMPETITIONS, MANAGERS, PLAYERS, TODAY}, person::player::Player, team::Team, time::db_string_to_date, types::TeamId};
+use crate::{competition::{Competition, season::Season}, database::{self, COMPETITIONS, MANAGERS, PLAYERS, TEAMS, TODAY}, match_event::SimMode, person::{manager::Manager, player::{Player, population::Population}}, team::Team, time::db_string_to_date, types::TeamId};
 
 
 // Advance the time with one day.
+// Everything is handled one at a time by ID, and fetched right before it is handled,
+// so that nothing is cloned before it is needed, and nothing is saved over changes made earlier in the day.
+// Players, managers and teams are still cloned one at a time, as their daily tasks look up other entries of the same tables.
+// Seasons are only cloned on the days they have games.
+// Games are played second by second unless the caller asks for quick simulation.
 #[tauri::command]
-pub fn go_to_next_day() {
+pub fn go_to_next_day(quick_sim: Option<bool>) {
+    let mode = match quick_sim {
+        Some(true) => SimMode::Quick,
+        _ => SimMode::Full
+    };
+
     let mut rng = rand::rng();
     let today = TODAY.lock().unwrap().clone();
 
     handle_players(&today, &mut rng);
+    Population::handle_youth_intake(&today, &mut rng);
     handle_managers_and_teams(&today, &mut rng);
+    handle_national_teams(&today);
 
     // Games are simulated here - this must be the last one!
-    handle_comps(&today, &mut rng);
+    handle_comps(&today, mode, &mut rng);
 
     *TODAY.lock().unwrap() = today.next_day().unwrap();
 }
 
 // Do the daily tasks of competitions.
-fn handle_comps(today: &Date, rng: &mut ThreadRng) {
-    let mut comps = COMPETITIONS.lock().unwrap().clone();
-    for comp in comps.values_mut() {
-        let mut season = Season::fetch_from_db(&comp.id, comp.get_seasons_amount() - 1);
+fn handle_comps(today: &Date, mode: SimMode, rng: &mut ThreadRng) {
+    let mut strengths = HashMap::new();
+    for id in database::get_ids(&COMPETITIONS, |_| true) {
+        let comp = Competition::fetch_from_db(&id);
+        let index = comp.get_seasons_amount() - 1;
 
-        // Simulate all games that happen today.
+        // Predict the upcoming games, and simulate the ones that happen today.
         if comp.format.is_some() {
-            season.simulate_day(&comp, &today, rng);
+            Season::update_predictions(&comp, &mut strengths);
+            if Season::read_from_db(&comp.id, index, |a| a.has_games_on(today)) {
+                Season::fetch_from_db(&comp.id, index).simulate_day(&comp, &today, mode, rng);
+            }
+        }
+
+        // Make sure the knockout series are played as their format says.
+        #[cfg(debug_assertions)] {
+            let problems = Season::read_from_db(&comp.id, index, |a| a.validate_series(&comp));
+            if !problems.is_empty() {
+                panic!("invalid knockout series in {}\n{:#?}", comp.name, problems);
+            }
         }
 
         // Create new seasons for parent competitions whose seasons are over.
-        if comp.parent_comp_id == 0 && *today > db_string_to_date(&season.end_date) {
-            // Cannot change teams between seasons, for now.
-            let teams: Vec<TeamId> = season.teams.iter().map(|a | a.team_id).collect();
+        if comp.parent_comp_id == 0 && Season::read_from_db(&comp.id, index, |a| *today > db_string_to_date(&a.end_date)) {
+            // Qualified teams are sent in by other competitions once they are over.
+            // Otherwise teams cannot change between seasons, for now.
+            let teams: Vec<TeamId> = match comp.has_qualification() {
+                true => Vec::new(),
+                _ => Season::read_from_db(&comp.id, index, |a| a.teams.iter().map(|a | a.team_id).collect())
+            };
             comp.create_and_setup_seasons(&teams, today, rng);
         }
     }
@@ -45,17 +73,23 @@ fn handle_comps(today: &Date, rng: &mut ThreadRng) {
 
 // Do the daily tasks of managers (and teams, they are connected).
 fn handle_managers_and_teams(today: &Date, rng: &mut ThreadRng) {
-    let mut managers = MANAGERS.lock().unwrap().clone();
     let mut teams_visited = HashSet::new();
 
-    for manager in managers.values_mut() {
-        // TODO: have the manager look for a job or something.
+    for id in database::get_ids(&MANAGERS, |a| a.person.is_active) {
+        let mut manager = match Manager::fetch_from_db(&id) {
+            Some(a) if a.person.is_active => a,
+            _ => continue
+        };
+
+        // Teams do the hiring, unemployed managers just wait.
         if manager.person.contract.is_none() {
+            manager.unemployed_checker(today, rng);
             continue;
         }
 
         let mut team = Team::fetch_from_db(&manager.person.contract.as_ref().unwrap().team_id);
         teams_visited.insert(team.id);
+        team.clear_expired_dead_money(today);
 
         // Initial evaluation here.
         // Done for human managers as well so the players can evaluate the contract offers they receive.
@@ -65,11 +99,19 @@ fn handle_managers_and_teams(today: &Date, rng: &mut ThreadRng) {
         if manager.is_human {
             team.return_actions_to_full();
             team.season_end_checker(today, rng);
+            team.board_checker(today, rng);
             team.save();
             continue;
         }
 
 
+        team.respond_to_offer_sheets(today);
+        team.respond_to_demands(today, rng);
+        team.offer_extensions(today, rng);
+        team.manage_farm_team(today);
+        team.cut_surplus_players(today);
+        team.claim_waived_players(today);
+
         let mut has_changes = false;
         while team.actions_remaining > 0 {
             let contract_offered = team.offer_contract(today, rng);
@@ -88,49 +130,62 @@ fn handle_managers_and_teams(today: &Date, rng: &mut ThreadRng) {
 
         team.return_actions_to_full();
         team.season_end_checker(today, rng);
+        team.board_checker(today, rng);
         team.save();
     }
 
-    // TODO: teams without managers should do tasks specific to them.
-    /*
-    let mut teams = TEAMS.lock().unwrap().clone();
-    for team in teams.values_mut() {
+    // Teams without managers still have to look after themselves, and find a new manager.
+    for id in database::get_ids(&TEAMS, |a| !a.is_national_team) {
+        if teams_visited.contains(&id) { continue; }
+
+        let mut team = Team::fetch_from_db(&id);
+        team.clear_expired_dead_money(today);
+        team.evaluate_player_needs();
+        team.return_actions_to_full();
+        team.season_end_checker(today, rng);
+        team.board_checker(today, rng);
+        team.fill_vacancy(today, rng);
+        team.save();
+    }
+}
 
+// Gather and release the squads of national teams.
+fn handle_national_teams(today: &Date) {
+    for id in database::get_ids(&TEAMS, |a| a.is_national_team) {
+        let mut team = Team::fetch_from_db(&id);
+        team.national_team_checker(today);
+        team.save();
     }
-    */
 }
 
 // Do the daily tasks of players.
 fn handle_players(today: &Date, rng: &mut ThreadRng) {
-    let mut players: Vec<Player> = PLAYERS.lock().unwrap().iter().filter_map(|(_, a)| match a.person.is_active {
-        true => Some(a.clone()),
-        _ => None,
-    }).collect();
+    for id in database::get_ids(&PLAYERS, |a| a.person.is_active) {
+        // Players who retired earlier in the day are left alone.
+        let mut player = match Player::fetch_from_db(&id) {
+            Some(a) if a.person.is_active => a,
+            _ => continue
+        };
+
+        // Released players go to whoever claimed them, or become free agents.
+        player.check_waivers(today);
+        player.check_loan(today);
+
+        // Players announce their retirements towards the end of the season, and retire once it is over.
+        player.consider_retirement(today, rng);
+        if player.retires_today(today) {
+            player.retire(today);
+            continue;
+        }
 
-    for player in players.iter_mut() {
         // Check if the player's contract has expired.
         let expired = player.person.check_if_contract_expired(today);
         if expired {
-            let mut team = Team::fetch_from_db(&player.person.contract.as_ref().unwrap().team_id);
-            player.person.contract = None;
-
-            team.roster.retain(|id| *id != player.id);
-            team.save();
-        }
-
-        player.check_expired_offers(today);
-
-        let signs_contract = player.person.decide_to_sign(today, rng);
-        if signs_contract {
-            player.choose_contract(today, rng);
+            player.become_free_agent(today);
         }
 
-        // Player thinks about retiring
-        if player.retires(rng) {
-            player.reject_contracts();
-            player.person.is_active = false;
-            continue;
-        }
+        player.check_free_agent_status(today);
+        player.handle_negotiations(today, rng);
 
         // Training after choosing the contract sounds most fair,
         // as then the player will choose their contract based on the most recent
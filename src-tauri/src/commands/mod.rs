@@ -3,9 +3,8 @@ pub mod continue_game;
 use std::cmp::Ordering;
 
 use serde_json::json;
-use time::Date;
 
-use crate::{competition::{self, Competition}, database::{COMPETITIONS, TODAY}, person::{Contract, manager::Manager, player::Player}, team::Team, time::date_to_db_string, types::{CompetitionId, PlayerId, TeamId}};
+use crate::{competition::{self, Competition, season::projection::Projection}, database::{COMPETITIONS, TODAY}, person::{ContractRole, manager::Manager, player::{Player, negotiation::Negotiation, retirement::RetiredPlayer}}, team::{Team, board::get_vacant_teams}, time::date_to_db_string, types::{CompetitionId, PlayerId, TeamId}};
 
 
 // Get name and ID of all competitions that are not part of another competition.
@@ -39,7 +38,9 @@ pub fn get_comp_select_package() -> Vec<(String, String)> {
 #[tauri::command]
 pub fn get_team_select_package(id: CompetitionId) -> Vec<(String, String)> {
     let teams = Competition::fetch_from_db(&id).get_teams();
-    let mut select_options: Vec<(String, String)> = teams.iter().map(|a| (a.id.to_string(), a.name.clone())).collect();
+
+    // National teams cannot be managed.
+    let mut select_options: Vec<(String, String)> = teams.iter().filter(|a| !a.is_national_team).map(|a| (a.id.to_string(), a.name.clone())).collect();
 
     // The default option that does nothing.
     select_options.push(("0".to_string(), "[No Team]".to_string()));
@@ -69,6 +70,13 @@ pub fn get_comp_screen_package(id: CompetitionId) -> serde_json::Value {
     }
 }
 
+// Get the projected final standings of a competition's current season.
+#[tauri::command]
+pub fn get_projection_package(id: CompetitionId) -> serde_json::Value {
+    let comp = Competition::fetch_from_db(&id);
+    Projection::build(&comp, &mut rand::rng()).get_package()
+}
+
 // Get all info for a team screen in a JSON string.
 #[tauri::command]
 pub fn get_team_screen_package(id: TeamId) -> serde_json::Value {
@@ -84,7 +92,9 @@ pub fn get_player_package(id: PlayerId) -> serde_json::Value {
 // Create a human manager in the game.
 #[tauri::command]
 pub fn create_human_manager(id: TeamId) {
-    let mut human = Manager::build_and_save_random(&TODAY.lock().unwrap().clone(), &mut rand::rng());
+    let today = TODAY.lock().unwrap().clone();
+    let mut rng = rand::rng();
+    let mut human = Manager::build_and_save_random(&today, &mut rng);
     human.is_human = true;
 
     human.person.forename = "Human".to_string();
@@ -98,18 +108,12 @@ pub fn create_human_manager(id: TeamId) {
     }
 
     let mut team = Team::fetch_from_db(&id);
-    human.person.contract = Some(Contract::build(&date_to_db_string(&TODAY.lock().unwrap()), &date_to_db_string(&Date::MAX), id));
-
     match team.get_manager() {
-        Some(mut manager) => {
-            manager.person.contract = None;
-            manager.save();
-        },
+        Some(mut manager) => manager.leave_team(),
         None => {}
     };
 
-    team.manager_id = human.id;
-    human.save();
+    team.hire_manager(&mut human, &today, &mut rng);
     team.save();
 
     println!("Took control of {}.", team.name);
@@ -118,8 +122,10 @@ pub fn create_human_manager(id: TeamId) {
 // Get information about the human.
 #[tauri::command]
 pub fn get_human_package() -> serde_json::Value {
-    let human = Manager::get_human().unwrap();
-    return human.get_package();
+    match Manager::get_human() {
+        Some(human) => human.get_package(),
+        _ => serde_json::Value::Null
+    }
 }
 
 // Get all free agents.
@@ -128,19 +134,293 @@ pub fn get_free_agents_package() -> serde_json::Value {
     Player::get_all_free_agents_package(&TODAY.lock().unwrap().clone())
 }
 
+// Check if the team is managed by the human, so that the frontend cannot act on behalf of other teams.
+fn is_human_team(team_id: TeamId) -> bool {
+    Manager::get_human().and_then(|a| a.person.contract).is_some_and(|a| a.team_id == team_id)
+}
+
 // Offer a contract to a player.
+// Return whether the offer was made.
 #[tauri::command]
-pub fn offer_contract(player_id: PlayerId, team_id: TeamId, years: i32) {
+pub fn offer_contract(player_id: PlayerId, team_id: TeamId, years: i32, salary: u32, role: ContractRole) -> bool {
+    if !is_human_team(team_id) || !Negotiation::is_valid_offer(years, salary) { return false; }
+
     let today = TODAY.lock().unwrap().clone();
     let mut team = Team::fetch_from_db(&team_id);
     let mut player = Player::fetch_from_db(&player_id).unwrap();
-    let contract = Contract::build_from_years(&team, &today, years);
+    let can_offer = player.is_open_to_offers_from(team_id) || player.is_open_to_extension_from(team_id, &today);
+    if !can_offer || team.actions_remaining == 0 || !team.can_register(&player, &today) { return false; }
 
-    team.offer_contract_to_player(&mut player, contract);
+    let contract = team.build_offer(&player, &today, years, salary, role);
+    team.offer_contract_to_player(&mut player, contract, years, &today);
 
     // This is for players to evaluate this team's attractiveness.
     team.evaluate_player_needs();
     team.save();
+
+    return true;
+}
+
+// Answer the demands of a player with a new offer.
+// Return whether the offer was made.
+#[tauri::command]
+pub fn counter_offer(player_id: PlayerId, team_id: TeamId, years: i32, salary: u32, role: ContractRole) -> bool {
+    if !is_human_team(team_id) || !Negotiation::is_valid_offer(years, salary) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    return team.counter_offer(&mut player, years, salary, role, &today);
+}
+
+// Give the player what they demand.
+// Return whether the offer was made.
+#[tauri::command]
+pub fn accept_demands(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    let demand = match player.get_negotiation(team_id) {
+        Some(negotiation) if negotiation.awaits_team() => negotiation.demand.clone().unwrap(),
+        _ => return false
+    };
+
+    return team.counter_offer(&mut player, demand.years, demand.salary, demand.role, &today);
+}
+
+// Walk away from negotiations with a player.
+#[tauri::command]
+pub fn withdraw_offer(player_id: PlayerId, team_id: TeamId) {
+    if !is_human_team(team_id) { return; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let mut team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    team.withdraw_offer(&mut player, &today);
+    team.evaluate_player_needs();
+    team.save();
+}
+
+// Promise a player a qualifying offer to keep the rights to them once their contract ends.
+// Return whether the offer was tendered.
+#[tauri::command]
+pub fn tender_qualifying_offer(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+    if !player.can_receive_qualifying_offer(team_id, &today) { return false; }
+
+    player.has_qualifying_offer = true;
+    player.save();
+
+    return true;
+}
+
+// Match the offer another team has made to a restricted free agent.
+#[tauri::command]
+pub fn match_offer_sheet(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+    if !player.has_right_of_first_refusal(team_id) || player.get_offer_sheet().is_none() { return false; }
+
+    player.match_offer_sheet(&team, &today);
+    player.save();
+
+    return true;
+}
+
+// Let a restricted free agent go to the team that made them an offer.
+#[tauri::command]
+pub fn decline_offer_sheet(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+    if !player.has_right_of_first_refusal(team_id) { return false; }
+
+    let offer_team_id = match player.get_offer_sheet() {
+        Some(sheet) => sheet.team_id,
+        None => return false
+    };
+
+    player.sign_negotiated_contract(offer_team_id, &today);
+    player.save();
+
+    return true;
+}
+
+// Put a player of the team on waivers.
+// Return whether the player was released.
+#[tauri::command]
+pub fn release_player(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let mut team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    let released = team.release_player(&mut player, &today);
+    team.evaluate_player_needs();
+    team.save();
+
+    return released;
+}
+
+// Buy out the contract of a player of the team.
+// Return whether the player was bought out.
+#[tauri::command]
+pub fn buy_out_player(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let mut team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    let bought_out = team.buy_out_player(&mut player);
+    team.evaluate_player_needs();
+    team.save();
+
+    return bought_out;
+}
+
+// Claim a player from waivers.
+// Return whether the claim was made.
+#[tauri::command]
+pub fn claim_waived_player(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    return team.claim_waived_player(&mut player, &today);
+}
+
+// Get all players on waivers.
+#[tauri::command]
+pub fn get_waiver_wire_package() -> serde_json::Value {
+    Player::get_waiver_wire_package(&TODAY.lock().unwrap().clone())
+}
+
+// Get all retired players, the hall of famers first.
+#[tauri::command]
+pub fn get_retired_players_package() -> serde_json::Value {
+    RetiredPlayer::get_archive_package()
+}
+
+// Send a player of the team down to its farm team.
+// Return whether the player was sent down.
+#[tauri::command]
+pub fn send_down_player(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let mut team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    let sent_down = team.send_down_player(&mut player, &today);
+    team.evaluate_player_needs();
+    team.save();
+
+    return sent_down;
+}
+
+// Bring a player of the team back from its farm team.
+// Return whether the player was called up.
+#[tauri::command]
+pub fn call_up_player(player_id: PlayerId, team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let mut team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    let called_up = team.call_up_player(&mut player, &today);
+    team.evaluate_player_needs();
+    team.save();
+
+    return called_up;
+}
+
+// Loan a player of the team to another team.
+// Return whether the player was loaned.
+#[tauri::command]
+pub fn loan_player(player_id: PlayerId, team_id: TeamId, loan_team_id: TeamId) -> bool {
+    if !is_human_team(team_id) { return false; }
+
+    let today = TODAY.lock().unwrap().clone();
+    let mut team = Team::fetch_from_db(&team_id);
+    let mut player = Player::fetch_from_db(&player_id).unwrap();
+
+    let loaned = team.loan_out_player(&mut player, loan_team_id, &today);
+    team.evaluate_player_needs();
+    team.save();
+
+    return loaned;
+}
+
+// Get all teams that are looking for a manager.
+#[tauri::command]
+pub fn get_job_market_package() -> serde_json::Value {
+    let human_id = match Manager::get_human() {
+        Some(human) => human.id,
+        _ => 0
+    };
+
+    get_vacant_teams().iter().map(|a| a.get_vacancy_package(human_id)).collect()
+}
+
+// Apply for a vacant manager job as the human.
+// Return whether the application was received.
+#[tauri::command]
+pub fn apply_for_job(team_id: TeamId) -> bool {
+    let human = match Manager::get_human() {
+        Some(human) => human,
+        _ => return false
+    };
+    if !human.person.is_active { return false; }
+
+    let mut team = Team::fetch_from_db(&team_id);
+    let received = team.receive_application(human.id);
+    team.save();
+
+    return received;
+}
+
+// Get the board's review of the human's most recent season.
+#[tauri::command]
+pub fn get_season_review_package() -> serde_json::Value {
+    match Manager::get_human() {
+        Some(human) => human.get_season_review_package(),
+        _ => serde_json::Value::Null
+    }
+}
+
+// End the career of the human manager.
+#[tauri::command]
+pub fn retire_human_manager() {
+    let mut human = match Manager::get_human() {
+        Some(human) => human,
+        _ => return
+    };
+    match human.get_team() {
+        Some(mut team) => {
+            team.release_manager(&mut human);
+            team.save();
+        },
+        None => {}
+    };
+
+    human.person.is_active = false;
+    human.save();
 }
 
 // Get relevant information for the top bar.
@@ -0,0 +1,60 @@
+// Rules for player contracts in a league.
+
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct ContractRules {
+    // Players at most this old become restricted free agents when their contract ends.
+    // 0 means every player becomes an unrestricted free agent.
+    restricted_max_age: u8,
+
+    // Percentage of the previous salary the team has to offer to keep its rights to a restricted free agent.
+    // None if the rights are kept without an offer.
+    pub qualifying_offer: Option<u8>,
+
+    // Whether the team can match the offers other teams make to its restricted free agents.
+    pub right_of_first_refusal: bool,
+}
+
+impl ContractRules {
+    pub fn build(restricted_max_age: u8, qualifying_offer: Option<u8>, right_of_first_refusal: bool) -> Self {
+        Self {
+            restricted_max_age: restricted_max_age,
+            qualifying_offer: qualifying_offer,
+            right_of_first_refusal: right_of_first_refusal,
+        }
+    }
+
+    // Check if a player of the given age becomes a restricted free agent.
+    pub fn is_restricted_age(&self, age: i8) -> bool {
+        age <= self.restricted_max_age as i8
+    }
+
+    // Get the salary of a qualifying offer, based on the previous salary.
+    pub fn get_qualifying_salary(&self, salary: u32) -> Option<u32> {
+        self.qualifying_offer.map(|a| salary * a as u32 / 100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restricted_age_includes_the_limit() {
+        let rules = ContractRules::build(25, Some(105), true);
+        assert!(rules.is_restricted_age(18));
+        assert!(rules.is_restricted_age(25));
+        assert!(!rules.is_restricted_age(26));
+
+        // Nobody is restricted without a limit.
+        assert!(!ContractRules::default().is_restricted_age(18));
+    }
+
+    #[test]
+    fn qualifying_salary_follows_percentage() {
+        assert_eq!(ContractRules::build(25, Some(105), true).get_qualifying_salary(1000), Some(1050));
+        assert_eq!(ContractRules::build(25, Some(105), true).get_qualifying_salary(333), Some(349));
+        assert_eq!(ContractRules::build(25, Some(100), false).get_qualifying_salary(800), Some(800));
+        assert_eq!(ContractRules::build(25, None, true).get_qualifying_salary(1000), None);
+    }
+}
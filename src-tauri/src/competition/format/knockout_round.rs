@@ -1,19 +1,171 @@
 // Functions exclusive to knockout stages.
 
+// How the winner of a knockout pair is decided.
+#[derive(Debug, serde::Serialize)]
+#[derive(Clone, Default, PartialEq)]
+pub enum SeriesType {
+    #[default]
+    BestOf,                 // The first team to reach the required amount of wins advances.
+    TwoLegged(TieBreaker),  // Both teams host a game, and the team with more goals in total advances.
+}
+
+// How a two-legged tie is decided if both teams have scored as many goals.
+#[derive(Debug, serde::Serialize)]
+#[derive(Clone, Default, PartialEq)]
+pub enum TieBreaker {
+    #[default]
+    Overtime,   // Overtime, and a shootout if needed, is played in the second leg.
+    AwayGoals,  // The team with more goals in away games advances. Overtime is played only if those are even too.
+}
+
+// How the pairs of a round are formed.
+#[derive(Debug, serde::Serialize)]
+#[derive(Clone, Default, PartialEq)]
+pub enum BracketType {
+    #[default]
+    Draw,           // Teams from the top seed pot are drawn against teams from the bottom seed pot at random.
+    Fixed,          // The bracket is decided in the first round, so that for example the winners of 1 v 8 and 4 v 5 meet next.
+    Reseed,         // The best remaining seed faces the worst remaining seed.
+    PickOpponent,   // The best remaining seed picks its opponent, then the next best seed, and so on.
+}
+
 #[derive(Debug, serde::Serialize)]
 #[derive(Clone, Default)]
 pub struct KnockoutRound {
     pub wins_required: u8,
+    pub series_type: SeriesType,
+
+    // How many games in a row each team hosts, alternating between the higher and the lower seed, starting with the higher seed.
+    // For example, [2, 3, 2] for best-of-seven. Empty lets the teams take turns, which only works for best-of series.
+    home_pattern: Vec<u8>,
+
+    // No team gets to play at home.
+    pub neutral_venue: bool,
+
+    pub bracket_type: BracketType,
+
+    // How many teams enter the bracket in this round, instead of advancing from the previous round.
+    // The top seeds get byes to the later rounds. 0 if the round is not a part of a bracket.
+    pub entering_teams: u8,
+
+    // The best final position the round is played for, if its teams have been eliminated from the main bracket.
+    // For example, 3 for a third place game. 0 for the rounds of the main bracket.
+    pub placement: u8,
 }
 
 impl KnockoutRound {
     // Build the element.
     pub fn build(wins_required: u8) -> Self {
-        KnockoutRound { wins_required: wins_required }
+        KnockoutRound {
+            wins_required: wins_required,
+            ..Default::default()
+        }
+    }
+
+    // Build a round of two-legged ties.
+    // The higher seed hosts the second leg, so it knows what it needs to do when playing at home.
+    pub fn build_two_legged(tie_breaker: TieBreaker) -> Self {
+        KnockoutRound {
+            wins_required: 1,
+            series_type: SeriesType::TwoLegged(tie_breaker),
+            home_pattern: vec![0, 1, 1],
+            ..Default::default()
+        }
+    }
+
+    // Build a round of single games played at neutral venues.
+    pub fn build_neutral_single_game() -> Self {
+        KnockoutRound {
+            wins_required: 1,
+            neutral_venue: true,
+            ..Default::default()
+        }
+    }
+
+    // Set the order in which the teams host the games of the pair.
+    pub fn set_home_pattern(&mut self, home_pattern: Vec<u8>) {
+        self.home_pattern = home_pattern;
+    }
+
+    // Make sure the knockout rules do not have illegal values.
+    pub fn is_valid(&self) -> bool {
+        // The maximum amount of matches cannot be counted without wins.
+        if self.wins_required == 0 { return false; }
+
+        let pattern_games: u8 = self.home_pattern.iter().sum();
+        match self.home_pattern.is_empty() {
+            true => self.series_type == SeriesType::BestOf,
+            _ => pattern_games == self.get_maximum_matches_in_pair()
+        }
     }
 
     // Get the amount of matches there can be in the round at most.
     pub fn get_maximum_matches_in_pair(&self) -> u8 {
-        return self.wins_required * 2 - 1
+        match self.series_type {
+            SeriesType::BestOf => self.wins_required * 2 - 1,
+            SeriesType::TwoLegged(_) => 2,
+        }
+    }
+
+    // Get the order in which the teams host the games.
+    fn get_home_pattern(&self) -> Vec<u8> {
+        match self.home_pattern.is_empty() {
+            // The higher seed hosts the first game and every other after that.
+            true => vec![1; self.get_maximum_matches_in_pair() as usize],
+            _ => self.home_pattern.clone()
+        }
+    }
+
+    // Check if the higher seed hosts the game with the given index of the pair.
+    pub fn is_higher_seed_home(&self, game_index: usize) -> bool {
+        let mut games = 0;
+        for (i, stretch) in self.get_home_pattern().iter().enumerate() {
+            games += *stretch as usize;
+            if game_index < games {
+                return i % 2 == 0;
+            }
+        }
+
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_seed_hosts_second_leg() {
+        let round = KnockoutRound::build_two_legged(TieBreaker::AwayGoals);
+        assert!(round.is_valid());
+        assert!(!round.is_higher_seed_home(0));
+        assert!(round.is_higher_seed_home(1));
+
+        // Two-legged ties have to say who hosts which leg.
+        let round = KnockoutRound { series_type: SeriesType::TwoLegged(TieBreaker::Overtime), ..KnockoutRound::build(1) };
+        assert!(!round.is_valid());
+    }
+
+    #[test]
+    fn best_of_series_follow_home_pattern() {
+        let mut round = KnockoutRound::build(4);
+        assert!(round.is_valid());
+        assert_eq!((0..7).map(|a| round.is_higher_seed_home(a)).collect::<Vec<bool>>(), [true, false, true, false, true, false, true]);
+
+        round.set_home_pattern(vec![2, 3, 2]);
+        assert!(round.is_valid());
+        assert_eq!((0..7).map(|a| round.is_higher_seed_home(a)).collect::<Vec<bool>>(), [true, true, false, false, false, true, true]);
+
+        round.set_home_pattern(vec![2, 2]);
+        assert!(!round.is_valid());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn rounds_without_wins_are_invalid() {
+        let mut round = KnockoutRound::build(0);
+        assert!(!round.is_valid());
+
+        round.set_home_pattern(vec![1]);
+        assert!(!round.is_valid());
+    }
+}
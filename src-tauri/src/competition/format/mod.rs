@@ -50,7 +50,8 @@ impl Format {
     // Make sure Stage does not have illegal values.
     fn is_valid(&self) -> bool {
         self.match_rules.is_valid() &&
-        self.format_type != Type::Null
+        self.format_type != Type::Null &&
+        self.knockout_round.as_ref().map_or(true, |a| a.is_valid())
     }
 
     // Get JSON for a competition screen.
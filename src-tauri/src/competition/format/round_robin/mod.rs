@@ -1,9 +1,10 @@
 // The round robin struct and methods for round-robin stages.
-mod schedule_validator;
+pub mod schedule_validator;
 
 use crate::{competition::{Competition, season::{Season, team::TeamCompData}}, match_event::Game, team::Team, types::{convert}};
 
-#[derive(Default, Clone, PartialEq, Debug)]
+#[derive(Debug, serde::Serialize)]
+#[derive(Default, Clone, PartialEq)]
 pub enum MatchGenType {
     #[default] Null,
     MatchCount,
@@ -21,6 +22,7 @@ pub struct RoundRobin {
     pub points_for_draw: u8,
     pub points_for_ot_loss: u8,
     pub points_for_loss: u8,
+    pub match_gen_type: MatchGenType,   // How teams are sorted when generating irregular schedules.
 }
 
 // Basics
@@ -36,6 +38,7 @@ impl RoundRobin {
             points_for_draw: points_for_draw,
             points_for_ot_loss: points_for_ot_loss,
             points_for_loss: points_for_loss,
+            match_gen_type: Self::MATCH_GEN_TYPE,
         }
     }
 
@@ -43,6 +46,14 @@ impl RoundRobin {
     pub fn is_valid(&self) -> bool {
         self.rounds != 0 || self.extra_matches != 0
     }
+
+    // Get the match generation type to use. Null falls back to the default.
+    pub fn get_match_gen_type(&self) -> MatchGenType {
+        match self.match_gen_type {
+            MatchGenType::Null => Self::MATCH_GEN_TYPE,
+            _ => self.match_gen_type.clone()
+        }
+    }
 }
 
 impl RoundRobin {
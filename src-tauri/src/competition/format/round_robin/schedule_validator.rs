@@ -1 +1,134 @@
-// Methods for validating match schedules.
\ No newline at end of file
+// Methods for validating match schedules.
+
+use std::collections::HashMap;
+
+use time::Date;
+
+use crate::{competition::{format::round_robin::RoundRobin, season::Season}, match_event::Game, time::{date_to_db_string, db_string_to_date}, types::{TeamId, convert}};
+
+// How many times longer than in an evenly spread schedule a team's break between games can be.
+const SPREAD_TOLERANCE: i64 = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleProblem {
+    MatchCount(TeamId, u8),             // The team has a wrong amount of matches.
+    HomeAwayBalance(TeamId, u8, u8),    // The team has too many more home matches than away matches, or the other way around.
+    DoubleGame(TeamId, String),         // The team plays more than once on the date.
+    PairingCount(TeamId, TeamId, u8),   // The teams face each other a wrong amount of times.
+    OutsideWindow(String),              // A game is played outside the season window.
+    PoorSpread(TeamId, u16),            // The team goes this many days without games.
+}
+
+impl ScheduleProblem {
+    // Check if the problem makes the schedule unfair or unplayable.
+    // A poor spread is only a matter of taste.
+    pub fn is_serious(&self) -> bool {
+        match self {
+            Self::PoorSpread(..) => false,
+            _ => true
+        }
+    }
+}
+
+impl RoundRobin {
+    // Check the generated match schedule of the season and return every problem found.
+    pub fn validate_schedule(&self, season: &Season) -> Vec<ScheduleProblem> {
+        let games = season.get_all_games();
+        let mut problems = Vec::new();
+
+        self.validate_match_counts(season, &games, &mut problems);
+        self.validate_pairings(season, &games, &mut problems);
+        validate_dates(season, &games, &mut problems);
+
+        return problems;
+    }
+
+    // Check that every team has the right amount of matches, and that home and away matches are balanced.
+    fn validate_match_counts(&self, season: &Season, games: &[&Game], problems: &mut Vec<ScheduleProblem>) {
+        let matches_per_team = self.get_theoretical_matches_per_team(season);
+
+        for team in season.teams.iter() {
+            let home = convert::int::<usize, u8>(games.iter().filter(|a| a.home.team_id == team.team_id).count());
+            let away = convert::int::<usize, u8>(games.iter().filter(|a| a.away.team_id == team.team_id).count());
+
+            if home + away != matches_per_team {
+                problems.push(ScheduleProblem::MatchCount(team.team_id, home + away));
+            }
+
+            if home.abs_diff(away) > 1 {
+                problems.push(ScheduleProblem::HomeAwayBalance(team.team_id, home, away));
+            }
+        }
+    }
+
+    // Check that the teams face each other as many times as the rounds require.
+    // Extra matches can make some pairs meet once more than others.
+    fn validate_pairings(&self, season: &Season, games: &[&Game], problems: &mut Vec<ScheduleProblem>) {
+        let round_length = self.get_round_length(season);
+        if round_length == 0 { return; }
+
+        let matches_per_team = self.get_theoretical_matches_per_team(season);
+        let min_count = matches_per_team / round_length;
+        let max_count = matches_per_team.div_ceil(round_length);
+
+        let mut counts: HashMap<[TeamId; 2], u8> = HashMap::new();
+        for game in games.iter() {
+            let pair = [game.home.team_id.min(game.away.team_id), game.home.team_id.max(game.away.team_id)];
+            *counts.entry(pair).or_default() += 1;
+        }
+
+        for (i, team1) in season.teams.iter().enumerate() {
+            for team2 in season.teams.iter().skip(i + 1) {
+                let pair = [team1.team_id.min(team2.team_id), team1.team_id.max(team2.team_id)];
+                let count = counts.get(&pair).cloned().unwrap_or_default();
+
+                if count < min_count || count > max_count {
+                    problems.push(ScheduleProblem::PairingCount(pair[0], pair[1], count));
+                }
+            }
+        }
+    }
+}
+
+// Check that the games are inside the season window, that no team plays twice on a day,
+// and that the games of each team are spread over the window.
+fn validate_dates(season: &Season, games: &[&Game], problems: &mut Vec<ScheduleProblem>) {
+    let start_date = db_string_to_date(&season.start_date);
+    let end_date = db_string_to_date(&season.end_date);
+    let window_length = (end_date - start_date).whole_days() + 1;
+
+    let mut team_dates: HashMap<TeamId, Vec<Date>> = HashMap::new();
+    for game in games.iter() {
+        let date = db_string_to_date(&game.date);
+        if date < start_date || date > end_date {
+            problems.push(ScheduleProblem::OutsideWindow(game.date.clone()));
+        }
+
+        for id in [game.home.team_id, game.away.team_id] {
+            team_dates.entry(id).or_default().push(date);
+        }
+    }
+
+    for team in season.teams.iter() {
+        let mut dates = match team_dates.remove(&team.team_id) {
+            Some(a) => a,
+            None => continue
+        };
+        dates.sort();
+
+        let max_break = window_length * SPREAD_TOLERANCE / (dates.len() as i64 + 1);
+        let mut longest_break = 0;
+        for pair in dates.windows(2) {
+            let days = (pair[1] - pair[0]).whole_days();
+            if days == 0 {
+                problems.push(ScheduleProblem::DoubleGame(team.team_id, date_to_db_string(&pair[0])));
+            }
+
+            longest_break = longest_break.max(days - 1);
+        }
+
+        if longest_break > max_break {
+            problems.push(ScheduleProblem::PoorSpread(team.team_id, convert::int::<i64, u16>(longest_break)));
+        }
+    }
+}
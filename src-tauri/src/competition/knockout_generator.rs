@@ -4,76 +4,167 @@ use ordinal::ToOrdinal as _;
 use rand::{Rng, rngs::ThreadRng};
 use time::{Date, Duration};
 
-use crate::{competition::{self, CompConnection, Competition, Seed, format::{self, knockout_round::KnockoutRound as KnockoutRoundFormat}, season::ranking::RankCriteria}, match_event, time::{AnnualDate, AnnualWindow, get_dates}, types::{CompetitionId, convert}};
+use crate::{competition::{self, CompConnection, Competition, Seed, format::{self, knockout_round::{BracketType, KnockoutRound as KnockoutRoundFormat}}, season::ranking::RankCriteria}, match_event, time::{AnnualDate, AnnualWindow, get_dates}, types::{CompetitionId, convert}};
+
+// A reason why a knockout competition cannot be built with the given amounts of teams.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BracketError {
+    TooFewTeams(u8, u8),        // The field is not at least twice as large as the amount of teams the competition ends with.
+    NoTeamsAtEnd,               // The competition has to end with at least one team.
+    InvalidRound(u8),           // A play-in round has no teams or an odd amount of them.
+    PlayInTooSmall(u8, u8),     // A play-in round has fewer teams than advance to it from the previous round.
+    PlayInTooLarge(u8, u8),     // A play-in round needs more teams than there are left to enter it.
+    NoFullBracket(u8, u8),      // The teams left after the play-in rounds do not make a full bracket down to the teams at the end.
+}
+
+// How many teams play a round, and how many of them enter the bracket in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundPlan {
+    pub teams: u8,
+    pub entering_teams: u8,
+}
+
+// Teams to send from one generated round to another, once the rounds have IDs.
+struct RoundLink {
+    from: usize,        // Index of the round the teams are sent from.
+    positions: [u8; 2], // Positions of the teams in the final ranking of the round.
+    to: usize,          // Index of the round the teams are sent to.
+}
 
 // Generate a knockout competition with each round being represented as its own competition element.
+// Return the ID of the knockout competition.
 pub fn build(
     name: &str, // Name of the knockout competition itself.
     round_names: Vec<&str>, // Names for the rounds to be generated. If there are more rounds than names, the remaining round will have automatically generated names.
     season_window: AnnualWindow, // Time when this competition is played.
     match_rules: Vec<match_event::Rules>,    // Match rules for each round. If there are more rounds than elements in this vector, the last element will be applied to remaining rounds.
-    wins_required: Vec<u8>, // Wins required to advance from each round. If there are more rounds than elements in this vector, the last element will be applied to remaining rounds.
-    mut teams_in_rounds: Vec<u8>,   // Number of teams the knockout competition has on each round.
+    formats: Vec<KnockoutRoundFormat>, // How the pairs of each round are played. If there are more rounds than elements in this vector, the last element will be applied to remaining rounds.
+    bracket_type: BracketType,  // How the pairs are formed. Applied to every round, as the rounds of a bracket depend on each other.
+    no_of_teams: u8,    // Number of teams the knockout competition starts with.
+    play_in_rounds: Vec<u8>,    // Number of teams in each play-in round before the full bracket. Empty to have one play-in round only if the field needs it.
     teams_at_end: u8,   // Number of teams the knockout competition ends with.
+    placement_games_to: u8, // The lowest final position decided with placement games, for example 3 for a third place game. 0 for none.
     connections: Vec<CompConnection>,    // Connections to other competitions; where to move which teams after the knockout is over.
     rank_criteria: Vec<RankCriteria>, today: &Date, rng: &mut ThreadRng
-) {
-    let mut parent_comp = Competition::build_and_save(name, Vec::new(), season_window, connections, teams_in_rounds[0], None, Vec::new(), Vec::new(), today);
-    parent_comp.competition_type = competition::Type::Tournament;
+) -> Result<CompetitionId, BracketError> {
+    let round_plans = plan_rounds(no_of_teams, &play_in_rounds, teams_at_end)?;
 
-    get_teams_in_rounds(&mut teams_in_rounds, teams_at_end);
-    let mut rounds = create_rounds(round_names, match_rules, wins_required, teams_in_rounds, rank_criteria, parent_comp.id);
+    let mut parent_comp = Competition::build_and_save(name, Vec::new(), season_window, connections, no_of_teams, None, Vec::new(), Vec::new(), today);
+    parent_comp.competition_type = competition::Type::Tournament;
 
+    let mut rounds = create_rounds(round_names, match_rules, formats, bracket_type, &round_plans, rank_criteria, parent_comp.id);
     set_date_boundaries(&mut rounds, &parent_comp.season_window, rng);
-    finalise_rounds(&mut parent_comp, &mut rounds, today);
+
+    let mut links = Vec::new();
+    link_bracket(&mut rounds, &mut links, round_plans.len(), 0, 0, &round_plans, 1, placement_games_to);
+
+    finalise_rounds(&mut parent_comp, &mut rounds, &links, today);
+    return Ok(parent_comp.id);
+}
+
+// Plan how many teams play each round, and how many of them enter the bracket in it.
+// The lowest seeds play the play-in rounds, and the top seeds get byes to the later rounds.
+pub fn plan_rounds(no_of_teams: u8, play_in_rounds: &[u8], teams_at_end: u8) -> Result<Vec<RoundPlan>, BracketError> {
+    if teams_at_end == 0 {
+        return Err(BracketError::NoTeamsAtEnd);
+    }
+    if (no_of_teams as u16) < teams_at_end as u16 * 2 {
+        return Err(BracketError::TooFewTeams(no_of_teams, teams_at_end));
+    }
+
+    // Without given play-in rounds, the lowest seeds play one only if the field does not make a full bracket.
+    let play_in_rounds = match play_in_rounds.is_empty() {
+        true => get_default_play_in_rounds(no_of_teams, teams_at_end),
+        _ => play_in_rounds.to_vec()
+    };
+
+    let mut plans = Vec::new();
+    let mut teams_left = no_of_teams;
+    let mut advancing_teams = 0;
+    for teams in play_in_rounds {
+        if teams == 0 || teams % 2 != 0 {
+            return Err(BracketError::InvalidRound(teams));
+        }
+        if teams < advancing_teams {
+            return Err(BracketError::PlayInTooSmall(teams, advancing_teams));
+        }
+
+        let entering_teams = teams - advancing_teams;
+        if entering_teams > teams_left {
+            return Err(BracketError::PlayInTooLarge(teams, teams_left));
+        }
+
+        plans.push(RoundPlan { teams: teams, entering_teams: entering_teams });
+        teams_left -= entering_teams;
+        advancing_teams = teams / 2;
+    }
+
+    // The rest of the rounds make a full bracket, with every remaining team entering the first of them.
+    let bracket_teams = teams_left as u16 + advancing_teams as u16;
+    if !is_full_bracket(bracket_teams, teams_at_end) {
+        return Err(BracketError::NoFullBracket(convert::int::<u16, u8>(bracket_teams), teams_at_end));
+    }
+
+    let mut teams = bracket_teams;
+    while teams > teams_at_end as u16 {
+        plans.push(RoundPlan { teams: convert::int::<u16, u8>(teams), entering_teams: teams_left });
+        teams_left = 0;
+        teams /= 2;
+    }
+
+    return Ok(plans);
 }
 
-// Get how many teams each round should actually have.
-fn get_teams_in_rounds(teams_in_rounds: &mut Vec<u8>, teams_at_end: u8) {
-    let mut last_rounds = Vec::new();
-    let mut next_teams = teams_at_end * 2;
-    while next_teams < *teams_in_rounds.last().unwrap() {
-        last_rounds.push(next_teams);
-        next_teams *= 2;
+// Get the play-in round needed to bring the field down to a full bracket.
+// For example, a field of ten teams ending with one has a play-in round of four teams, and the top six seeds get a bye.
+fn get_default_play_in_rounds(no_of_teams: u8, teams_at_end: u8) -> Vec<u8> {
+    let mut bracket_teams = teams_at_end as u16;
+    while bracket_teams * 2 <= no_of_teams as u16 {
+        bracket_teams *= 2;
     }
 
-    // Return the no of teams for each round here if there is no need for special rounds.
-    if &next_teams == teams_in_rounds.last().unwrap() {
-        last_rounds.reverse();
-        teams_in_rounds.append(&mut last_rounds);
-        return;
+    if bracket_teams == no_of_teams as u16 {
+        return Vec::new();
     }
 
-    // In case there is need for a special round...
-    let start_teams = teams_in_rounds.swap_remove(teams_in_rounds.len() - 1);
-    let round_size = (start_teams - last_rounds.last().unwrap()) * 2;
+    return vec![convert::int::<u16, u8>((no_of_teams as u16 - bracket_teams) * 2)];
+}
+
+// Check if the amount of teams halves down to the teams at the end, with at least one round played.
+fn is_full_bracket(teams: u16, teams_at_end: u8) -> bool {
+    let mut teams_at_end = teams_at_end as u16;
+    if teams <= teams_at_end { return false; }
+
+    while teams_at_end < teams {
+        teams_at_end *= 2;
+    }
 
-    teams_in_rounds.push(round_size);
-    last_rounds.reverse();
-    teams_in_rounds.append(&mut last_rounds);
+    return teams_at_end == teams;
 }
 
 // Create rounds.
-fn create_rounds(round_names: Vec<&str>, match_rules: Vec<match_event::Rules>, wins_required: Vec<u8>, teams_in_rounds: Vec<u8>, rank_criteria: Vec<RankCriteria>, parent_comp_id: CompetitionId) -> Vec<Competition> {
+fn create_rounds(round_names: Vec<&str>, match_rules: Vec<match_event::Rules>, formats: Vec<KnockoutRoundFormat>, bracket_type: BracketType, round_plans: &[RoundPlan], rank_criteria: Vec<RankCriteria>, parent_comp_id: CompetitionId) -> Vec<Competition> {
     let mut rounds = Vec::new();
 
-    for (i, round_size) in teams_in_rounds.iter().enumerate() {
+    for (i, plan) in round_plans.iter().enumerate() {
         let mut round = Competition::default();
         round.parent_comp_id = parent_comp_id;
-        round.min_no_of_teams = *round_size;
+        round.min_no_of_teams = plan.teams;
         round.rank_criteria = rank_criteria.clone();
 
         // Give the round a name from predefined options, or a default one.
         match i < round_names.len() {
             true => round.name = round_names[i].to_string(),
-            _ => assign_default_name(&mut round, i, teams_in_rounds.len())
+            _ => assign_default_name(&mut round, i, round_plans.len())
         };
 
+        let mut knockout_format = get_from_index_or_last(&formats, i);
+        knockout_format.bracket_type = bracket_type.clone();
+        knockout_format.entering_teams = plan.entering_teams;
+
         round.format = format::Format::build(
             None,
-            Some(KnockoutRoundFormat::build(
-                get_from_index_or_last(&wins_required, i)
-            )),
+            Some(knockout_format),
             get_from_index_or_last(&match_rules, i)
         );
 
@@ -83,6 +174,25 @@ fn create_rounds(round_names: Vec<&str>, match_rules: Vec<match_event::Rules>, w
     return rounds;
 }
 
+// Create a placement round, with the rules and the time window of the main bracket round played alongside it.
+fn create_placement_round(main_round: &Competition, plan: &RoundPlan, placement: u8, round_index: usize, total_rounds: usize) -> Competition {
+    let mut round = main_round.clone();
+    round.min_no_of_teams = plan.teams;
+
+    // For example, "3rd Place Game" or "5th Place Semi Final".
+    assign_default_name(&mut round, round_index, total_rounds);
+    round.name = match round.name.as_str() {
+        "Final" => format!("{} Place Game", placement.to_ordinal_string()),
+        _ => format!("{} Place {}", placement.to_ordinal_string(), round.name)
+    };
+
+    let knockout_format = round.format.as_mut().unwrap().knockout_round.as_mut().unwrap();
+    knockout_format.entering_teams = plan.entering_teams;
+    knockout_format.placement = placement;
+
+    return round;
+}
+
 // Get a generic name for a knockout round based on how many teams it has.
 fn assign_default_name(round: &mut Competition, round_index: usize, total_rounds: usize) {
     let rounds_left = total_rounds - round_index;
@@ -149,43 +259,164 @@ fn get_from_index_or_last<T: Clone>(vector: &[T], index: usize) -> T {
     }
 }
 
-// Finalise the rounds.
-fn finalise_rounds(parent_comp: &mut Competition, rounds: &mut Vec<Competition>, today: &Date) {
-    let rounds_clone = rounds.clone();
-    let mut connections = vec![0; rounds.len()];
+// Link the rounds of a bracket so that the winners go to the next round.
+// The teams eliminated from a round play placement rounds of their own, if they are playing for positions up to placement_games_to.
+// The placement rounds are added after the rounds their teams come from, and played alongside the main bracket rounds that follow.
+fn link_bracket(rounds: &mut Vec<Competition>, links: &mut Vec<RoundLink>, main_rounds: usize, first_index: usize, first_stage: usize, plans: &[RoundPlan], best_position: u8, placement_games_to: u8) {
+    for (i, plan) in plans.iter().enumerate() {
+        let index = first_index + i;
+        let winners = plan.teams / 2;
+        if i + 1 < plans.len() {
+            links.push(RoundLink { from: index, positions: [1, winners], to: index + 1 });
+        }
+
+        // The eliminated teams finish behind every team still in the bracket.
+        let teams_left = winners as u16 + plans[i + 1..].iter().map(|a| a.entering_teams as u16).sum::<u16>();
+        if best_position as u16 + teams_left > placement_games_to as u16 { continue; }
+        let placement = convert::int::<u16, u8>(best_position as u16 + teams_left);
+
+        // A single eliminated team has nothing to play for.
+        let placement_plans = match plan_rounds(winners, &[], 1) {
+            Ok(a) => a,
+            Err(_) => continue
+        };
+
+        let placement_index = rounds.len();
+        let stage = first_stage + i + 1;
+        for (j, placement_plan) in placement_plans.iter().enumerate() {
+            let main_round = &rounds[(stage + j).min(main_rounds - 1)];
+            let round = create_placement_round(main_round, placement_plan, placement, j, placement_plans.len());
+            rounds.push(round);
+        }
+
+        // The eliminated teams are ranked after the winners, and the best of them get byes to the later placement rounds.
+        let mut position = winners + 1;
+        for (j, placement_plan) in placement_plans.iter().enumerate().rev() {
+            if placement_plan.entering_teams == 0 { continue; }
 
+            links.push(RoundLink { from: index, positions: [position, position + placement_plan.entering_teams - 1], to: placement_index + j });
+            position += placement_plan.entering_teams;
+        }
+
+        link_bracket(rounds, links, main_rounds, placement_index, stage, &placement_plans, placement, placement_games_to);
+    }
+}
+
+// Finalise the rounds.
+fn finalise_rounds(parent_comp: &mut Competition, rounds: &mut Vec<Competition>, links: &[RoundLink], today: &Date) {
     let teams = Vec::new();
-    for (i, round) in rounds.iter_mut().enumerate() {
+    for round in rounds.iter_mut() {
         round.save_new(&teams, today);
         parent_comp.child_comp_ids.push(round.id);
+    }
 
-        if i == rounds_clone.len() - 1 { continue; }
+    // The teams are sent onwards with their seeds, so that the higher seeds keep the home advantage.
+    for link in links.iter() {
+        let comp_to_connect = rounds[link.to].id;
+        rounds[link.from].connections.push(CompConnection::build(link.positions, comp_to_connect, Seed::Preserve, false));
+    }
 
-        // Doing connections.
-        create_connections(i.clone(), round, &rounds_clone, &mut connections);
+    for round in rounds.iter() {
         round.save();
     }
 
     parent_comp.save();
 }
 
-// Create connections between knockout rounds.
-fn create_connections(i: usize, round: &mut Competition, rounds: &[Competition], connections: &mut Vec<u8>) {
-    let mut advancing_teams = convert::u8_to_i8(round.min_no_of_teams / 2);
 
-    let mut last;
-    let mut first;
-    let mut iteration = 0;
-    while advancing_teams > 0 {
-        iteration += 1;
-        let space_in_next_round = rounds[i].min_no_of_teams - connections[i];
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(teams: u8, entering_teams: u8) -> RoundPlan {
+        RoundPlan { teams: teams, entering_teams: entering_teams }
+    }
+
+    // Check that every team enters the bracket once, and that each round is fed by the one before it.
+    fn assert_consistent(plans: &[RoundPlan], no_of_teams: u8, teams_at_end: u8) {
+        let entering_teams: u16 = plans.iter().map(|a| a.entering_teams as u16).sum();
+        assert_eq!(entering_teams, no_of_teams as u16, "{no_of_teams} teams to {teams_at_end}: {plans:?}");
+
+        let mut advancing_teams = 0;
+        for plan in plans.iter() {
+            assert_eq!(plan.teams % 2, 0, "{no_of_teams} teams to {teams_at_end}: {plans:?}");
+            assert_eq!(plan.teams, advancing_teams + plan.entering_teams, "{no_of_teams} teams to {teams_at_end}: {plans:?}");
+            advancing_teams = plan.teams / 2;
+        }
 
-        last = advancing_teams;
-        first = (advancing_teams - convert::u8_to_i8(space_in_next_round) + 1).clamp(1, i8::MAX);
-        let added_teams = last - first + 1;
+        assert_eq!(advancing_teams, teams_at_end, "{no_of_teams} teams to {teams_at_end}: {plans:?}");
+    }
 
-        round.connections.push(CompConnection::build([first as u8, last as u8], round.id + iteration, Seed::Preserve, false));
-        connections[i] += added_teams as u8;
-        advancing_teams -= added_teams;
+    #[test]
+    fn top_seeds_get_byes() {
+        assert_eq!(plan_rounds(10, &[], 1), Ok(vec![plan(4, 4), plan(8, 6), plan(4, 0), plan(2, 0)]));
+        assert_eq!(plan_rounds(16, &[], 1), Ok(vec![plan(16, 16), plan(8, 0), plan(4, 0), plan(2, 0)]));
+        assert_eq!(plan_rounds(12, &[8], 2), Ok(vec![plan(8, 8), plan(8, 4), plan(4, 0)]));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn every_field_makes_a_consistent_bracket() {
+        for teams_at_end in 1..=8 {
+            for no_of_teams in teams_at_end * 2..=128 {
+                let plans = plan_rounds(no_of_teams, &[], teams_at_end).unwrap();
+                assert_consistent(&plans, no_of_teams, teams_at_end);
+            }
+        }
+    }
+
+    #[test]
+    fn impossible_setups_are_errors() {
+        assert_eq!(plan_rounds(8, &[], 0), Err(BracketError::NoTeamsAtEnd));
+        assert_eq!(plan_rounds(3, &[], 2), Err(BracketError::TooFewTeams(3, 2)));
+        assert_eq!(plan_rounds(10, &[3], 1), Err(BracketError::InvalidRound(3)));
+        assert_eq!(plan_rounds(10, &[8, 2], 1), Err(BracketError::PlayInTooSmall(2, 4)));
+        assert_eq!(plan_rounds(6, &[8], 1), Err(BracketError::PlayInTooLarge(8, 6)));
+        assert_eq!(plan_rounds(10, &[2], 1), Err(BracketError::NoFullBracket(9, 1)));
+    }
+
+    // Create and link the rounds of a bracket the way build does, without saving them.
+    fn build_bracket(no_of_teams: u8, placement_games_to: u8) -> (Vec<Competition>, Vec<RoundLink>) {
+        let plans = plan_rounds(no_of_teams, &[], 1).unwrap();
+        let mut rounds = create_rounds(Vec::new(), vec![match_event::Rules::build(3, 1200, 0, true, 0.0)], vec![KnockoutRoundFormat::build(1)], BracketType::Fixed, &plans, Vec::new(), 0);
+        let mut links = Vec::new();
+        link_bracket(&mut rounds, &mut links, plans.len(), 0, 0, &plans, 1, placement_games_to);
+
+        return (rounds, links);
+    }
+
+    fn get_names(rounds: &[Competition]) -> Vec<&str> {
+        rounds.iter().map(|a| a.name.as_str()).collect()
+    }
+
+    fn get_links(links: &[RoundLink]) -> Vec<(usize, [u8; 2], usize)> {
+        links.iter().map(|a| (a.from, a.positions, a.to)).collect()
+    }
+
+    #[test]
+    fn third_place_game_is_played_by_semi_final_losers() {
+        let (rounds, links) = build_bracket(8, 3);
+
+        assert_eq!(get_names(&rounds), vec!["Quarter Final", "Semi Final", "Final", "3rd Place Game"]);
+        assert_eq!(get_links(&links), vec![(0, [1, 4], 1), (1, [1, 2], 2), (1, [3, 4], 3)]);
+        assert_eq!(rounds[3].format.as_ref().unwrap().knockout_round.as_ref().unwrap().placement, 3);
+    }
+
+    #[test]
+    fn placement_rounds_decide_every_position() {
+        let (rounds, links) = build_bracket(8, 8);
+
+        assert_eq!(get_names(&rounds), vec![
+            "Quarter Final", "Semi Final", "Final",
+            "5th Place Semi Final", "5th Place Game", "7th Place Game",
+            "3rd Place Game"
+        ]);
+        assert_eq!(get_links(&links), vec![
+            (0, [1, 4], 1), (0, [5, 8], 3),
+            (3, [1, 2], 4), (3, [3, 4], 5),
+            (1, [1, 2], 2), (1, [3, 4], 6)
+        ]);
+
+        let placements: Vec<u8> = rounds.iter().map(|a| a.format.as_ref().unwrap().knockout_round.as_ref().unwrap().placement).collect();
+        assert_eq!(placements, vec![0, 0, 0, 5, 5, 7, 3]);
+    }
+}
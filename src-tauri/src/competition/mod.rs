@@ -2,6 +2,9 @@
 pub mod season;
 pub mod format;
 pub mod knockout_generator;
+pub mod contract_rules;
+pub mod roster_rules;
+pub mod schedule_rules;
 
 use std::{cmp::Ordering, iter::zip};
 
@@ -10,9 +13,9 @@ use serde::Serialize;
 use serde_json::json;
 use time::Date;
 
-use crate::{competition::season::{Season, ranking::{RankCriteria, get_sort_functions}, team::TeamCompData}, database::{COMPETITIONS, SEASONS}, team::Team, time::{AnnualWindow, db_string_to_date}, types::{CompetitionId, TeamId, convert}};
+use crate::{competition::season::{Season, knockout_round::PairId, ranking::{RankCriteria, get_sort_functions}, team::TeamCompData}, database::{COMPETITIONS, IDS, IdKind, SEASONS}, team::Team, time::{AnnualWindow, db_string_to_date}, types::{CompetitionId, TeamId, convert}};
 
-use self::format::Format;
+use self::{contract_rules::ContractRules, format::Format, roster_rules::RosterRules, schedule_rules::ScheduleRules};
 
 #[derive(Debug, PartialEq)]
 #[derive(Default, Clone, Serialize)]
@@ -36,6 +39,9 @@ pub struct Competition {
     pub child_comp_ids: Vec<CompetitionId>,
     pub parent_comp_id: CompetitionId,
     pub competition_type: Type,
+    pub contract_rules: ContractRules,
+    pub roster_rules: RosterRules,
+    schedule_rules: Option<ScheduleRules>,  // None if the competition follows the rules of its parent.
 }
 
 // Basics.
@@ -92,14 +98,14 @@ impl Competition {
 
     // Save a competition to the database for the first time.
     fn save_new(&mut self, teams: &[Team], today: &Date) {
-        self.create_id(COMPETITIONS.lock().unwrap().len() + 1);
+        self.create_id(IDS.lock().unwrap().next(IdKind::Competition));
         self.save();
 
         // Let's create a seasons entry for this competition so we never have to check for its existence.
         SEASONS.lock().unwrap().insert(self.id, Vec::new());
 
         // Create and save the first season.
-        let team_ids: Vec<u8> = teams.iter().map(|a| a.id).collect();
+        let team_ids: Vec<TeamId> = teams.iter().map(|a| a.id).collect();
         self.create_new_season(&team_ids, today);
     }
 
@@ -108,6 +114,24 @@ impl Competition {
         COMPETITIONS.lock().unwrap().insert(self.id, self.clone());
     }
 
+    // Set the contract rules of the competition and save it.
+    pub fn set_contract_rules(&mut self, contract_rules: ContractRules) {
+        self.contract_rules = contract_rules;
+        self.save();
+    }
+
+    // Set the roster rules of the competition and save it.
+    pub fn set_roster_rules(&mut self, roster_rules: RosterRules) {
+        self.roster_rules = roster_rules;
+        self.save();
+    }
+
+    // Set the schedule rules of the competition and save it.
+    pub fn set_schedule_rules(&mut self, schedule_rules: ScheduleRules) {
+        self.schedule_rules = Some(schedule_rules);
+        self.save();
+    }
+
     fn get_parent(&self) -> Option<Competition> {
         // Get the parent of this competition.
         return Competition::fetch_from_db_option(&self.parent_comp_id);
@@ -174,11 +198,71 @@ impl Competition {
         }
     }
 
+    // Get the best final position the knockout round is played for, or 0 if the competition is not a placement round.
+    pub fn get_placement(&self) -> u8 {
+        match self.format.as_ref().and_then(|a| a.knockout_round.as_ref()) {
+            Some(knockout_round) => knockout_round.placement,
+            None => 0
+        }
+    }
+
+    // Get how many teams advance from this competition to other competitions.
+    fn get_advancing_spots(&self) -> u8 {
+        self.connections.iter().map(|a| a.teams_from_positions[1]).max().unwrap_or(0)
+    }
+
+    // Get how many teams of the first round robin stage qualify for the next stage, or 0 if none do.
+    pub fn get_playoff_spots(&self) -> u8 {
+        for id in self.child_comp_ids.iter() {
+            let child_comp = Competition::fetch_from_db(id);
+            if child_comp.get_round_robin_format().is_some() {
+                return child_comp.get_advancing_spots();
+            }
+        }
+
+        return 0;
+    }
+
+    // Get the rules for when the competition's games are played.
+    // Stages without rules of their own follow the parent competition.
+    pub fn get_schedule_rules(&self) -> ScheduleRules {
+        match &self.schedule_rules {
+            Some(rules) => rules.clone(),
+            None if self.parent_comp_id != 0 => Competition::fetch_from_db(&self.parent_comp_id).get_schedule_rules(),
+            _ => ScheduleRules::default()
+        }
+    }
+
+    // Get the child competitions the teams are drawn into when the season begins.
+    // Round robin stages at the start are groups played side by side, otherwise the first stage takes everyone.
+    fn get_group_ids(&self) -> Vec<CompetitionId> {
+        let ids: Vec<CompetitionId> = self.child_comp_ids.iter()
+            .take_while(|id| Competition::fetch_from_db(id).get_round_robin_format().is_some())
+            .cloned().collect();
+
+        if ids.is_empty() {
+            return self.child_comp_ids.iter().take(1).cloned().collect();
+        }
+
+        return ids;
+    }
+
+    // Check if the teams of the competition qualify through other competitions.
+    // Otherwise the same teams take part every season.
+    pub fn has_qualification(&self) -> bool {
+        COMPETITIONS.lock().unwrap().values().any(|a| a.connections.iter().any(|b| b.comp_to_connect == self.id))
+    }
+
     // Get the current season of the competition.
-    fn get_current_season(&self) -> Season {
+    pub fn get_current_season(&self) -> Season {
         Season::fetch_from_db(&self.id, self.get_seasons_amount() - 1)
     }
 
+    // Check if the current season of the competition is over, without cloning the season.
+    pub fn is_current_season_over(&self) -> bool {
+        Season::read_from_db(&self.id, self.get_seasons_amount() - 1, |a| a.is_over)
+    }
+
     // Get the teams in the competition's current season.
     pub fn get_teams(&self) -> Vec<Team> {
         self.get_current_season().get_teams()
@@ -306,13 +390,27 @@ impl Competition {
         let mut upcoming_games = Vec::new();
         let mut played_games = Vec::new();
         let mut rounds = Vec::new();
+        let mut team_ids = Vec::new();
         for (season, comp) in zip(child_seasons.iter_mut(), child_comps.iter_mut()) {
+            let knockout_round = season.knockout_round.as_ref().unwrap();
+            let mut round = knockout_round.get_comp_screen_json(comp, &season.get_all_games());
+            round["name"] = json!(comp.name);
+            rounds.push(round);
+
+            for pair in knockout_round.pairs.iter() {
+                for id in [pair.home.team_id, pair.away.team_id] {
+                    if !team_ids.contains(&id) { team_ids.push(id); }
+                }
+            }
+
             upcoming_games.append(&mut season.upcoming_games);
             played_games.append(&mut season.played_games);
+        }
 
-            let mut round = season.knockout_round.as_ref().unwrap().get_comp_screen_json();
-            round["name"] = json!(comp.name);
-            rounds.push(round);
+        // The path through the bracket of every team, so that the screen can show it.
+        let mut paths = serde_json::Map::new();
+        for id in team_ids {
+            paths.insert(id.to_string(), json!(self.get_bracket_path(id)));
         }
 
         // Upcoming games with next last.
@@ -326,9 +424,39 @@ impl Competition {
         comp_json["season"]["upcoming_games"] = upcoming_games.iter().map(|a| a.get_comp_screen_json()).collect();
         comp_json["season"]["played_games"] = played_games.iter().map(|a| a.get_comp_screen_json()).collect();
         comp_json["season"]["rounds"] = json!(rounds);
+        comp_json["season"]["paths"] = json!(paths);
 
         return comp_json;
     }
+
+    // Get the pairs the team has played in the main bracket of the knockout competition, from the round it entered to the latest one.
+    pub fn get_bracket_path(&self, team_id: TeamId) -> Vec<PairId> {
+        // Find the latest pair of the team in the main bracket, and follow the bracket back from there.
+        let mut next = None;
+        for id in self.child_comp_ids.iter().rev() {
+            let round = Competition::fetch_from_db(id);
+            if round.get_placement() != 0 { continue; }
+
+            let season = round.get_current_season();
+            let index = season.knockout_round.and_then(|a| a.pairs.iter().position(|b| b.has_team(team_id)));
+            if let Some(index) = index {
+                next = Some(PairId::build(*id, index));
+                break;
+            }
+        }
+
+        let mut path = Vec::new();
+        while let Some(pair_id) = next {
+            next = match pair_id.get_pair() {
+                Some(pair) => pair.get_feeder(team_id),
+                None => None
+            };
+            path.push(pair_id);
+        }
+
+        path.reverse();
+        return path;
+    }
 }
 
 // What to do with the seed of the team.
@@ -365,6 +493,12 @@ impl CompConnection {
 
     // Send teams onwards to the next stage.
     fn send_teams(&self, teams: &[TeamCompData], rng: &mut ThreadRng) {
+        let mut teamdata = self.get_teams_to_send(teams);
+        Competition::fetch_from_db(&self.comp_to_connect).setup_season(&mut teamdata, rng);
+    }
+
+    // Get the teams that go to the next stage from the ranked teams, with their seeds and stats for the next stage.
+    fn get_teams_to_send(&self, teams: &[TeamCompData]) -> Vec<TeamCompData> {
         let mut teamdata = Vec::new();
 
         for i in self.teams_from_positions[0] - 1..self.teams_from_positions[1]  {
@@ -384,6 +518,6 @@ impl CompConnection {
             teamdata.push(team);
         }
 
-        Competition::fetch_from_db(&self.comp_to_connect).setup_season(&mut teamdata, rng);
+        return teamdata;
     }
 }
\ No newline at end of file
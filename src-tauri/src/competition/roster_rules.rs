@@ -0,0 +1,117 @@
+// Rules for the rosters and lineups of the teams in a league.
+
+use serde_json::json;
+
+use crate::{person::player::{Player, position::PositionId}, types::CountryId};
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct RosterRules {
+    // How many players a team can have registered.
+    pub min_roster_size: u8,
+    pub max_roster_size: u8,
+
+    // How many players of each kind are dressed for a game.
+    pub goalkeepers: u8,
+    pub defence_pairs: u8,
+    pub forward_lines: u8,
+
+    // Players from other countries than the league's own are imports.
+    country_id: CountryId,
+
+    // None if there is no limit to them.
+    pub max_imports: Option<u8>,
+
+    // Players older than this are not allowed to play in the league.
+    // None if there is no limit to their age.
+    pub max_age: Option<u8>,
+}
+
+impl Default for RosterRules {
+    fn default() -> Self {
+        Self {
+            min_roster_size: 0,
+            max_roster_size: u8::MAX,
+            goalkeepers: 2,
+            defence_pairs: 4,
+            forward_lines: 4,
+            country_id: CountryId::default(),
+            max_imports: None,
+            max_age: None,
+        }
+    }
+}
+
+impl RosterRules {
+    pub fn build(min_roster_size: u8, max_roster_size: u8, goalkeepers: u8, defence_pairs: u8, forward_lines: u8) -> Self {
+        Self {
+            min_roster_size: min_roster_size,
+            max_roster_size: max_roster_size,
+            goalkeepers: goalkeepers,
+            defence_pairs: defence_pairs,
+            forward_lines: forward_lines,
+            ..Default::default()
+        }
+    }
+
+    // Limit the number of players from other countries.
+    pub fn set_import_limit(&mut self, country_id: CountryId, max_imports: u8) {
+        self.country_id = country_id;
+        self.max_imports = Some(max_imports);
+    }
+
+    // Limit the age of the players, as in junior leagues.
+    pub fn set_max_age(&mut self, max_age: u8) {
+        self.max_age = Some(max_age);
+    }
+
+    // Get how many players of the given position are dressed for a game.
+    pub fn get_lineup_places(&self, position: &PositionId) -> i8 {
+        let places = match position {
+            PositionId::Goalkeeper => self.goalkeepers,
+            PositionId::LeftDefender | PositionId::RightDefender => self.defence_pairs,
+            _ => self.forward_lines
+        };
+
+        return places as i8;
+    }
+
+    // Check if the player counts as an import in the league.
+    pub fn is_import(&self, player: &Player) -> bool {
+        self.max_imports.is_some() && player.person.get_country_id() != self.country_id
+    }
+
+    // Check if a player of the given age is allowed to play in the league.
+    pub fn allows_age(&self, age: i8) -> bool {
+        match self.max_age {
+            Some(max_age) => age <= max_age as i8,
+            None => true
+        }
+    }
+
+    // Get relevant information for the frontend.
+    pub fn get_package(&self) -> serde_json::Value {
+        json!({
+            "min_roster_size": self.min_roster_size,
+            "max_roster_size": self.max_roster_size,
+            "dressed_players": self.goalkeepers + self.defence_pairs * 2 + self.forward_lines * 3,
+            "max_imports": self.max_imports,
+            "max_age": self.max_age,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_age_limits_only_when_set() {
+        let mut rules = RosterRules::default();
+        assert!(rules.allows_age(40));
+
+        rules.set_max_age(20);
+        assert!(rules.allows_age(20));
+        assert!(!rules.allows_age(21));
+    }
+}
@@ -0,0 +1,69 @@
+// Rules for when the games of a competition are played.
+
+use serde_json::json;
+use time::{Date, Weekday};
+
+use crate::time::AnnualWindow;
+
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct ScheduleRules {
+    // Days of the week the games are preferably played on. Empty means any day.
+    pub game_days: Vec<Weekday>,
+
+    // How many days a team must have off between its games.
+    // 0 allows games on consecutive days.
+    pub min_rest_days: u8,
+
+    // How many times a team can play on consecutive days in a season.
+    // None if there is no limit.
+    pub max_back_to_backs: Option<u8>,
+
+    // Periods when no games are played, such as holidays.
+    blackouts: Vec<AnnualWindow>,
+}
+
+impl ScheduleRules {
+    pub fn build(game_days: Vec<Weekday>, min_rest_days: u8, max_back_to_backs: Option<u8>) -> Self {
+        Self {
+            game_days: game_days,
+            min_rest_days: min_rest_days,
+            max_back_to_backs: max_back_to_backs,
+            ..Default::default()
+        }
+    }
+
+    // Add a period when no games are played.
+    pub fn add_blackout(&mut self, blackout: AnnualWindow) {
+        self.blackouts.push(blackout);
+    }
+
+    // Check if the date is one of the preferred game days.
+    pub fn is_game_day(&self, date: &Date) -> bool {
+        self.game_days.is_empty() || self.game_days.contains(&date.weekday())
+    }
+
+    // Check if no games should be played on the date.
+    pub fn is_blackout(&self, date: &Date) -> bool {
+        self.blackouts.iter().any(|a| a.is_active(date))
+    }
+
+    // Check if a team can play this many games on consecutive days.
+    pub fn allows_back_to_backs(&self, back_to_backs: u8) -> bool {
+        match self.max_back_to_backs {
+            Some(max) => back_to_backs <= max,
+            None => true
+        }
+    }
+
+    // Get relevant information for the frontend.
+    pub fn get_package(&self) -> serde_json::Value {
+        let game_days: Vec<String> = self.game_days.iter().map(|a| a.to_string()).collect();
+        json!({
+            "game_days": game_days,
+            "min_rest_days": self.min_rest_days,
+            "max_back_to_backs": self.max_back_to_backs,
+            "blackouts": self.blackouts,
+        })
+    }
+}
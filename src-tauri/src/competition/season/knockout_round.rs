@@ -1,9 +1,11 @@
 // Knockout season parametres.
 
-use rand::{rngs::ThreadRng, Rng};
+use std::{cmp::Ordering, collections::HashSet};
+
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
 use serde_json::json;
 
-use crate::{competition::{season::{schedule_generator::assign_dates, team::TeamCompData}, Competition}, match_event::Game, time::db_string_to_date, types::TeamId};
+use crate::{competition::{format::knockout_round::{BracketType, KnockoutRound as KnockoutRoundFormat, SeriesType, TieBreaker}, season::{schedule_generator::assign_dates, team::TeamCompData}, Competition}, match_event::{Game, leg::{EarlierLegs, Leg}}, team::Team, time::db_string_to_date, types::{CompetitionId, TeamId, convert}};
 
 #[derive(Debug, serde::Serialize)]
 #[derive(Default, Clone)]
@@ -20,19 +22,52 @@ impl KnockoutRound {
     }
 
     // Get relevant information for a competition screen.
-    pub fn get_comp_screen_json(&self) -> serde_json::Value {
-        let pairs: Vec<serde_json::Value> = self.pairs.iter().map(|a | a.get_comp_screen_json()).collect();
+    pub fn get_comp_screen_json(&self, comp: &Competition, games: &[&Game]) -> serde_json::Value {
+        let format = comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap();
+        let pairs: Vec<serde_json::Value> = self.pairs.iter().enumerate().map(|(i, a)| a.get_comp_screen_json(format, PairId::build(comp.id, i), games)).collect();
         json!({
-            "pairs": pairs
+            "pairs": pairs,
+            "placement": format.placement
         })
     }
 
     // Set up a knockout round.
     // Return the games.
     pub fn setup(&mut self, teams: &[TeamCompData], start: &str, end: &str, comp: &Competition, rng: &mut ThreadRng) -> Vec<Game> {
-        self.draw_teams(teams, rng);
+        let format = comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap();
+        self.pair_teams(teams, format, |id| Team::fetch_from_db(&id).get_lineup_strength(), rng);
+        self.link_feeders(comp);
+
         let matchdays = self.generate_matchdays(comp);
-        return assign_dates(matchdays, &db_string_to_date(start), &db_string_to_date(end), comp, false, rng);
+        let mut games = assign_dates(matchdays, &db_string_to_date(start), &db_string_to_date(end), comp, false, rng);
+
+        for game in games.iter_mut() {
+            game.is_neutral_venue = format.neutral_venue;
+        }
+
+        // The games are stored with the earliest last, so the first game of each pair found from the end is the first leg.
+        if let SeriesType::TwoLegged(tie_breaker) = &format.series_type {
+            let mut first_legs_found = HashSet::new();
+            for game in games.iter_mut().rev() {
+                game.leg = match first_legs_found.insert(game.home.team_id.min(game.away.team_id)) {
+                    true => Leg::First,
+                    _ => Leg::Second(tie_breaker.clone())
+                };
+            }
+        }
+
+        return games;
+    }
+
+    // Form the pairs of the round the way the format asks for.
+    // The strengths of the teams are only needed when the teams pick their opponents.
+    pub fn pair_teams(&mut self, teams: &[TeamCompData], format: &KnockoutRoundFormat, get_strength: impl FnMut(TeamId) -> f64, rng: &mut ThreadRng) {
+        match format.bracket_type {
+            BracketType::Draw => self.draw_teams(teams, rng),
+            BracketType::Fixed => self.pair_by_bracket(teams),
+            BracketType::Reseed => self.pair_by_reseeding(teams, rng),
+            BracketType::PickOpponent => self.pair_by_picking(teams, get_strength, rng),
+        }
     }
 
     // Draw the pairs for the round.
@@ -56,6 +91,11 @@ impl KnockoutRound {
             pair.home = TeamCompData::build(home_id, draw_pots.first().unwrap().0);
             pair.away = TeamCompData::build(away_id, draw_pots.last().unwrap().0);
 
+            // The higher seed always has the home advantage.
+            if pair.away.seed < pair.home.seed {
+                std::mem::swap(&mut pair.home, &mut pair.away);
+            }
+
             // Remove pots if empty.
             for (i, pot) in draw_pots.into_iter().rev().enumerate() {
                 let index = match i {
@@ -79,7 +119,7 @@ impl KnockoutRound {
             self.pairs.push(KnockoutPair::default());
         }
 
-        let mut pots: Vec<(u8, Vec<u8>)> = Vec::new();
+        let mut pots: Vec<(u8, Vec<TeamId>)> = Vec::new();
         for team in teams.iter() {
             match pots.iter().position(|pot| pot.0 == team.seed) {
                 // Add team to an existing pot.
@@ -99,6 +139,125 @@ impl KnockoutRound {
         pot.swap_remove(rng.random_range(0..pot.len()))
     }
 
+    // Pair the teams so that the bracket stays the same through the rounds.
+    // A team's line in the bracket comes from its seed, and the winner of a pair takes the line of the higher seed in the next round.
+    // Teams with the same seed keep the order they entered in, which is the order of their groups.
+    // That way the winner of a group faces the runner-up of another group, and not its own.
+    fn pair_by_bracket(&mut self, teams: &[TeamCompData]) {
+        let mut teams = teams.to_vec();
+        teams.sort_by_key(|a| a.seed);
+        let size = teams.len();
+
+        // If the seeds do not fit a bracket, as on the first round, the bracket is decided here.
+        // The new seeds are preserved to the next rounds.
+        let mut lines: Vec<usize> = teams.iter().map(|a| get_bracket_line(a.seed, size)).collect();
+        if !size.is_power_of_two() || !(1..=size).all(|a| lines.contains(&a)) {
+            for (i, team) in teams.iter_mut().enumerate() {
+                team.seed = convert::int::<usize, u8>(i + 1);
+            }
+            lines = (1..=size).collect();
+        }
+
+        for line in get_bracket_order(size) {
+            let home = &teams[lines.iter().position(|a| *a == line).unwrap()];
+            let away = &teams[lines.iter().position(|a| *a == size + 1 - line).unwrap()];
+            self.pairs.push(KnockoutPair::build(home.clone(), away.clone()));
+        }
+    }
+
+    // Pair the best remaining seed with the worst remaining seed, the second best with the second worst, and so on.
+    fn pair_by_reseeding(&mut self, teams: &[TeamCompData], rng: &mut ThreadRng) {
+        let teams = sort_by_seed(teams, rng);
+        for i in 0..teams.len() / 2 {
+            self.pairs.push(KnockoutPair::build(teams[i].clone(), teams[teams.len() - 1 - i].clone()));
+        }
+    }
+
+    // Let the best remaining seed pick its opponent from the remaining teams, until every team has been paired.
+    // Teams pick the opponent with the weakest lineup.
+    fn pair_by_picking(&mut self, teams: &[TeamCompData], mut get_strength: impl FnMut(TeamId) -> f64, rng: &mut ThreadRng) {
+        let mut teams = sort_by_seed(teams, rng);
+        let mut strengths: Vec<f64> = teams.iter().map(|a| get_strength(a.team_id)).collect();
+
+        while teams.len() > 1 {
+            let picker = teams.remove(0);
+            strengths.remove(0);
+
+            let mut index = 0;
+            for (i, strength) in strengths.iter().enumerate() {
+                if *strength < strengths[index] {
+                    index = i;
+                }
+            }
+
+            strengths.remove(index);
+            self.pairs.push(KnockoutPair::build(picker, teams.remove(index)));
+        }
+    }
+
+    // Link the pairs to the pairs of the earlier rounds their teams advanced from.
+    // Only the latest earlier round each team played in feeds this round, and only that round is updated to know where its winners went.
+    fn link_feeders(&mut self, comp: &Competition) {
+        let earlier_rounds: Vec<CompetitionId> = get_rounds(comp).into_iter().take_while(|a| *a != comp.id).collect();
+        let mut is_linked = vec![[false; 2]; self.pairs.len()];
+
+        for id in earlier_rounds.into_iter().rev() {
+            let mut season = Competition::fetch_from_db(&id).get_current_season();
+            let round = match season.knockout_round.as_mut() {
+                Some(a) => a,
+                None => continue
+            };
+
+            let mut has_changes = false;
+            for (i, pair) in self.pairs.iter_mut().enumerate() {
+                for (side, team_id) in [pair.home.team_id, pair.away.team_id].into_iter().enumerate() {
+                    if is_linked[i][side] { continue; }
+
+                    let index = match round.pairs.iter().position(|a| a.has_team(team_id)) {
+                        Some(a) => a,
+                        None => continue
+                    };
+
+                    pair.feeders[side] = Some(PairId::build(id, index));
+                    round.pairs[index].destination = Some(BracketSlot::build(PairId::build(comp.id, i), side == 0));
+                    is_linked[i][side] = true;
+                    has_changes = true;
+                }
+            }
+
+            if has_changes {
+                season.save();
+            }
+
+            if is_linked.iter().all(|a| a[0] && a[1]) {
+                break;
+            }
+        }
+    }
+
+    // Find where the winners of the pairs went from the later rounds that have been set up.
+    // Needed when this round sets up the next round by sending its teams onwards, as this round is saved after that.
+    pub fn link_destinations(&mut self, comp: &Competition) {
+        for id in get_rounds(comp).into_iter().skip_while(|a| *a != comp.id).skip(1) {
+            let season = Competition::fetch_from_db(&id).get_current_season();
+            let round = match season.knockout_round {
+                Some(a) => a,
+                None => continue
+            };
+
+            for (i, pair) in round.pairs.iter().enumerate() {
+                for (side, feeder) in pair.feeders.iter().enumerate() {
+                    match feeder {
+                        Some(a) if a.comp_id == comp.id => {
+                            self.pairs[a.index].destination = Some(BracketSlot::build(PairId::build(id, i), side == 0));
+                        },
+                        _ => ()
+                    }
+                }
+            }
+        }
+    }
+
     // Update the teamdata for the knockout pairs.
     pub fn update_teamdata(&mut self, games: &[Game]) {
         for pair in self.pairs.iter_mut() {
@@ -114,7 +273,7 @@ impl KnockoutRound {
         for pair in self.pairs.iter_mut() {
             if pair.is_over { continue; }
 
-            let is_pair_over = pair.get_winner_loser(comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap().wins_required);
+            let is_pair_over = pair.get_winner_loser(comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap());
             if is_pair_over.is_none() {
                 is_over = false;
                 continue;
@@ -131,18 +290,80 @@ impl KnockoutRound {
     }
 }
 
+// Identifies a pair of a knockout round.
+#[derive(Debug, serde::Serialize)]
+#[derive(Default, Clone, PartialEq)]
+pub struct PairId {
+    pub comp_id: CompetitionId,
+    pub index: usize,   // Index of the pair in the round.
+}
+
+impl PairId {
+    // Build the element.
+    pub fn build(comp_id: CompetitionId, index: usize) -> Self {
+        Self {
+            comp_id: comp_id,
+            index: index
+        }
+    }
+
+    // Get the pair from the current season of the round.
+    pub fn get_pair(&self) -> Option<KnockoutPair> {
+        let season = Competition::fetch_from_db(&self.comp_id).get_current_season();
+        season.knockout_round?.pairs.get(self.index).cloned()
+    }
+}
+
+// The place the winner of a pair takes in a later round.
+#[derive(Debug, serde::Serialize)]
+#[derive(Default, Clone, PartialEq)]
+pub struct BracketSlot {
+    pub pair_id: PairId,
+    pub is_home: bool,
+}
+
+impl BracketSlot {
+    // Build the element.
+    fn build(pair_id: PairId, is_home: bool) -> Self {
+        Self {
+            pair_id: pair_id,
+            is_home: is_home
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+#[derive(Default, Clone, PartialEq)]
+pub enum PairStatus {
+    #[default]
+    NotStarted,
+    InProgress,
+    Over,
+}
+
 #[derive(Debug, serde::Serialize)]
 #[derive(Default, Clone)]
 pub struct KnockoutPair {
-    pub home: TeamCompData,
+    pub home: TeamCompData, // The higher seed.
     pub away: TeamCompData,
     is_over: bool,
+    away_goals: [u16; 2],       // Goals the home and the away team have scored in their away games.
+    shootout_winner: TeamId,    // 0 if no game of the pair has been decided in a shootout.
+
+    pub feeders: [Option<PairId>; 2],       // Pairs the home and the away team advanced from. None if the team entered the bracket in this round.
+    pub destination: Option<BracketSlot>,   // None until the winner has been placed in the next round.
 }
 
 // Basics.
 impl KnockoutPair {
     // Build the element.
+    // The higher seed always has the home advantage.
     fn build(home: TeamCompData, away: TeamCompData) -> Self {
+        let (home, away) = match away.seed < home.seed {
+            true => (away, home),
+            _ => (home, away)
+        };
+
         Self {
             home: home,
             away: away,
@@ -151,23 +372,86 @@ impl KnockoutPair {
     }
 
     // Get nice JSON for comp screen.
-    fn get_comp_screen_json(&self) -> serde_json::Value {
+    // Two-legged ties show the aggregate score, series show the wins.
+    fn get_comp_screen_json(&self, format: &KnockoutRoundFormat, id: PairId, games: &[&Game]) -> serde_json::Value {
+        let mut home = self.home.get_comp_screen_json_pair();
+        let mut away = self.away.get_comp_screen_json_pair();
+
+        if let SeriesType::TwoLegged(_) = format.series_type {
+            home["score"] = json!(self.home.goals_scored);
+            away["score"] = json!(self.away.goals_scored);
+        }
+
+        let games: Vec<serde_json::Value> = games.iter()
+            .filter(|a| a.is_team_playing(self.home.team_id) && a.is_team_playing(self.away.team_id))
+            .map(|a| a.get_comp_screen_json())
+            .collect();
+
         json!({
-            "home": self.home.get_comp_screen_json_pair(),
-            "away": self.away.get_comp_screen_json_pair()
+            "id": id,
+            "home": home,
+            "away": away,
+            "status": self.get_status(),
+            "feeders": self.feeders,
+            "destination": self.destination,
+            "games": games
         })
     }
 
+    // Check if the team is in the pair.
+    pub fn has_team(&self, team_id: TeamId) -> bool {
+        self.home.team_id == team_id || self.away.team_id == team_id
+    }
+
+    // Get how far the pair has been played.
+    pub fn get_status(&self) -> PairStatus {
+        if self.is_over { PairStatus::Over }
+        else if self.home.get_game_count() > 0 { PairStatus::InProgress }
+        else { PairStatus::NotStarted }
+    }
+
+    // Get the pair the team advanced from to this pair.
+    pub fn get_feeder(&self, team_id: TeamId) -> Option<PairId> {
+        if self.home.team_id == team_id { self.feeders[0].clone() }
+        else if self.away.team_id == team_id { self.feeders[1].clone() }
+        else { None }
+    }
+
     // Get the victor and the loser of the pair, or None if neither has won.
-    fn get_winner_loser(&self, wins_required: u8) -> Option<[TeamCompData; 2]> {
-        if self.home.get_wins() >= wins_required {
-            return Some([self.home.clone(), self.away.clone()]);
-        }
-        if self.away.get_wins() >= wins_required {
-            return Some([self.away.clone(), self.home.clone()]);
+    pub fn get_winner_loser(&self, format: &KnockoutRoundFormat) -> Option<[TeamCompData; 2]> {
+        let order = match &format.series_type {
+            SeriesType::BestOf => {
+                if self.home.get_wins() >= format.wins_required { Ordering::Greater }
+                else if self.away.get_wins() >= format.wins_required { Ordering::Less }
+                else { return None; }
+            },
+            SeriesType::TwoLegged(tie_breaker) => {
+                if self.home.get_game_count() < 2 { return None; }
+                self.get_aggregate_order(tie_breaker)
+            }
+        };
+
+        match order {
+            Ordering::Less => Some([self.away.clone(), self.home.clone()]),
+            _ => Some([self.home.clone(), self.away.clone()])
         }
+    }
+
+    // Compare the results of a two-legged tie. Greater means the home team wins.
+    // If nothing separates the teams, the higher seed goes through.
+    fn get_aggregate_order(&self, tie_breaker: &TieBreaker) -> Ordering {
+        let away_goals_order = match tie_breaker {
+            TieBreaker::AwayGoals => self.away_goals[0].cmp(&self.away_goals[1]),
+            TieBreaker::Overtime => Ordering::Equal
+        };
 
-        return None;
+        let shootout_order = if self.shootout_winner == self.home.team_id { Ordering::Greater }
+            else if self.shootout_winner == self.away.team_id { Ordering::Less }
+            else { Ordering::Equal };
+
+        return self.home.goals_scored.cmp(&self.away.goals_scored)
+            .then(away_goals_order)
+            .then(shootout_order);
     }
 
     // Remove any upcoming games from these two teams.
@@ -176,23 +460,195 @@ impl KnockoutPair {
             game.home.team_id != self.home.team_id &&
             game.home.team_id != self.away.team_id &&
             game.away.team_id != self.home.team_id &&
-            game.home.team_id != self.away.team_id
+            game.away.team_id != self.away.team_id
         });
     }
 
     // Update the teamdata for the pair.
     fn update_teamdata(&mut self, games: &[Game]) {
         for game in games.iter() {
-            if self.home.team_id == game.home.team_id {
-                self.home.update(&game.home, &game.away, game.has_overtime());
-                self.away.update(&game.away, &game.home, game.has_overtime());
-                break;
-            }
-            else if self.home.team_id == game.away.team_id {
-                self.home.update(&game.away, &game.home, game.has_overtime());
-                self.away.update(&game.home, &game.away, game.has_overtime());
-                break;
+            let is_higher_seed_home = if self.home.team_id == game.home.team_id { true }
+                else if self.home.team_id == game.away.team_id { false }
+                else { continue; };
+
+            let goals = [game.home.get_goal_amount(), game.away.get_goal_amount()];
+            let shootout = game.home.shootout_goals.cmp(&game.away.shootout_goals);
+            self.add_result(is_higher_seed_home, goals, shootout, game.has_overtime());
+            break;
+        }
+    }
+
+    // Update the pair with the result of a game.
+    // The goals and the shootout ordering are from the point of view of the home team of the game.
+    pub fn add_result(&mut self, is_higher_seed_home: bool, goals: [u16; 2], shootout: Ordering, had_overtime: bool) {
+        if is_higher_seed_home {
+            self.home.add_result(goals[0], goals[1], shootout, had_overtime);
+            self.away.add_result(goals[1], goals[0], shootout.reverse(), had_overtime);
+            self.away_goals[1] += goals[1];
+        }
+        else {
+            self.home.add_result(goals[1], goals[0], shootout.reverse(), had_overtime);
+            self.away.add_result(goals[0], goals[1], shootout, had_overtime);
+            self.away_goals[0] += goals[1];
+        }
+
+        if shootout.is_ne() {
+            self.shootout_winner = match shootout.is_gt() == is_higher_seed_home {
+                true => self.home.team_id,
+                _ => self.away.team_id
+            };
+        }
+    }
+
+    // Get the goals of the games played so far, for a second leg hosted by the higher seed or the lower seed.
+    pub fn get_earlier_legs(&self, is_higher_seed_home: bool) -> EarlierLegs {
+        let mut earlier_legs = EarlierLegs {
+            goals: [self.home.goals_scored, self.away.goals_scored],
+            away_goals: self.away_goals,
+        };
+
+        if !is_higher_seed_home {
+            earlier_legs.goals.reverse();
+            earlier_legs.away_goals.reverse();
+        }
+
+        return earlier_legs;
+    }
+}
+
+// Get the teams in the order of their seeds, the best first. Teams with the same seed are in random order.
+fn sort_by_seed(teams: &[TeamCompData], rng: &mut ThreadRng) -> Vec<TeamCompData> {
+    let mut teams = teams.to_vec();
+    teams.shuffle(rng);
+    teams.sort_by_key(|a| a.seed);
+    return teams;
+}
+
+// Get the line of the seed in a bracket with the given amount of teams.
+// For example, the winner between seeds 1 and 8 takes line 1, and the winner between seeds 4 and 5 takes line 4 in a bracket of four.
+fn get_bracket_line(seed: u8, size: usize) -> usize {
+    let mut line = seed as usize;
+    let mut bracket = line.next_power_of_two();
+    while bracket > size {
+        if line > bracket / 2 {
+            line = bracket + 1 - line;
+        }
+        bracket /= 2;
+    }
+
+    return line;
+}
+
+// Get the lines of the higher seeds of the pairs in the order they are in the bracket.
+// For example, [1, 4, 2, 3] in a bracket of eight, so that the winners of 1 v 8 and 4 v 5 meet next.
+fn get_bracket_order(size: usize) -> Vec<usize> {
+    if !size.is_power_of_two() {
+        return (1..=size / 2).collect();
+    }
+
+    let mut order = vec![1];
+    let mut bracket = 1;
+    while bracket < size / 2 {
+        bracket *= 2;
+        order = order.into_iter().flat_map(|a| [a, bracket + 1 - a]).collect();
+    }
+
+    return order;
+}
+
+// Get the IDs of the rounds of the bracket the round is a part of, in the order they are played.
+// The main bracket and each set of placement rounds are brackets of their own.
+fn get_rounds(comp: &Competition) -> Vec<CompetitionId> {
+    if comp.parent_comp_id == 0 {
+        return Vec::new();
+    }
+
+    let placement = comp.get_placement();
+    return Competition::fetch_from_db(&comp.parent_comp_id).child_comp_ids.into_iter()
+        .filter(|a| Competition::fetch_from_db(a).get_placement() == placement)
+        .collect();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_pair() -> KnockoutPair {
+        KnockoutPair::build(TeamCompData::build(1, 1), TeamCompData::build(2, 8))
+    }
+
+    fn build_game(home_id: TeamId, away_id: TeamId) -> Game {
+        Game::build(&TeamCompData::build(home_id, 0), &TeamCompData::build(away_id, 0), 1, "2026-04-01")
+    }
+
+    #[test]
+    fn clean_up_removes_every_game_of_the_pair() {
+        let pair = build_pair();
+        let mut games = vec![build_game(1, 2), build_game(2, 1), build_game(3, 4), build_game(3, 2), build_game(1, 4)];
+        pair.clean_up_games(&mut games);
+
+        let left: Vec<[TeamId; 2]> = games.iter().map(|a| [a.home.team_id, a.away.team_id]).collect();
+        assert_eq!(left, vec![[3, 4]]);
+    }
+
+    #[test]
+    fn best_of_series_end_at_wins_required() {
+        for wins_required in 1..=4 {
+            let format = KnockoutRoundFormat::build(wins_required);
+            let mut pair = build_pair();
+
+            // The lower seed wins the first games, and the higher seed wins the rest, so the series goes the distance.
+            for game in 0..format.get_maximum_matches_in_pair() {
+                assert!(pair.get_winner_loser(&format).is_none(), "best of {}: over after {game} games", format.get_maximum_matches_in_pair());
+
+                let goals = match game < wins_required - 1 {
+                    true => [1, 2],
+                    _ => [3, 1]
+                };
+                pair.add_result(true, goals, Ordering::Equal, false);
             }
+
+            let [winner, loser] = pair.get_winner_loser(&format).unwrap();
+            assert_eq!([winner.team_id, loser.team_id], [1, 2]);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn two_legged_tie_goes_to_away_goals() {
+        let format = KnockoutRoundFormat::build_two_legged(TieBreaker::AwayGoals);
+        let mut pair = build_pair();
+
+        // 1-1 away, then 0-0 at home.
+        pair.add_result(false, [1, 1], Ordering::Equal, false);
+        assert!(pair.get_winner_loser(&format).is_none());
+        pair.add_result(true, [0, 0], Ordering::Equal, false);
+
+        let [winner, _] = pair.get_winner_loser(&format).unwrap();
+        assert_eq!(winner.team_id, 1);
+
+        // The lower seed wins 2-3 away after losing 1-0 at home.
+        let mut pair = build_pair();
+        pair.add_result(false, [0, 1], Ordering::Equal, false);
+        pair.add_result(true, [2, 3], Ordering::Equal, false);
+
+        let [winner, _] = pair.get_winner_loser(&format).unwrap();
+        assert_eq!(winner.team_id, 2);
+    }
+
+    #[test]
+    fn fixed_bracket_keeps_groups_apart() {
+        // The winners and the runners-up of two groups, as they enter the playoffs.
+        let teams = vec![TeamCompData::build(1, 1), TeamCompData::build(2, 2), TeamCompData::build(3, 1), TeamCompData::build(4, 2)];
+        let groups = [[1, 2], [3, 4]];
+
+        let mut round = KnockoutRound::build();
+        round.pair_by_bracket(&teams);
+
+        let pairs: Vec<[TeamId; 2]> = round.pairs.iter().map(|a| [a.home.team_id, a.away.team_id]).collect();
+        assert_eq!(pairs, vec![[1, 4], [3, 2]]);
+        for pair in pairs {
+            assert!(!groups.iter().any(|a| a.contains(&pair[0]) && a.contains(&pair[1])), "{:?} are from the same group", pair);
+        }
+    }
+}
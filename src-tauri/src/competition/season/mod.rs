@@ -3,13 +3,17 @@ pub mod team;
 pub mod round_robin;
 pub mod knockout_round;
 pub mod ranking;
+pub mod projection;
 mod schedule_generator;
+mod series_validator;
 
-use rand::rngs::ThreadRng;
+use std::{collections::{HashMap, HashSet}, iter::zip, sync::Arc};
+
+use rand::{rngs::ThreadRng, seq::SliceRandom};
 use serde_json::json;
 use time::Date;
 
-use crate::{competition::{Competition, season::{knockout_round::KnockoutRound as KnockoutRoundSeason, round_robin::RoundRobin as RoundRobinSeason, team::TeamCompData}}, database::SEASONS, match_event::Game, team::Team, time::{date_to_db_string, db_string_to_date}, types::{CompetitionId, TeamId, convert}};
+use crate::{competition::{Competition, season::{knockout_round::KnockoutRound as KnockoutRoundSeason, round_robin::RoundRobin as RoundRobinSeason, team::TeamCompData}}, database::{self, SEASONS}, match_event::{Game, SimMode, prediction::{GameStrength, Prediction}}, team::Team, time::{date_to_db_string, db_string_to_date}, types::{CompetitionId, TeamId, convert}};
 
 #[derive(Debug)]
 #[derive(Default, Clone)]
@@ -18,13 +22,13 @@ pub struct Season {
     comp_id: CompetitionId,
     name: String,   // Years during which the season takes place.
     pub teams: Vec<TeamCompData>,
-    start_date: String,
+    pub start_date: String,
     pub end_date: String,
     pub round_robin: Option<RoundRobinSeason>,
     pub knockout_round: Option<KnockoutRoundSeason>,
 
     pub upcoming_games: Vec<Game>,  // Upcoming games are stored with earliest LAST.
-    pub played_games: Vec<Game>,    // Played games are stored with earliest FIRST.
+    pub played_games: Vec<Arc<Game>>,   // Played games are stored with earliest FIRST. Shared, so that cloning a season does not clone them.
 
     // Helper for easily checking if the season is over.
     pub is_over: bool,
@@ -79,8 +83,19 @@ impl Season {
     }
 
     pub fn fetch_from_db(comp_id: &CompetitionId, index: usize) -> Self {
-        SEASONS.lock().unwrap().get(comp_id)
-            .expect(&format!("no Competition with id {comp_id}"))[index].clone()
+        Self::read_from_db(comp_id, index, Self::clone)
+    }
+
+    // Look at the season in the database without cloning it.
+    // The seasons stay locked meanwhile, so the function must not access them.
+    pub fn read_from_db<R>(comp_id: &CompetitionId, index: usize, f: impl FnOnce(&Self) -> R) -> R {
+        database::read_entry(&SEASONS, comp_id, |a| f(&a[index])).unwrap_or_else(|| panic!("no Competition with id {comp_id}"))
+    }
+
+    // Change the season in the database without cloning it.
+    // The same rules apply as when reading the season.
+    pub fn update_in_db<R>(comp_id: &CompetitionId, index: usize, f: impl FnOnce(&mut Self) -> R) -> R {
+        database::update_entry(&SEASONS, comp_id, |a| f(&mut a[index])).unwrap_or_else(|| panic!("no Competition with id {comp_id}"))
     }
 
     // Save a season to the database for the first time.
@@ -121,13 +136,44 @@ impl Season {
                 serde_json::Value::Null
             }
             else {
-                self.knockout_round.as_ref().unwrap().get_comp_screen_json()
+                self.knockout_round.as_ref().unwrap().get_comp_screen_json(comp, &self.get_all_games())
             },
             "upcoming_games": upcoming_games,
-            "played_games": played_games
+            "played_games": played_games,
+            "schedule_rules": if comp.format.is_none() {
+                serde_json::Value::Null
+            }
+            else {
+                comp.get_schedule_rules().get_package()
+            },
+            "schedule_report": if comp.format.is_none() {
+                serde_json::Value::Null
+            }
+            else {
+                self.get_schedule_report(comp).get_package()
+            }
         })
     }
 
+    // Get the IDs of the teams that are still playing in any competition.
+    // A team stays in a stage until its season is over, unless it is knocked out.
+    // Teams that advance are sent to the next stage as soon as their stage is over, even if its games are not yet scheduled.
+    pub fn get_teams_still_playing(today: &Date) -> HashSet<TeamId> {
+        SEASONS.lock().unwrap().values()
+            .filter_map(|a| a.last())
+            .filter(|a| (a.round_robin.is_some() || a.knockout_round.is_some()) && !a.is_over && db_string_to_date(&a.start_date) <= *today)
+            .flat_map(|a| a.teams.iter().map(|b| b.team_id).filter(|id| !a.has_eliminated(*id)))
+            .collect()
+    }
+
+    // Check if the team has been knocked out of the season.
+    fn has_eliminated(&self, team_id: TeamId) -> bool {
+        match self.knockout_round.as_ref() {
+            Some(a) => a.eliminated_teams.iter().any(|b| b.team_id == team_id),
+            None => false
+        }
+    }
+
     // Get all teams participating in the season.
     pub fn get_teams(&self) -> Vec<Team> {
         self.teams.iter().map(|a | Team::fetch_from_db(&a.team_id)).collect()
@@ -157,17 +203,64 @@ impl Season {
 
         // In this case the competition must have child competitions, so set them up instead.
         else {
-            let mut teams = Vec::new();
-            for (i, id) in comp.child_comp_ids.iter().enumerate() {
-                if i == 0 {
-                    // Set up all the teams here if the child competition is the first one.
-                    // Teams that cannot be added will go to the next rounds.
-                    // Does not support group competitions yet.
-                    teams = self.teams.clone();
-                }
-                Competition::fetch_from_db(id).setup_season(&mut teams, rng);
+            self.distribute_teams(comp, rng, |child_comp, teams, rng| child_comp.setup_season(teams, rng));
+        }
+    }
+
+    // Distribute the teams of a parent competition to its child competitions, and set up each child with the teams it gets.
+    // The children take the teams they need, and leave the rest.
+    fn distribute_teams(&self, comp: &Competition, rng: &mut ThreadRng, mut set_up: impl FnMut(&Competition, &mut Vec<TeamCompData>, &mut ThreadRng)) {
+        // The teams are drawn into the groups of the first stage.
+        let group_ids = comp.get_group_ids();
+        let mut groups = self.draw_groups(group_ids.len(), rng);
+        for (id, teams) in zip(group_ids.iter(), groups.iter_mut()) {
+            set_up(&Competition::fetch_from_db(id), teams, rng);
+        }
+
+        // Teams that cannot be added will go to the next rounds.
+        // Knockout rounds only take the teams entering the bracket in them, which are the lowest seeds left.
+        // Placement rounds get their teams from the main bracket.
+        let mut teams = groups.concat();
+        for id in comp.child_comp_ids.iter().skip(group_ids.len()) {
+            let child_comp = Competition::fetch_from_db(id);
+            if child_comp.get_placement() != 0 { continue; }
+
+            let mut entering_teams = match child_comp.format.as_ref().and_then(|a| a.knockout_round.as_ref()) {
+                Some(knockout_round) if knockout_round.entering_teams != 0 => {
+                    teams.split_off(teams.len().saturating_sub(knockout_round.entering_teams as usize))
+                },
+                _ => std::mem::take(&mut teams)
+            };
+
+            set_up(&child_comp, &mut entering_teams, rng);
+            teams.append(&mut entering_teams);
+        }
+    }
+
+    // Draw the teams of the season into groups.
+    // Teams with similar seeds go to the same pot, and each group gets one team from every pot.
+    fn draw_groups(&self, amount: usize, rng: &mut ThreadRng) -> Vec<Vec<TeamCompData>> {
+        // A single group takes the teams in the order of their seeds, so that the lowest seeds are taken first.
+        if amount <= 1 {
+            let mut teams = self.teams.clone();
+            teams.sort_by_key(|a| a.seed);
+            return vec![teams];
+        }
+
+        let mut teams = self.teams.clone();
+        teams.shuffle(rng);
+        teams.sort_by(|a, b| a.seed.cmp(&b.seed));
+
+        let mut groups = vec![Vec::new(); amount];
+        for pot in teams.chunks(amount) {
+            let mut pot = pot.to_vec();
+            pot.shuffle(rng);
+            for (group, team) in zip(groups.iter_mut(), pot) {
+                group.push(team);
             }
         }
+
+        return groups;
     }
 
     // Set up a round robin season.
@@ -224,14 +317,13 @@ impl Season {
     }
 
     // Get all games of this season (not including sub or parent competitions).
-    pub fn get_all_games(&self) -> Vec<Game> {
-        let mut games: Vec<Game> = self.upcoming_games.iter().cloned().collect();
-        games.append(&mut self.played_games.clone());
-        return games;
+    pub fn get_all_games(&self) -> Vec<&Game> {
+        self.upcoming_games.iter().chain(self.played_games.iter().map(|a| a.as_ref())).collect()
     }
 
     // Simulate the games for this day.
-    pub fn simulate_day(&mut self, comp: &Competition, today: &Date, rng: &mut ThreadRng) {
+    // The caller chooses whether the games are played second by second or quickly.
+    pub fn simulate_day(&mut self, comp: &Competition, today: &Date, mode: SimMode, rng: &mut ThreadRng) {
         let mut games = Vec::new();
 
         while !self.upcoming_games.is_empty() {
@@ -239,7 +331,8 @@ impl Season {
 
             // Play the game if it happens today.
             if db_string_to_date(&game.date) == *today {
-                game.play(rng);
+                game.set_earlier_legs(&self.played_games);
+                game.play(mode, rng);
                 games.push(game);
             }
 
@@ -253,10 +346,56 @@ impl Season {
         if games.is_empty() { return; }
 
         self.update_teamdata(comp, &games, rng);
-        self.played_games.append(&mut games);
+        self.played_games.extend(games.into_iter().map(Arc::new));
         self.save();
     }
 
+    // Check if the season has games on the given date.
+    pub fn has_games_on(&self, date: &Date) -> bool {
+        self.upcoming_games.last().is_some_and(|a| db_string_to_date(&a.date) == *date)
+    }
+
+    // Get the games of the next matchday, as the upcoming games are stored with the earliest last.
+    fn get_next_matchday(&self) -> &[Game] {
+        let date = match self.upcoming_games.last() {
+            Some(a) => &a.date,
+            None => return &[]
+        };
+
+        let start = self.upcoming_games.iter().rposition(|a| a.date != *date).map_or(0, |i| i + 1);
+        return &self.upcoming_games[start..];
+    }
+
+    // Predict the upcoming games of the latest season of the competition with the current strengths of the teams.
+    // The games of the next matchday are predicted again every day, as the teams change until then.
+    // Later games are predicted as soon as they are scheduled, and kept until they are next.
+    // The strengths are shared between the competitions, so that each team only needs to be evaluated once a day.
+    // The games are predicted in the database, as the teams have to be evaluated before the seasons can be locked.
+    pub fn update_predictions(comp: &Competition, strengths: &mut HashMap<TeamId, GameStrength>) {
+        let index = comp.get_seasons_amount() - 1;
+        let team_ids: Vec<TeamId> = Self::read_from_db(&comp.id, index, |a| {
+            let start = a.upcoming_games.len() - a.get_next_matchday().len();
+            a.upcoming_games.iter().enumerate()
+                .filter(|(i, game)| *i >= start || game.prediction.is_none())
+                .flat_map(|(_, game)| [game.home.team_id, game.away.team_id])
+                .collect()
+        });
+        if team_ids.is_empty() { return; }
+
+        for id in team_ids {
+            strengths.entry(id).or_insert_with(|| Team::fetch_from_db(&id).get_game_strength());
+        }
+
+        let rules = &comp.format.as_ref().unwrap().match_rules;
+        Self::update_in_db(&comp.id, index, |a| {
+            let start = a.upcoming_games.len() - a.get_next_matchday().len();
+            for (i, game) in a.upcoming_games.iter_mut().enumerate() {
+                if i < start && game.prediction.is_some() { continue; }
+                game.prediction = Some(Prediction::build(&strengths[&game.home.team_id], &strengths[&game.away.team_id], rules, game.is_neutral_venue));
+            }
+        });
+    }
+
     // Check if the season has ended, and react appropriately.
     // Return whether over or not.
     pub fn check_if_over(&mut self, comp: &Competition, rng: &mut ThreadRng) -> bool {
@@ -299,5 +438,10 @@ impl Season {
         for connection in comp.connections.iter() {
             connection.send_teams(&self.teams, rng);
         }
+
+        // Sending the teams may have set up the next knockout rounds.
+        if self.knockout_round.is_some() {
+            self.knockout_round.as_mut().unwrap().link_destinations(comp);
+        }
     }
 }
\ No newline at end of file
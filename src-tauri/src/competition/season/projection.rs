@@ -0,0 +1,463 @@
+// Monte Carlo projections of how the current season of a competition ends.
+// The rest of the season is simulated thousands of times, with every game drawn from the expected goals of the teams instead of being played.
+
+use std::collections::HashMap;
+
+use rand::rngs::ThreadRng;
+use serde_json::json;
+
+use crate::{competition::{Competition, format::knockout_round::SeriesType, season::{Season, ranking::merge_rankings, team::TeamCompData}}, match_event::{leg::{EarlierLegs, Leg}, prediction::{DrawnResult, GameStrength, get_expected_goals}}, team::Team, types::{CompetitionId, TeamId, convert}};
+
+// How a team fared in the simulations.
+#[derive(Debug)]
+#[derive(Default, Clone)]
+struct TeamProjection {
+    team_id: TeamId,
+    positions: Vec<u32>,    // How many times the team finished in each position of the stage.
+    advanced: u32,          // How many times the team advanced to a later stage of the competition.
+    champion: u32,          // How many times the team won the whole competition.
+}
+
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct Projection {
+    iterations: u32,
+    advancing_spots: u8,    // How many teams of the stage advance to a later stage of the competition.
+    teams: Vec<TeamProjection>, // In the order of the current standings.
+}
+
+impl Projection {
+    // How many times the rest of the season is simulated.
+    const ITERATIONS: u32 = 5000;
+
+    // Project the final standings of a stage, along with the rest of the competition it is a part of.
+    pub fn build(comp: &Competition, rng: &mut ThreadRng) -> Self {
+        let season = comp.get_current_season();
+        if !season.has_enough_teams(comp.min_no_of_teams) { return Self::build_empty(&season); }
+
+        let mut root = comp.clone();
+        while root.parent_comp_id != 0 {
+            root = Competition::fetch_from_db(&root.parent_comp_id);
+        }
+
+        let mut simulation = Simulation::build(&root);
+        return Self::project(comp, &season, root.id, &mut simulation, rng);
+    }
+
+    // Build a projection without any simulations for the teams of the season.
+    fn build_empty(season: &Season) -> Self {
+        Self {
+            teams: season.teams.iter().map(|a| TeamProjection {
+                team_id: a.team_id,
+                positions: vec![0; season.teams.len()],
+                ..Default::default()
+            }).collect(),
+            ..Default::default()
+        }
+    }
+
+    // Project the stage by simulating the rest of the competition from its root.
+    fn project(comp: &Competition, season: &Season, root_id: CompetitionId, simulation: &mut Simulation, rng: &mut ThreadRng) -> Self {
+        let mut projection = Self::build_empty(season);
+
+        // Only the connections within the competition lead to a later stage.
+        let advancing_positions: Vec<[u8; 2]> = comp.connections.iter()
+            .filter(|a| simulation.comps.contains_key(&a.comp_to_connect))
+            .map(|a| a.teams_from_positions)
+            .collect();
+        projection.advancing_spots = advancing_positions.iter().map(|a| a[1]).max().unwrap_or(0);
+
+        for _ in 0..Self::ITERATIONS {
+            let state = simulation.simulate(root_id, rng);
+            let champion = state.seasons[&root.id].teams.first().map(|a| a.team_id);
+
+            for (i, team) in state.seasons[&comp.id].teams.iter().enumerate() {
+                let team_projection = match projection.teams.iter_mut().find(|a| a.team_id == team.team_id) {
+                    Some(a) => a,
+                    None => continue
+                };
+
+                let position = convert::int::<usize, u8>(i + 1);
+                team_projection.positions[i] += 1;
+                if advancing_positions.iter().any(|a| a[0] <= position && position <= a[1]) {
+                    team_projection.advanced += 1;
+                }
+                if champion == Some(team.team_id) {
+                    team_projection.champion += 1;
+                }
+            }
+        }
+
+        projection.iterations = Self::ITERATIONS;
+        return projection;
+    }
+
+    // Get the share of simulations for every position, advancing and winning the competition, for a competition screen.
+    pub fn get_package(&self) -> serde_json::Value {
+        let iterations = self.iterations.max(1) as f64;
+        let teams: Vec<serde_json::Value> = self.teams.iter().map(|a| json!({
+            "id": a.team_id,
+            "name": Team::fetch_from_db(&a.team_id).name,
+            "positions": a.positions.iter().map(|b| *b as f64 / iterations).collect::<Vec<f64>>(),
+            "advanced": a.advanced as f64 / iterations,
+            "champion": a.champion as f64 / iterations
+        })).collect();
+
+        json!({
+            "iterations": self.iterations,
+            "advancing_spots": self.advancing_spots,
+            "teams": teams
+        })
+    }
+}
+
+// The seasons of the competitions as they are during one simulation.
+#[derive(Debug)]
+#[derive(Default, Clone)]
+struct State {
+    seasons: HashMap<CompetitionId, Season>,            // Without the games, which are not needed.
+    games: HashMap<CompetitionId, Vec<[TeamId; 2]>>,    // Home and away teams of the round robin games left to play.
+}
+
+// A competition with all its child competitions, to simulate the rest of the season in.
+#[derive(Debug)]
+#[derive(Default)]
+struct Simulation {
+    comps: HashMap<CompetitionId, Competition>,
+    state: State,   // The seasons as they are today.
+    strengths: HashMap<TeamId, GameStrength>,
+    lineup_strengths: HashMap<TeamId, f64>, // For teams that pick their opponents.
+    expected_goals: HashMap<(CompetitionId, TeamId, TeamId), [f64; 2]>,
+}
+
+impl Simulation {
+    // Build the simulation of the current season of the competition.
+    fn build(root: &Competition) -> Self {
+        let mut simulation = Self::default();
+        simulation.add_competition(root);
+        return simulation;
+    }
+
+    // Add the competition and its child competitions to the simulation.
+    fn add_competition(&mut self, comp: &Competition) {
+        let mut season = comp.get_current_season();
+        if season.round_robin.is_some() {
+            let games = season.upcoming_games.iter().map(|a| [a.home.team_id, a.away.team_id]).collect();
+            self.state.games.insert(comp.id, games);
+        }
+
+        season.upcoming_games.clear();
+        season.played_games.clear();
+        self.state.seasons.insert(comp.id, season);
+        self.comps.insert(comp.id, comp.clone());
+
+        for id in comp.child_comp_ids.iter() {
+            self.add_competition(&Competition::fetch_from_db(id));
+        }
+    }
+
+    // Simulate the rest of the season once, and return how the seasons ended.
+    fn simulate(&mut self, root_id: CompetitionId, rng: &mut ThreadRng) -> State {
+        let mut state = self.state.clone();
+        self.play_competition(root_id, &mut state, rng);
+        return state;
+    }
+
+    // Play the rest of the competition's season, and send its teams to the next stages like the season does.
+    fn play_competition(&mut self, id: CompetitionId, state: &mut State, rng: &mut ThreadRng) {
+        let comp = self.comps[&id].clone();
+        if !state.seasons[&id].has_enough_teams(comp.min_no_of_teams) { return; }
+
+        if state.seasons[&id].round_robin.is_some() {
+            self.play_round_robin(&comp, state, rng);
+        }
+        else if state.seasons[&id].knockout_round.is_some() {
+            self.play_knockout_round(&comp, state, rng);
+        }
+
+        // Parent competitions are ranked from their stages, with the latest stage deciding first.
+        // Placement rounds do not decide the winner, so they are not played.
+        else {
+            let child_ids: Vec<CompetitionId> = comp.child_comp_ids.iter()
+                .filter(|a| self.comps[a].get_placement() == 0)
+                .cloned().collect();
+
+            for child_id in child_ids.iter() {
+                self.play_competition(*child_id, state, rng);
+            }
+
+            let ranks: Vec<Vec<TeamCompData>> = child_ids.iter()
+                .filter(|a| state.seasons[a].has_enough_teams(self.comps[a].min_no_of_teams))
+                .map(|a| state.seasons[a].teams.clone())
+                .collect();
+            state.seasons.get_mut(&id).unwrap().teams = merge_rankings(&ranks, &[]);
+        }
+
+        let teams = state.seasons[&id].teams.clone();
+        for connection in comp.connections.iter() {
+            if !self.comps.contains_key(&connection.comp_to_connect) { continue; }
+
+            let mut teamdata = connection.get_teams_to_send(&teams);
+            self.enter_teams(connection.comp_to_connect, &mut teamdata, state, rng);
+        }
+    }
+
+    // Add teams to a season that has not been set up yet, and set it up when it has enough teams.
+    // The teams that are not needed are left in the vector.
+    fn enter_teams(&mut self, id: CompetitionId, teams: &mut Vec<TeamCompData>, state: &mut State, rng: &mut ThreadRng) {
+        let comp = self.comps[&id].clone();
+        let season = state.seasons.get_mut(&id).unwrap();
+        if season.has_enough_teams(comp.min_no_of_teams) { return; }
+
+        while !teams.is_empty() && !season.has_enough_teams(comp.min_no_of_teams) {
+            season.teams.push(teams.swap_remove(teams.len() - 1));
+        }
+
+        if !season.has_enough_teams(comp.min_no_of_teams) { return; }
+
+        // The order of the teams becomes correct by reversing, as in the season setup.
+        season.teams.reverse();
+        if season.round_robin.is_some() {
+            let games = season.generate_match_pool(&comp, rng);
+            state.games.insert(id, games);
+        }
+        else if season.knockout_round.is_some() {
+            let format = comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap();
+            let lineup_strengths = &mut self.lineup_strengths;
+            let get_strength = |id| *lineup_strengths.entry(id).or_insert_with(|| Team::fetch_from_db(&id).get_lineup_strength());
+            season.knockout_round.as_mut().unwrap().pair_teams(&season.teams, format, get_strength, rng);
+        }
+        else {
+            let season = season.clone();
+            season.distribute_teams(&comp, rng, |child_comp, teams, rng| self.enter_teams(child_comp.id, teams, state, rng));
+        }
+    }
+
+    // Play the round robin games that are left.
+    fn play_round_robin(&mut self, comp: &Competition, state: &mut State, rng: &mut ThreadRng) {
+        let rules = &comp.format.as_ref().unwrap().match_rules;
+        let games = state.games.remove(&comp.id).unwrap_or_default();
+        let season = state.seasons.get_mut(&comp.id).unwrap();
+
+        for [home_id, away_id] in games {
+            let expected_goals = self.get_expected_goals(comp, home_id, away_id, false);
+            let result = DrawnResult::draw(expected_goals, rules, &Leg::Single, &EarlierLegs::default(), rng);
+
+            for team in season.teams.iter_mut() {
+                if team.team_id == home_id {
+                    team.add_result(result.goals[0], result.goals[1], result.shootout, result.had_overtime);
+                }
+                else if team.team_id == away_id {
+                    team.add_result(result.goals[1], result.goals[0], result.shootout.reverse(), result.had_overtime);
+                }
+            }
+        }
+
+        season.rank_teams(comp, rng);
+    }
+
+    // Play the pairs of the knockout round until each has a winner.
+    fn play_knockout_round(&mut self, comp: &Competition, state: &mut State, rng: &mut ThreadRng) {
+        let format = comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap();
+        let rules = &comp.format.as_ref().unwrap().match_rules;
+        let season = state.seasons.get_mut(&comp.id).unwrap();
+        let round = season.knockout_round.as_mut().unwrap();
+
+        for pair in round.pairs.iter_mut() {
+            while pair.get_winner_loser(format).is_none() {
+                let index = pair.home.get_game_count() as usize;
+                let is_higher_seed_home = format.is_higher_seed_home(index);
+                let [home_id, away_id] = match is_higher_seed_home {
+                    true => [pair.home.team_id, pair.away.team_id],
+                    _ => [pair.away.team_id, pair.home.team_id]
+                };
+
+                let leg = match &format.series_type {
+                    SeriesType::BestOf => Leg::Single,
+                    SeriesType::TwoLegged(_) if index == 0 => Leg::First,
+                    SeriesType::TwoLegged(tie_breaker) => Leg::Second(tie_breaker.clone())
+                };
+
+                let expected_goals = self.get_expected_goals(comp, home_id, away_id, format.neutral_venue);
+                let result = DrawnResult::draw(expected_goals, rules, &leg, &pair.get_earlier_legs(is_higher_seed_home), rng);
+                pair.add_result(is_higher_seed_home, result.goals, result.shootout, result.had_overtime);
+            }
+        }
+
+        round.check_if_over(comp, &mut Vec::new());
+        season.rank_teams(comp, rng);
+    }
+
+    // Get the goals the home and the away team are expected to score in a game of the competition.
+    // The teams are evaluated only once, as their lineups do not change during the simulations.
+    fn get_expected_goals(&mut self, comp: &Competition, home_id: TeamId, away_id: TeamId, is_neutral_venue: bool) -> [f64; 2] {
+        let key = (comp.id, home_id, away_id);
+        if let Some(expected_goals) = self.expected_goals.get(&key) {
+            return *expected_goals;
+        }
+
+        for id in [home_id, away_id] {
+            self.strengths.entry(id).or_insert_with(|| Team::fetch_from_db(&id).get_game_strength());
+        }
+
+        let rules = &comp.format.as_ref().unwrap().match_rules;
+        let expected_goals = get_expected_goals(&self.strengths[&home_id], &self.strengths[&away_id], rules, is_neutral_venue);
+        self.expected_goals.insert(key, expected_goals);
+        return expected_goals;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::*;
+    use crate::{competition::{CompConnection, Seed, format::{Format, knockout_round::KnockoutRound as KnockoutRoundFormat, round_robin::RoundRobin as RoundRobinFormat}, season::{knockout_round::KnockoutRound as KnockoutRoundSeason, ranking::RankCriteria, round_robin::RoundRobin as RoundRobinSeason}}, match_event::Rules};
+
+    const ROOT_ID: CompetitionId = 1;
+    const STAGE_ID: CompetitionId = 2;
+    const FINAL_ID: CompetitionId = 3;
+    const TEAM_IDS: [TeamId; 4] = [1, 2, 3, 4];
+
+    // Build a competition with a round robin stage, whose top two teams play a final.
+    // The games of the stage are played only if they are left in the simulation.
+    fn build_simulation(is_stage_over: bool) -> Simulation {
+        let mut simulation = Simulation::default();
+        let teams: Vec<TeamCompData> = TEAM_IDS.iter().enumerate().map(|(i, id)| TeamCompData::build(*id, i as u8 + 1)).collect();
+
+        let root = Competition {
+            id: ROOT_ID,
+            child_comp_ids: vec![STAGE_ID, FINAL_ID],
+            rank_criteria: vec![RankCriteria::ChildCompRanking],
+            ..Default::default()
+        };
+        let stage = Competition {
+            id: STAGE_ID,
+            parent_comp_id: ROOT_ID,
+            min_no_of_teams: 4,
+            connections: vec![CompConnection::build([1, 2], FINAL_ID, Seed::GetFromPosition, false)],
+            format: Format::build(Some(RoundRobinFormat::build(2, 0, 3, 2, 1, 1, 0)), None, Rules::build(3, 1200, 300, false, 0.0)),
+            rank_criteria: vec![RankCriteria::Points, RankCriteria::GoalDifference, RankCriteria::GoalsScored, RankCriteria::Seed],
+            ..Default::default()
+        };
+        let final_round = Competition {
+            id: FINAL_ID,
+            parent_comp_id: ROOT_ID,
+            min_no_of_teams: 2,
+            format: Format::build(None, Some(KnockoutRoundFormat::build(1)), Rules::build(3, 1200, 0, true, 0.0)),
+            rank_criteria: vec![RankCriteria::Seed],
+            ..Default::default()
+        };
+
+        let mut stage_season = Season {
+            comp_id: STAGE_ID,
+            teams: teams.clone(),
+            round_robin: Some(RoundRobinSeason::build()),
+            ..Default::default()
+        };
+
+        // The first team has won all its games and the last team has lost all of them, so the standings are clear.
+        let mut games = Vec::new();
+        for _ in 0..2 {
+            for (i, home_id) in TEAM_IDS.iter().enumerate() {
+                for away_id in TEAM_IDS[i + 1..].iter() {
+                    games.push([*home_id, *away_id]);
+                }
+            }
+        }
+
+        if is_stage_over {
+            for [home_id, away_id] in games.drain(..) {
+                for team in stage_season.teams.iter_mut() {
+                    if team.team_id == home_id {
+                        team.add_result(3, 1, Ordering::Equal, false);
+                    }
+                    else if team.team_id == away_id {
+                        team.add_result(1, 3, Ordering::Equal, false);
+                    }
+                }
+            }
+            stage_season.is_over = true;
+        }
+
+        simulation.state.games.insert(STAGE_ID, games);
+        simulation.state.seasons.insert(ROOT_ID, Season { comp_id: ROOT_ID, teams: teams, ..Default::default() });
+        simulation.state.seasons.insert(STAGE_ID, stage_season);
+        simulation.state.seasons.insert(FINAL_ID, Season { comp_id: FINAL_ID, knockout_round: Some(KnockoutRoundSeason::build()), ..Default::default() });
+
+        // The teams get weaker down the seeds, so that the projection is not even.
+        for comp_id in [STAGE_ID, FINAL_ID] {
+            for (i, home_id) in TEAM_IDS.iter().enumerate() {
+                for (j, away_id) in TEAM_IDS.iter().enumerate() {
+                    if i == j { continue; }
+                    simulation.expected_goals.insert((comp_id, *home_id, *away_id), [4.0 - i as f64 * 0.5, 4.0 - j as f64 * 0.5]);
+                }
+            }
+        }
+
+        for comp in [root, stage, final_round] {
+            simulation.comps.insert(comp.id, comp);
+        }
+
+        return simulation;
+    }
+
+    // Project the round robin stage of the simulation.
+    fn project(simulation: &mut Simulation) -> Projection {
+        let stage = simulation.comps[&STAGE_ID].clone();
+        let season = simulation.state.seasons[&STAGE_ID].clone();
+        return Projection::project(&stage, &season, ROOT_ID, simulation, &mut rand::rng());
+    }
+
+    #[test]
+    fn finished_stage_keeps_its_standings() {
+        let mut simulation = build_simulation(true);
+        let projection = project(&mut simulation);
+        assert_eq!(projection.iterations, Projection::ITERATIONS);
+        assert_eq!(projection.advancing_spots, 2);
+
+        for (i, team) in projection.teams.iter().enumerate() {
+            for (position, count) in team.positions.iter().enumerate() {
+                let expected = if position == i { projection.iterations } else { 0 };
+                assert_eq!(*count, expected, "team {} in position {}", team.team_id, position + 1);
+            }
+
+            let expected = if i < 2 { projection.iterations } else { 0 };
+            assert_eq!(team.advanced, expected, "team {}", team.team_id);
+        }
+    }
+
+    #[test]
+    fn position_shares_add_up_to_one() {
+        let mut simulation = build_simulation(false);
+        let projection = project(&mut simulation);
+
+        for team in projection.teams.iter() {
+            assert_eq!(team.positions.iter().sum::<u32>(), projection.iterations, "team {}", team.team_id);
+        }
+
+        for position in 0..TEAM_IDS.len() {
+            let total: u32 = projection.teams.iter().map(|a| a.positions[position]).sum();
+            assert_eq!(total, projection.iterations, "position {}", position + 1);
+        }
+    }
+
+    #[test]
+    fn advanced_and_champion_shares_follow_advancing_spots() {
+        let mut simulation = build_simulation(false);
+        let projection = project(&mut simulation);
+        let spots = projection.advancing_spots as usize;
+
+        for team in projection.teams.iter() {
+            assert!(team.champion <= team.advanced, "team {}: {team:?}", team.team_id);
+            assert!(team.advanced <= projection.iterations, "team {}: {team:?}", team.team_id);
+
+            // A team advances exactly when it finishes in one of the advancing spots.
+            assert_eq!(team.advanced, team.positions[..spots].iter().sum::<u32>(), "team {}", team.team_id);
+        }
+
+        // The final always has a winner.
+        assert_eq!(projection.teams.iter().map(|a| a.advanced).sum::<u32>(), projection.iterations * spots as u32);
+        assert_eq!(projection.teams.iter().map(|a| a.champion).sum::<u32>(), projection.iterations);
+    }
+}
@@ -1,6 +1,6 @@
 // Functions and methods for ranking teams within a season.
 
-use std::{cmp::Ordering, collections::HashMap};
+use std::{cmp::Ordering, collections::HashMap, iter::zip};
 
 use rand::{rngs::ThreadRng, seq::IndexedRandom};
 
@@ -150,37 +150,60 @@ impl Season {
     // Sort child competitions and determine the ranking based on them.
     fn sort_child_competitions(&mut self, comp: &Competition, rng: &mut ThreadRng) -> bool {
         let mut ranks = Vec::new();
+        let mut placement_ranks = Vec::new();
         for id in comp.child_comp_ids.iter() {
             let child_comp = Competition::fetch_from_db(id);
             let mut season = Season::fetch_from_db(id, self.index);
 
             let sorted = season.rank_teams(&child_comp, rng);
-            if sorted {
-                ranks.push(season.teams.clone());
-            }
-        }
-
-        let mut team_ranking: Vec<TeamCompData> = Vec::new();
-        for rank in ranks.iter().rev() {
-            for team in rank.iter() {
-                let mut is_added = false;
-                for ranked_team in team_ranking.iter() {
-                    if team.team_id == ranked_team.team_id {
-                        is_added = true;
-                        break;
-                    }
-                }
+            if !sorted { continue; }
 
-                if !is_added {
-                    team_ranking.push(team.clone());
-                }
-            }
+            match child_comp.get_placement() {
+                0 => ranks.push(season.teams.clone()),
+                _ => placement_ranks.push(season.teams.clone())
+            };
         }
 
+        let team_ranking = merge_rankings(&ranks, &placement_ranks);
+
         if team_ranking.len() >= self.teams.len() {
             self.teams = team_ranking;
             return true;
         }
         return false;
     }
-}
\ No newline at end of file
+}
+
+// Merge the rankings of child competitions into one, with the latest competition having the highest priority.
+pub fn merge_rankings(ranks: &[Vec<TeamCompData>], placement_ranks: &[Vec<TeamCompData>]) -> Vec<TeamCompData> {
+    let mut team_ranking: Vec<TeamCompData> = Vec::new();
+    for rank in ranks.iter().rev() {
+        for team in rank.iter() {
+            let mut is_added = false;
+            for ranked_team in team_ranking.iter() {
+                if team.team_id == ranked_team.team_id {
+                    is_added = true;
+                    break;
+                }
+            }
+
+            if !is_added {
+                team_ranking.push(team.clone());
+            }
+        }
+    }
+
+    // Placement rounds rank their teams among the positions the teams already hold.
+    // Each placement round comes after the rounds its teams come from, so the later ones decide the final positions.
+    for rank in placement_ranks.iter() {
+        let mut positions: Vec<usize> = rank.iter().filter_map(|a| team_ranking.iter().position(|b| b.team_id == a.team_id)).collect();
+        if positions.len() != rank.len() { continue; }
+
+        positions.sort();
+        for (position, team) in zip(positions, rank.iter()) {
+            team_ranking[position] = team.clone();
+        }
+    }
+
+    return team_ranking;
+}
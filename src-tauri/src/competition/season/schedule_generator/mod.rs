@@ -1,25 +1,41 @@
 // Scheduling-related methods that are valid for both Knockout and RoundRobin.
 
 mod sorting;
+pub mod report;
 
 use std::{collections::{HashMap, HashSet}, iter::zip};
 use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
-use ::time::Date;
+use ::time::{Date, Duration};
 
 use crate::{
-    competition::{Competition, format::round_robin::{MatchGenType, RoundRobin as RoundRobinFormat}, season::{Season, knockout_round::{KnockoutPair, KnockoutRound as KnockoutRoundSeason}, team::TeamCompData}}, match_event::Game, time::{date_to_db_string, db_string_to_date, get_dates}, types::{TeamId, convert}
+    competition::{Competition, schedule_rules::ScheduleRules, format::{knockout_round::KnockoutRound as KnockoutRoundFormat, round_robin::MatchGenType}, season::{Season, knockout_round::{KnockoutPair, KnockoutRound as KnockoutRoundSeason}, team::TeamCompData}}, database::SEASONS, match_event::Game, time::{date_to_db_string, db_string_to_date, get_dates}, types::{CompetitionId, TeamId, convert}
 };
 
+use self::report::ScheduleReport;
+
 impl Season {
     // Generate a match schedule for round robin stages.
     pub fn generate_schedule(&mut self, comp: &Competition, rng: &mut ThreadRng) {
         let mut match_pool = self.generate_match_pool(comp, rng);
         let matchdays = generate_matchdays(&mut match_pool, rng);
         self.upcoming_games = assign_dates(matchdays, &db_string_to_date(&self.start_date), &db_string_to_date(&self.end_date), comp, true, rng);
+
+        // Make sure the generated schedule is what the competition asked for.
+        #[cfg(debug_assertions)] {
+            let problems = comp.get_round_robin_format().unwrap().validate_schedule(self);
+            if problems.iter().any(|a| a.is_serious()) {
+                panic!("invalid match schedule in {}\n{:#?}", comp.name, problems);
+            }
+        }
+    }
+
+    // Get a report of how well the season's calendar follows the schedule rules of the competition.
+    pub fn get_schedule_report(&self, comp: &Competition) -> ScheduleReport {
+        ScheduleReport::build(&self.get_all_games(), &comp.get_schedule_rules(), &get_busy_dates(&self.comp_id))
     }
 
     // Generate matches for a round robin stage.
-    fn generate_match_pool(&self, comp: &Competition, rng: &mut ThreadRng) -> Vec<[TeamId; 2]> {
+    pub fn generate_match_pool(&self, comp: &Competition, rng: &mut ThreadRng) -> Vec<[TeamId; 2]> {
         // How many times should uncertain generations be attempted before giving up.
         const ATTEMPTS: u8 = u8::MAX;
         let round_robin = comp.format.as_ref().unwrap().round_robin.as_ref().unwrap();
@@ -27,6 +43,7 @@ impl Season {
         let matches_in_round = round_robin.get_round_length(self);
         let matches_in_full_round = matches_in_round * 2;
         let mut matches = round_robin.get_theoretical_matches_per_team(self);
+        let match_gen_type = round_robin.get_match_gen_type();
         let mut match_pool = Vec::new();
 
         // Complete rounds.
@@ -38,30 +55,16 @@ impl Season {
         // Half rounds.
         let mut prev_schedule_data = Vec::new();
         if matches >= matches_in_round {
-            prev_schedule_data = self.attempt_irregular_generation(matches_in_round, &mut match_pool, prev_schedule_data, ATTEMPTS, rng);
-
-            // If unsuccessful, move on to the next part with one match less.
-            if prev_schedule_data.len() == 0 {
-                matches = matches_in_round - 1;
-
-                // Making sure we are not trying the impossible.
-                if self.teams.len() % 2 != 0 && matches % 2 != 0 {
-                    matches -= 1;
-                }
-            }
-
-            // Otherwise, just move on!
-            else {
-                matches -= matches_in_round;
-            }
+            prev_schedule_data = self.generate_half_round(&mut match_pool, rng);
+            matches -= matches_in_round;
         }
 
         // Handle the leftover matches.
         while matches > 0 {
-            prev_schedule_data = self.attempt_irregular_generation(matches, &mut match_pool, prev_schedule_data, ATTEMPTS, rng);
+            let schedule_data = self.attempt_irregular_generation(matches, &mut match_pool, prev_schedule_data.clone(), &match_gen_type, ATTEMPTS, rng);
 
             // If unsuccessful, try again with one match less.
-            if prev_schedule_data.len() == 0 {
+            if schedule_data.len() == 0 {
                 matches -= 1;
             }
 
@@ -90,30 +93,60 @@ impl Season {
         }
     }
 
+    // Generate matches where every team plays every other once, with home and away matches balanced.
+    // Return the schedule data of the generated matches.
+    fn generate_half_round(&self, match_pool: &mut Vec<[TeamId; 2]>, rng: &mut ThreadRng) -> Vec<TeamScheduleData> {
+        let mut schedule_data = TeamScheduleData::generate(&self.teams);
+        schedule_data.shuffle(rng);
+
+        // The teams are placed in a circle, and each team hosts the teams following it in the first half of the circle.
+        // With an even amount of teams, the last team is left out of the circle and handled separately.
+        let circle_length = schedule_data.len() - (1 - schedule_data.len() % 2);
+        for home_index in 0..circle_length {
+            for offset in 1..=(circle_length / 2) {
+                let away_index = (home_index + offset) % circle_length;
+                add_match(&mut schedule_data, home_index, away_index, match_pool);
+            }
+        }
+
+        // The last team hosts every other team of the circle.
+        if circle_length < schedule_data.len() {
+            let last_index = circle_length;
+            for index in 0..circle_length {
+                if index % 2 == 0 {
+                    add_match(&mut schedule_data, index, last_index, match_pool);
+                }
+                else {
+                    add_match(&mut schedule_data, last_index, index, match_pool);
+                }
+            }
+        }
+
+        return schedule_data;
+    }
+
     // Attempt to generate an irregular schedule of matches.
     // Return team schedule datas if successful. Otherwise return an empty vector.
     fn attempt_irregular_generation(
         &self, matches_per_team: u8,
         match_pool: &mut Vec<[TeamId; 2]>,
         prev_schedule_data: Vec<TeamScheduleData>,
+        match_gen_type: &MatchGenType,
         attempts: u8, rng: &mut ThreadRng
     ) -> Vec<TeamScheduleData> {
         let prev_schedule_map = TeamScheduleData::vector_to_hashmap(prev_schedule_data);
 
-        let team1_sort = RoundRobinFormat::MATCH_GEN_TYPE.clone();
-        let team2_sort = RoundRobinFormat::MATCH_GEN_TYPE.clone();
-
-        let team1_sorts = if team1_sort == MatchGenType::Alternating {
+        let team1_sorts = if *match_gen_type == MatchGenType::Alternating {
            Vec::from([MatchGenType::Random, MatchGenType::MatchCount])
         }
         else {
-            Vec::from([team1_sort.clone()])
+            Vec::from([match_gen_type.clone()])
         };
-        let team2_sorts = if team2_sort == MatchGenType::Alternating {
+        let team2_sorts = if *match_gen_type == MatchGenType::Alternating {
             Vec::from([MatchGenType::MatchCount, MatchGenType::Random])
         }
         else {
-            Vec::from([team2_sort.clone()])
+            Vec::from([match_gen_type.clone()])
         };
 
         let mut data = Vec::new();
@@ -127,32 +160,26 @@ impl Season {
                 (index % team1_sorts.len(), index % team2_sorts.len())
             };
 
-            // self.round_robin_rules.sort_team1 = team1_sorts[team1_index].clone();
-            // self.round_robin_rules.sort_team2 = team2_sorts[team2_index].clone();
-
-            data = self.generate_irregular_matches(matches_per_team, match_pool, &prev_schedule_map, rng);
+            let sorts = [&team1_sorts[team1_index], &team2_sorts[team2_index]];
+            data = self.generate_irregular_matches(matches_per_team, match_pool, &prev_schedule_map, sorts, rng);
             if data.len() > 0 {
                 break;
             }
-            // self.failures += 1;
         }
 
-        // Give MatchGenType::Alternating back to the round_robin_rules so it can be used next time.
-        // self.round_robin_rules.sort_team1 = team1_sort;
-        // self.round_robin_rules.sort_team2 = team2_sort;
         return data;
     }
 
     // Generate a match schedule with arbitrary number of games.
     // Add to an existing match pool vector if successful.
     // Return the schedule data. If unsuccessful, return empty vector.
-    fn generate_irregular_matches(&self, matches_per_team: u8, match_pool: &mut Vec<[TeamId; 2]>, prev_schedule_map: &HashMap<TeamId, TeamScheduleData>, rng: &mut ThreadRng) -> Vec<TeamScheduleData> {
+    fn generate_irregular_matches(&self, matches_per_team: u8, match_pool: &mut Vec<[TeamId; 2]>, prev_schedule_map: &HashMap<TeamId, TeamScheduleData>, sorts: [&MatchGenType; 2], rng: &mut ThreadRng) -> Vec<TeamScheduleData> {
         let mut schedule_data = TeamScheduleData::generate(&self.teams);
         let mut completed_schedule_data = Vec::new();
         let mut created_matches = Vec::new();
 
         while schedule_data.len() > 0 {
-            if !self.generate_irregular_match(&mut schedule_data, prev_schedule_map, rng, &mut created_matches, &mut completed_schedule_data, matches_per_team) {
+            if !self.generate_irregular_match(&mut schedule_data, prev_schedule_map, sorts, rng, &mut created_matches, &mut completed_schedule_data, matches_per_team) {
                 return Vec::new();
             }
         }
@@ -163,12 +190,13 @@ impl Season {
     }
 
     // Generate a single irregular match. Return whether successful or not.
+    // The first sort type is used for picking the first team, and the second for picking its opponent.
     fn generate_irregular_match(&self, schedule_data: &mut Vec<TeamScheduleData>, prev_schedule_map: &HashMap<TeamId, TeamScheduleData>,
-    rng: &mut ThreadRng, created_matches: &mut Vec<[TeamId; 2]>, completed_schedule_data: &mut Vec<TeamScheduleData>, matches_per_team: u8
+    sorts: [&MatchGenType; 2], rng: &mut ThreadRng, created_matches: &mut Vec<[TeamId; 2]>, completed_schedule_data: &mut Vec<TeamScheduleData>, matches_per_team: u8
     ) -> bool {
         // Randomise and sort.
         schedule_data.shuffle(rng);
-        sorting::sort_default(&RoundRobinFormat::MATCH_GEN_TYPE, schedule_data, prev_schedule_map, rng);
+        sorting::sort_default(sorts[0], schedule_data, prev_schedule_map, rng);
         let mut temp_schedule_data = schedule_data.clone();
 
         let mut team1 = temp_schedule_data.swap_remove(0);
@@ -181,26 +209,33 @@ impl Season {
             return false;
         }
 
-        let home_filter = TeamScheduleData::filter_for_home_game(&temp_schedule_data, &prev_schedule_map, matches_per_team);
-        let away_filter = TeamScheduleData::filter_for_away_game(&temp_schedule_data, &prev_schedule_map, matches_per_team);
-
-        if home_filter.is_empty() && away_filter.is_empty() {
-            return false;
-        }
-
         // Get the match data from a match generation that occurred previously.
         let prev_team1 = match prev_schedule_map.get(&team1.team_id) {
             Some(p) => p,
             None => &TeamScheduleData::default(),
         };
 
+        // Only keep the opponents if team1 itself can still play the other side of the match.
+        let home_filter = match team1.can_have_home_games(prev_team1, matches_per_team) {
+            true => TeamScheduleData::filter_for_home_game(&temp_schedule_data, &prev_schedule_map, matches_per_team),
+            _ => Vec::new()
+        };
+        let away_filter = match team1.can_have_away_games(prev_team1, matches_per_team) {
+            true => TeamScheduleData::filter_for_away_game(&temp_schedule_data, &prev_schedule_map, matches_per_team),
+            _ => Vec::new()
+        };
+
+        if home_filter.is_empty() && away_filter.is_empty() {
+            return false;
+        }
+
         let home_away_diff = team1.get_home_away_difference(prev_team1);
         let mut team2;
 
         // team1 needs a home game.
         if away_filter.len() == 0 || (home_filter.len() > 0 && home_away_diff <= 0) {
             temp_schedule_data = home_filter;
-            sorting::sort_away(&RoundRobinFormat::MATCH_GEN_TYPE, &mut temp_schedule_data, prev_schedule_map, rng);
+            sorting::sort_away(sorts[1], &mut temp_schedule_data, prev_schedule_map, rng);
             team2 = temp_schedule_data.swap_remove(0);
             created_matches.push([team1.team_id, team2.team_id]);
 
@@ -211,7 +246,7 @@ impl Season {
         // team1 needs an away game.
         else {
             temp_schedule_data = away_filter;
-            sorting::sort_home(&RoundRobinFormat::MATCH_GEN_TYPE, &mut temp_schedule_data, prev_schedule_map, rng);
+            sorting::sort_home(sorts[1], &mut temp_schedule_data, prev_schedule_map, rng);
             team2 = temp_schedule_data.swap_remove(0);
             created_matches.push([team2.team_id, team1.team_id]);
 
@@ -233,29 +268,140 @@ impl Season {
     }
 }
 
+// Add a match between the teams at the given indexes to the schedule data and the match pool.
+fn add_match(schedule_data: &mut [TeamScheduleData], home_index: usize, away_index: usize, match_pool: &mut Vec<[TeamId; 2]>) {
+    let home_id = schedule_data[home_index].team_id;
+    let away_id = schedule_data[away_index].team_id;
+
+    schedule_data[home_index].home_matches.push(away_id);
+    schedule_data[away_index].away_matches.push(home_id);
+    match_pool.push([home_id, away_id]);
+}
+
 // Give each matchday a date, build the games and return them.
+// Several calendars are generated, and the one that follows the schedule rules best is kept.
 pub fn assign_dates(matchdays: Vec<Vec<[TeamId; 2]>>, start_date: &Date, end_date: &Date, comp: &Competition, randomise_order: bool, rng: &mut ThreadRng) -> Vec<Game> {
-let mut dates = get_dates(start_date, end_date);
-    let mut game_dates = Vec::new();
+    // How many calendars are compared.
+    const ATTEMPTS: u8 = 5;
 
-    for _ in 0..matchdays.len() {
-        game_dates.push(dates.swap_remove(rng.random_range(0..dates.len())));
-    }
+    let rules = comp.get_schedule_rules();
+    let busy_dates = get_busy_dates(&comp.id);
+
+    let mut best_games = Vec::new();
+    let mut best_penalty = u32::MAX;
+    for _ in 0..ATTEMPTS {
+        let mut game_dates = pick_dates(&matchdays, start_date, end_date, &rules, &busy_dates, rng);
+
+        // Knockout matchdays all have the same teams, so sorting the dates afterwards does not break the rules.
+        if !randomise_order { game_dates.sort(); }
 
-    if !randomise_order { game_dates.sort(); }
+        let mut games = Vec::new();
+        for (date, matchday) in zip(game_dates.iter(), matchdays.iter()) {
+            let date_string = date_to_db_string(&date);
+            build_games(matchday, &date_string, comp, &mut games);
+        }
+
+        let penalty = ScheduleReport::build(&games.iter().collect::<Vec<&Game>>(), &rules, &busy_dates).get_penalty();
+        if penalty < best_penalty {
+            best_games = games;
+            best_penalty = penalty;
+        }
 
-    let mut games = Vec::new();
-    for (date, matchday) in zip(game_dates.iter(), matchdays.iter()) {
-        let date_string = date_to_db_string(&date);
-        build_games(matchday, &date_string, comp, &mut games);
+        // Cannot do better than perfect.
+        if penalty == 0 { break; }
     }
 
     // Sort the games so that the earliest are LAST.
-    games.sort_by(|a, b|
+    best_games.sort_by(|a, b|
         db_string_to_date(&b.date).cmp(&db_string_to_date(&a.date))
         .then(b.get_name().cmp(&a.get_name())));
 
-    return games;
+    return best_games;
+}
+
+// Pick a date for each matchday, following the schedule rules as well as possible.
+fn pick_dates(matchdays: &[Vec<[TeamId; 2]>], start_date: &Date, end_date: &Date, rules: &ScheduleRules, busy_dates: &HashMap<TeamId, HashSet<Date>>, rng: &mut ThreadRng) -> Vec<Date> {
+    let mut dates: Vec<Date> = get_dates(start_date, end_date).into_iter().filter(|a| !rules.is_blackout(a)).collect();
+
+    // Not enough days outside the blackouts, so any day will do.
+    if dates.len() < matchdays.len() {
+        dates = get_dates(start_date, end_date);
+    }
+
+    let mut team_dates = busy_dates.clone();
+    let mut back_to_backs: HashMap<TeamId, u8> = HashMap::new();
+    let mut game_dates = Vec::new();
+    for matchday in matchdays.iter() {
+        let teams: Vec<TeamId> = matchday.iter().flatten().cloned().collect();
+
+        // Only the dates that break the fewest rules are considered.
+        let levels: Vec<u8> = dates.iter().map(|a| get_date_level(a, &teams, rules, &team_dates, &back_to_backs)).collect();
+        let best_level = levels.iter().min().cloned().unwrap_or_default();
+        let best_dates: Vec<usize> = (0..dates.len()).filter(|i| levels[*i] == best_level).collect();
+        let date = dates.swap_remove(best_dates[rng.random_range(0..best_dates.len())]);
+
+        for id in teams.iter() {
+            let played = team_dates.entry(*id).or_default();
+            let neighbours = [date.previous_day(), date.next_day()].into_iter().flatten().filter(|a| played.contains(a)).count();
+            played.insert(date);
+
+            *back_to_backs.entry(*id).or_default() += convert::int::<usize, u8>(neighbours);
+        }
+
+        game_dates.push(date);
+    }
+
+    return game_dates;
+}
+
+// Get how badly playing the teams' games on the date would break the schedule rules.
+// 0: Fine. 1: Not a preferred game day. 2: Too little rest, or too many back-to-backs. 3: A team already plays on the date.
+fn get_date_level(date: &Date, teams: &[TeamId], rules: &ScheduleRules, team_dates: &HashMap<TeamId, HashSet<Date>>, back_to_backs: &HashMap<TeamId, u8>) -> u8 {
+    let mut level = match rules.is_game_day(date) {
+        true => 0,
+        _ => 1
+    };
+
+    for id in teams.iter() {
+        let played = match team_dates.get(id) {
+            Some(a) => a,
+            None => continue
+        };
+
+        if played.contains(date) { return 3; }
+
+        let mut neighbours = 0;
+        for days in 1..=(rules.min_rest_days as i64).max(1) {
+            for other in [date.checked_sub(Duration::days(days)), date.checked_add(Duration::days(days))].into_iter().flatten() {
+                if played.contains(&other) {
+                    neighbours += 1;
+                }
+            }
+        }
+
+        let team_back_to_backs = back_to_backs.get(id).cloned().unwrap_or_default();
+        if neighbours > 0 && (rules.min_rest_days > 0 || !rules.allows_back_to_backs(team_back_to_backs + neighbours)) {
+            level = 2;
+        }
+    }
+
+    return level;
+}
+
+// Get the dates on which teams have games in competitions other than the given one.
+fn get_busy_dates(comp_id: &CompetitionId) -> HashMap<TeamId, HashSet<Date>> {
+    let mut busy_dates: HashMap<TeamId, HashSet<Date>> = HashMap::new();
+    for season in SEASONS.lock().unwrap().values().filter_map(|a| a.last()) {
+        if season.comp_id == *comp_id { continue; }
+
+        for game in season.upcoming_games.iter().chain(season.played_games.iter().map(|a| a.as_ref())) {
+            let date = db_string_to_date(&game.date);
+            busy_dates.entry(game.home.team_id).or_default().insert(date);
+            busy_dates.entry(game.away.team_id).or_default().insert(date);
+        }
+    }
+
+    return busy_dates;
 }
 
 // Convert the simple representations of two teams into Game elements.
@@ -303,10 +449,10 @@ pub fn generate_matchdays(match_pool: &mut Vec<[TeamId; 2]>, rng: &mut ThreadRng
 impl KnockoutRoundSeason {
     // Generate matchdays for the knockout round.
     pub fn generate_matchdays(&self, comp: &Competition) -> Vec<Vec<[TeamId; 2]>> {
-        let matches = comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap().get_maximum_matches_in_pair();
-        let mut matchdays = vec![Vec::new(); matches as usize];
+        let format = comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap();
+        let mut matchdays = vec![Vec::new(); format.get_maximum_matches_in_pair() as usize];
         for pair in self.pairs.iter() {
-            pair.generate_matchdays(&mut matchdays);
+            pair.generate_matchdays(format, &mut matchdays);
         }
 
         return matchdays;
@@ -315,9 +461,10 @@ impl KnockoutRoundSeason {
 
 impl KnockoutPair {
     // Generate matchdays for the knockout pair.
-    fn generate_matchdays(&self, matchdays: &mut Vec<Vec<[TeamId; 2]>>) {
+    // The home team of the pair is the higher seed, and the format decides which games it hosts.
+    fn generate_matchdays(&self, format: &KnockoutRoundFormat, matchdays: &mut Vec<Vec<[TeamId; 2]>>) {
         for (i, day) in matchdays.iter_mut().enumerate() {
-            if i % 2 == 0 {
+            if format.is_higher_seed_home(i) {
                 day.push([self.home.team_id, self.away.team_id]);
             }
             else {
@@ -477,4 +624,220 @@ impl TeamScheduleData {
         }
         return map;
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use ::time::Weekday;
+
+    use super::*;
+    use crate::{competition::format::{Format, round_robin::{RoundRobin, schedule_validator::ScheduleProblem}}, match_event::Rules, time::{AnnualDate, AnnualWindow}};
+
+    // Build a season with the given amount of teams, without saving it.
+    fn build_season(no_of_teams: u16) -> Season {
+        Season {
+            teams: (1..=no_of_teams).map(|id| TeamCompData::build(id, convert::int::<u16, u8>(id))).collect(),
+            start_date: "2025-09-01".to_string(),
+            end_date: "2026-03-31".to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Build the games of the match pool, each on its own day from the start of the season.
+    fn build_daily_games(season: &Season, match_pool: &[[TeamId; 2]]) -> Vec<Game> {
+        let start_date = db_string_to_date(&season.start_date);
+        match_pool.iter().enumerate().map(|(i, matchup)| {
+            let home = &season.teams[season.get_team_index(matchup[0])];
+            let away = &season.teams[season.get_team_index(matchup[1])];
+            Game::build(home, away, 1, &date_to_db_string(&(start_date + Duration::days(i as i64))))
+        }).collect()
+    }
+
+    // Build the games of the matchdays, each matchday on its own day from the start of the season.
+    fn build_matchday_games(season: &Season, matchdays: &[Vec<[TeamId; 2]>]) -> Vec<Game> {
+        let start_date = db_string_to_date(&season.start_date);
+        matchdays.iter().enumerate().flat_map(|(i, matchday)| {
+            let date = date_to_db_string(&(start_date + Duration::days(i as i64)));
+            matchday.iter().map(move |matchup| {
+                let home = &season.teams[season.get_team_index(matchup[0])];
+                let away = &season.teams[season.get_team_index(matchup[1])];
+                Game::build(home, away, 1, &date)
+            })
+        }).collect()
+    }
+
+    // Build the matchdays of a double round robin of four teams, where every team plays on every matchday.
+    fn build_double_round_matchdays() -> Vec<Vec<[TeamId; 2]>> {
+        let first_half = vec![vec![[1, 2], [3, 4]], vec![[3, 1], [4, 2]], vec![[1, 4], [2, 3]]];
+        let second_half: Vec<Vec<[TeamId; 2]>> = first_half.iter().map(|a| a.iter().map(|b| [b[1], b[0]]).collect()).collect();
+        return first_half.into_iter().chain(second_half).collect();
+    }
+
+    // Pick the dates of the matchdays, and get the dates each team plays on, in order.
+    fn pick_team_dates(matchdays: &[Vec<[TeamId; 2]>], start_date: &str, end_date: &str, rules: &ScheduleRules) -> HashMap<TeamId, Vec<Date>> {
+        let dates = pick_dates(matchdays, &db_string_to_date(start_date), &db_string_to_date(end_date), rules, &HashMap::new(), &mut rand::rng());
+        assert_eq!(dates.len(), matchdays.len());
+
+        let mut team_dates: HashMap<TeamId, Vec<Date>> = HashMap::new();
+        for (date, matchday) in zip(dates, matchdays) {
+            for id in matchday.iter().flatten() {
+                team_dates.entry(*id).or_default().push(date);
+            }
+        }
+
+        for dates in team_dates.values_mut() {
+            dates.sort();
+        }
+
+        return team_dates;
+    }
+
+    #[test]
+    fn picked_dates_follow_game_days_and_blackouts() {
+        // December and January have nine Saturdays, and the blackout takes three of them.
+        let mut rules = ScheduleRules::build(vec![Weekday::Saturday], 0, None);
+        rules.add_blackout(AnnualWindow::build(AnnualDate::build(12, 20), AnnualDate::build(1, 6)));
+
+        let matchdays = build_double_round_matchdays();
+        for _ in 0..20 {
+            let team_dates = pick_team_dates(&matchdays, "2025-12-01", "2026-01-31", &rules);
+            for (id, dates) in team_dates.iter() {
+                assert_eq!(dates.len(), matchdays.len(), "team {id}: {dates:?}");
+                for date in dates.iter() {
+                    assert!(rules.is_game_day(date), "team {id} plays on a {}", date.weekday());
+                    assert!(!rules.is_blackout(date), "team {id} plays during the blackout on {date}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn picked_dates_follow_minimum_rest() {
+        // Each picked date rules out five days, so the window always has room for another matchday with enough rest.
+        let rules = ScheduleRules::build(Vec::new(), 2, None);
+
+        let matchdays = build_double_round_matchdays();
+        for _ in 0..20 {
+            let team_dates = pick_team_dates(&matchdays, "2025-09-01", "2025-10-10", &rules);
+            for (id, dates) in team_dates.iter() {
+                for pair in dates.windows(2) {
+                    let rest = (pair[1] - pair[0]).whole_days() - 1;
+                    assert!(rest >= rules.min_rest_days as i64, "team {id} rests {rest} days between {} and {}", pair[0], pair[1]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn picked_dates_follow_maximum_back_to_backs() {
+        // Each picked date rules out three days, so the window always has room for another matchday without a back-to-back.
+        let rules = ScheduleRules::build(Vec::new(), 0, Some(1));
+
+        let matchdays = build_double_round_matchdays();
+        for _ in 0..20 {
+            let team_dates = pick_team_dates(&matchdays, "2025-09-01", "2025-09-20", &rules);
+            for (id, dates) in team_dates.iter() {
+                let back_to_backs = dates.windows(2).filter(|a| (a[1] - a[0]).whole_days() == 1).count();
+                assert!(rules.allows_back_to_backs(convert::int::<usize, u8>(back_to_backs)), "team {id} has {back_to_backs} back-to-backs: {dates:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn date_level_ranks_broken_rules() {
+        let rules = ScheduleRules::build(vec![Weekday::Saturday], 1, None);
+        let saturday = db_string_to_date("2025-09-06");
+        let sunday = db_string_to_date("2025-09-07");
+        let teams = [1, 2];
+        let back_to_backs = HashMap::new();
+
+        let mut team_dates: HashMap<TeamId, HashSet<Date>> = HashMap::new();
+        assert_eq!(get_date_level(&saturday, &teams, &rules, &team_dates, &back_to_backs), 0);
+        assert_eq!(get_date_level(&sunday, &teams, &rules, &team_dates, &back_to_backs), 1);
+
+        team_dates.entry(2).or_default().insert(saturday);
+        assert_eq!(get_date_level(&sunday, &teams, &rules, &team_dates, &back_to_backs), 2);
+        assert_eq!(get_date_level(&saturday, &teams, &rules, &team_dates, &back_to_backs), 3);
+    }
+
+    #[test]
+    fn round_robin_schedules_are_valid() {
+        // Rounds and extra matches to test. Each gives a valid amount of matches for any amount of teams.
+        const FORMATS: [(u8, u8); 4] = [(1, 0), (2, 0), (1, 2), (3, 2)];
+        const MATCH_GEN_TYPES: [MatchGenType; 4] = [MatchGenType::Null, MatchGenType::MatchCount, MatchGenType::Random, MatchGenType::Alternating];
+
+        let mut rng = rand::rng();
+        for no_of_teams in 3..=30 {
+            for (rounds, extra_matches) in FORMATS {
+                for match_gen_type in MATCH_GEN_TYPES {
+                    let mut round_robin = RoundRobin::build(rounds, extra_matches, 3, 2, 1, 1, 0);
+                    round_robin.match_gen_type = match_gen_type.clone();
+                    let comp = Competition {
+                        format: Format::build(Some(round_robin.clone()), None, Rules::build(3, 1200, 300, false, 0.0)),
+                        ..Default::default()
+                    };
+
+                    let mut season = build_season(no_of_teams);
+                    let mut match_pool = season.generate_match_pool(&comp, &mut rng);
+                    let matchdays = generate_matchdays(&mut match_pool, &mut rng);
+                    season.upcoming_games = build_matchday_games(&season, &matchdays);
+
+                    let problems: Vec<ScheduleProblem> = round_robin.validate_schedule(&season).into_iter().filter(|a| a.is_serious()).collect();
+                    assert!(problems.is_empty(), "{no_of_teams} teams, {rounds} rounds, {extra_matches} extra matches, {:?}\n{:#?}", match_gen_type, problems);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn half_round_pairs_every_team_once() {
+        let mut rng = rand::rng();
+        for no_of_teams in 3..=20 {
+            let season = build_season(no_of_teams);
+            let mut match_pool = Vec::new();
+            season.generate_half_round(&mut match_pool, &mut rng);
+
+            let pairs: HashSet<[TeamId; 2]> = match_pool.iter().map(|a| [a[0].min(a[1]), a[0].max(a[1])]).collect();
+            assert_eq!(pairs.len(), match_pool.len(), "{no_of_teams} teams: a pair plays twice");
+            assert_eq!(pairs.len(), (no_of_teams * (no_of_teams - 1) / 2) as usize, "{no_of_teams} teams: a pair is missing");
+
+            for team in season.teams.iter() {
+                let home = match_pool.iter().filter(|a| a[0] == team.team_id).count();
+                let away = match_pool.iter().filter(|a| a[1] == team.team_id).count();
+                assert!(home.abs_diff(away) <= 1, "{no_of_teams} teams: team {} has {home} home and {away} away games", team.team_id);
+            }
+        }
+    }
+
+    #[test]
+    fn full_schedule_is_valid() {
+        let mut season = build_season(6);
+        let mut match_pool = Vec::new();
+        season.generate_full_round(&mut match_pool);
+        season.upcoming_games = build_daily_games(&season, &match_pool);
+
+        let round_robin = RoundRobin::build(2, 0, 3, 2, 1, 1, 0);
+        assert_eq!(round_robin.validate_schedule(&season), Vec::new());
+    }
+
+    #[test]
+    fn finds_schedule_problems() {
+        let mut season = build_season(4);
+        let mut match_pool = Vec::new();
+        season.generate_full_round(&mut match_pool);
+        let removed = match_pool.pop().unwrap();
+        season.upcoming_games = build_daily_games(&season, &match_pool);
+
+        // The same team plays twice on the first day, and a game is played after the season.
+        season.upcoming_games[1].date = season.upcoming_games[0].date.clone();
+        season.upcoming_games.last_mut().unwrap().date = "2026-04-01".to_string();
+
+        let problems = RoundRobin::build(2, 0, 3, 2, 1, 1, 0).validate_schedule(&season);
+        let pair = [removed[0].min(removed[1]), removed[0].max(removed[1])];
+        assert!(problems.contains(&ScheduleProblem::MatchCount(removed[0], 5)));
+        assert!(problems.contains(&ScheduleProblem::PairingCount(pair[0], pair[1], 1)));
+        assert!(problems.contains(&ScheduleProblem::DoubleGame(match_pool[0][0], season.upcoming_games[0].date.clone())));
+        assert!(problems.contains(&ScheduleProblem::OutsideWindow("2026-04-01".to_string())));
+    }
+}
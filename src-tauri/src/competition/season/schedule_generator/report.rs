@@ -0,0 +1,195 @@
+// Measuring how well a calendar of games follows the schedule rules of its competition.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::json;
+use time::Date;
+
+use crate::{competition::schedule_rules::ScheduleRules, match_event::Game, time::db_string_to_date, types::{TeamId, convert}};
+
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct ScheduleReport {
+    games: u16,
+    off_game_days: u16,         // Games played on other days than the preferred game days.
+    in_blackouts: u16,          // Games played during blackouts.
+    clashes: u16,               // Times a team has another game on the same day.
+    short_rests: u16,           // Times a team gets less rest than the rules require.
+    back_to_backs: u16,         // Times a team plays on consecutive days.
+    excess_back_to_backs: u16,  // Back-to-backs over the limit of the rules.
+    total_rest_days: u32,
+    rests: u32,
+    longest_break: u16,         // Longest stretch of days a team goes without games of the calendar.
+}
+
+impl ScheduleReport {
+    // Build a report of the given games.
+    // The games teams have in other competitions are taken into account for clashes and rest.
+    pub fn build(games: &[&Game], rules: &ScheduleRules, busy_dates: &HashMap<TeamId, HashSet<Date>>) -> Self {
+        let mut report = Self::default();
+
+        // Each team's game dates, with a flag for whether the game is in this calendar.
+        let mut team_dates: HashMap<TeamId, Vec<(Date, bool)>> = HashMap::new();
+        for game in games.iter() {
+            let date = db_string_to_date(&game.date);
+            report.games += 1;
+            if !rules.is_game_day(&date) { report.off_game_days += 1; }
+            if rules.is_blackout(&date) { report.in_blackouts += 1; }
+
+            for id in [game.home.team_id, game.away.team_id] {
+                team_dates.entry(id).or_default().push((date, true));
+            }
+        }
+
+        for (id, dates) in team_dates.iter_mut() {
+            if let Some(busy) = busy_dates.get(id) {
+                dates.extend(busy.iter().map(|a| (*a, false)));
+            }
+            dates.sort();
+
+            report.add_team(dates, rules);
+        }
+
+        return report;
+    }
+
+    // Add the calendar of a single team to the report.
+    fn add_team(&mut self, dates: &[(Date, bool)], rules: &ScheduleRules) {
+        let mut back_to_backs = 0;
+        for pair in dates.windows(2) {
+            let ((date1, is_own1), (date2, is_own2)) = (pair[0], pair[1]);
+
+            // Other competitions are responsible for their own games.
+            if !is_own1 && !is_own2 { continue; }
+
+            let days = (date2 - date1).whole_days();
+            if days == 0 {
+                self.clashes += 1;
+                continue;
+            }
+
+            let rest = convert::int::<i64, u16>(days - 1);
+            if rest < rules.min_rest_days as u16 { self.short_rests += 1; }
+            if rest == 0 { back_to_backs += 1; }
+
+            // The spread of the calendar only looks at its own games.
+            if is_own1 && is_own2 {
+                self.total_rest_days += rest as u32;
+                self.rests += 1;
+                self.longest_break = self.longest_break.max(rest);
+            }
+        }
+
+        self.back_to_backs += back_to_backs;
+        if let Some(max) = rules.max_back_to_backs {
+            self.excess_back_to_backs += back_to_backs.saturating_sub(max as u16);
+        }
+    }
+
+    // Get a penalty for comparing calendars. Lower is better.
+    pub fn get_penalty(&self) -> u32 {
+        self.clashes as u32 * 1000 +
+        self.in_blackouts as u32 * 100 +
+        self.short_rests as u32 * 100 +
+        self.excess_back_to_backs as u32 * 100 +
+        self.off_game_days as u32 * 10 +
+        self.back_to_backs as u32
+    }
+
+    // Get the average amount of days teams have off between games.
+    fn get_average_rest(&self) -> f64 {
+        match self.rests {
+            0 => 0.0,
+            _ => self.total_rest_days as f64 / self.rests as f64
+        }
+    }
+
+    // Get relevant information for the frontend.
+    pub fn get_package(&self) -> serde_json::Value {
+        json!({
+            "games": self.games,
+            "off_game_days": self.off_game_days,
+            "in_blackouts": self.in_blackouts,
+            "clashes": self.clashes,
+            "short_rests": self.short_rests,
+            "back_to_backs": self.back_to_backs,
+            "excess_back_to_backs": self.excess_back_to_backs,
+            "average_rest": format!("{:.1}", self.get_average_rest()),
+            "longest_break": self.longest_break,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Weekday;
+
+    use super::*;
+    use crate::{competition::season::team::TeamCompData, time::{AnnualDate, AnnualWindow}};
+
+    // Build the games of a double round robin of four teams, with a date for each matchday.
+    fn build_games(dates: [&str; 6]) -> Vec<Game> {
+        let teams: Vec<TeamCompData> = (1..=4).map(|id| TeamCompData::build(id, convert::int::<TeamId, u8>(id))).collect();
+        let matchdays = [[[0, 1], [2, 3]], [[2, 0], [3, 1]], [[0, 3], [1, 2]], [[1, 0], [3, 2]], [[0, 2], [1, 3]], [[3, 0], [2, 1]]];
+
+        let mut games = Vec::new();
+        for (date, matchday) in dates.iter().zip(matchdays) {
+            for [home, away] in matchday {
+                games.push(Game::build(&teams[home], &teams[away], 1, date));
+            }
+        }
+
+        return games;
+    }
+
+    // Get the penalty of the calendar.
+    fn get_penalty(games: &[Game], rules: &ScheduleRules) -> u32 {
+        ScheduleReport::build(&games.iter().collect::<Vec<&Game>>(), rules, &HashMap::new()).get_penalty()
+    }
+
+    #[test]
+    fn worse_calendar_gets_higher_penalty() {
+        let rules = ScheduleRules::build(vec![Weekday::Saturday], 1, Some(0));
+
+        // Every Saturday in September and October.
+        let good = build_games(["2025-09-06", "2025-09-13", "2025-09-20", "2025-09-27", "2025-10-04", "2025-10-11"]);
+        assert_eq!(get_penalty(&good, &rules), 0);
+
+        // One matchday is moved to a Wednesday.
+        let off_game_day = build_games(["2025-09-06", "2025-09-13", "2025-09-17", "2025-09-27", "2025-10-04", "2025-10-11"]);
+
+        // One matchday is moved to the Sunday after another.
+        let back_to_back = build_games(["2025-09-06", "2025-09-13", "2025-09-14", "2025-09-27", "2025-10-04", "2025-10-11"]);
+
+        // Two matchdays are played on the same Saturday.
+        let clash = build_games(["2025-09-06", "2025-09-13", "2025-09-13", "2025-09-27", "2025-10-04", "2025-10-11"]);
+
+        let penalties: Vec<u32> = [&good, &off_game_day, &back_to_back, &clash].iter().map(|a| get_penalty(a, &rules)).collect();
+        assert!(penalties.is_sorted_by(|a, b| a < b), "{penalties:?}");
+    }
+
+    #[test]
+    fn games_in_blackouts_are_penalised() {
+        let mut rules = ScheduleRules::build(Vec::new(), 0, None);
+        let dates = ["2025-12-06", "2025-12-13", "2025-12-20", "2025-12-27", "2026-01-03", "2026-01-10"];
+        let games = build_games(dates);
+        assert_eq!(get_penalty(&games, &rules), 0);
+
+        rules.add_blackout(AnnualWindow::build(AnnualDate::build(12, 24), AnnualDate::build(12, 31)));
+        let report = ScheduleReport::build(&games.iter().collect::<Vec<&Game>>(), &rules, &HashMap::new());
+        assert_eq!(report.in_blackouts, 2);
+        assert!(report.get_penalty() > 0);
+    }
+
+    #[test]
+    fn games_in_other_competitions_count_for_rest() {
+        let rules = ScheduleRules::build(Vec::new(), 1, None);
+        let games = build_games(["2025-09-06", "2025-09-13", "2025-09-20", "2025-09-27", "2025-10-04", "2025-10-11"]);
+        let busy_dates = HashMap::from([(1, HashSet::from([db_string_to_date("2025-09-07")]))]);
+
+        let report = ScheduleReport::build(&games.iter().collect::<Vec<&Game>>(), &rules, &busy_dates);
+        assert_eq!(report.short_rests, 1);
+        assert_eq!(report.back_to_backs, 1);
+        assert!(report.get_penalty() > get_penalty(&games, &rules));
+    }
+}
@@ -0,0 +1,49 @@
+// Methods for checking that knockout series are played as their format says.
+
+use crate::{competition::{Competition, season::{Season, knockout_round::PairStatus}}, types::{TeamId, convert}};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeriesProblem {
+    GameAfterPairOver(TeamId, String),  // The team has a game scheduled on the date, even though its pair is over.
+    TooManyGames(TeamId, TeamId, u8),   // The pair has more games than the format allows.
+    GameOutsidePairs(TeamId, TeamId),   // The teams play each other without being a pair.
+}
+
+impl Season {
+    // Check the knockout pairs of the season and return every problem found.
+    pub fn validate_series(&self, comp: &Competition) -> Vec<SeriesProblem> {
+        let mut problems = Vec::new();
+        let knockout_round = match self.knockout_round.as_ref() {
+            Some(a) => a,
+            None => return problems
+        };
+
+        let max_games = comp.format.as_ref().unwrap().knockout_round.as_ref().unwrap().get_maximum_matches_in_pair();
+        let games = self.get_all_games();
+
+        for pair in knockout_round.pairs.iter() {
+            let pair_games = convert::int::<usize, u8>(games.iter().filter(|a| a.is_team_playing(pair.home.team_id) && a.is_team_playing(pair.away.team_id)).count());
+            if pair_games > max_games {
+                problems.push(SeriesProblem::TooManyGames(pair.home.team_id, pair.away.team_id, pair_games));
+            }
+
+            if pair.get_status() != PairStatus::Over { continue; }
+
+            for game in self.upcoming_games.iter() {
+                for id in [game.home.team_id, game.away.team_id] {
+                    if pair.has_team(id) {
+                        problems.push(SeriesProblem::GameAfterPairOver(id, game.date.clone()));
+                    }
+                }
+            }
+        }
+
+        for game in games.iter() {
+            if !knockout_round.pairs.iter().any(|a| a.has_team(game.home.team_id) && a.has_team(game.away.team_id)) {
+                problems.push(SeriesProblem::GameOutsidePairs(game.home.team_id, game.away.team_id));
+            }
+        }
+
+        return problems;
+    }
+}
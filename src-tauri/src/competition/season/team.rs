@@ -1,5 +1,7 @@
 // Data for teams.
 
+use std::cmp::Ordering;
+
 use ordinal::ToOrdinal;
 use serde_json::json;
 
@@ -67,6 +69,7 @@ impl TeamCompData {
             "id": self.team_id,
             "name": self.get_team().name,
             "wins": self.get_wins(),
+            "score": self.get_wins(),
             "seed": self.seed
         })
     }
@@ -106,15 +109,22 @@ impl TeamCompData {
 
     // Update the team data after a match.
     pub fn update(&mut self, this: &TeamGameData, opponent: &TeamGameData, had_overtime: bool) {
-        let self_goals = this.get_goal_amount();
-        let opp_goals = opponent.get_goal_amount();
+        let shootout = this.shootout_goals.cmp(&opponent.shootout_goals);
+        self.add_result(this.get_goal_amount(), opponent.get_goal_amount(), shootout, had_overtime);
+    }
+
+    // Update the team data with the goals of a game.
+    // The shootout ordering is Greater if the team won the shootout, and Equal if there was none.
+    pub fn add_result(&mut self, self_goals: u16, opp_goals: u16, shootout: Ordering, had_overtime: bool) {
+        // A shootout decides the winner of a game that is otherwise even.
+        let order = self_goals.cmp(&opp_goals).then(shootout);
 
         // This team won.
-        if self_goals > opp_goals {
+        if order.is_gt() {
             if !had_overtime { self.regular_wins += 1; }
             else { self.ot_wins += 1; }
         }
-        else if self_goals < opp_goals {
+        else if order.is_lt() {
             if !had_overtime { self.regular_losses += 1; }
             else { self.ot_losses += 1; }
         }
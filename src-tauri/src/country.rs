@@ -2,7 +2,7 @@
 use std::collections::HashMap;
 use rand::{Rng, rngs::ThreadRng};
 
-use crate::{database::COUNTRIES, io::load_country_names, person::Gender, types::{CountryId, CountryNamePool}};
+use crate::{database::{COUNTRIES, IDS, IdKind, TEAMS}, io::load_country_names, person::Gender, types::{CountryId, CountryNamePool}};
 
 #[derive(Default, Clone)]
 pub struct Country {
@@ -34,7 +34,7 @@ impl Country {  // Basics.
     // Build a Country element and store it in the database. Return the created element.
     pub fn build_and_save(name: &str) -> Self {
         let mut country = Self::build(name);
-        country.create_id(COUNTRIES.lock().unwrap().len() + 1);
+        country.create_id(IDS.lock().unwrap().next(IdKind::Country));
 
         country.save();
         return country;
@@ -102,6 +102,29 @@ impl Country {
         (forename, surname)
     }
 
+    // Get how likely a person is to come from each country, sorted by country ID.
+    // Countries with teams of their own produce more players, so their leagues are not filled with imports.
+    // The teams are counted only once, so the weights should be fetched once for a whole batch of people.
+    pub fn get_weights() -> Vec<(CountryId, u32)> {
+        let team_counts = Self::get_team_counts();
+        let mut weights: Vec<(CountryId, u32)> = COUNTRIES.lock().unwrap().values()
+            .map(|a| (a.id, a.get_combined_name_weight() * team_counts.get(&a.id).copied().unwrap_or_default().max(1)))
+            .collect();
+
+        weights.sort_by_key(|(id, _)| *id);
+        return weights;
+    }
+
+    // Get how many club teams are based in each country.
+    fn get_team_counts() -> HashMap<CountryId, u32> {
+        let mut counts = HashMap::new();
+        for team in TEAMS.lock().unwrap().values().filter(|a| !a.is_national_team) {
+            *counts.entry(team.country_id).or_insert(0) += 1;
+        }
+
+        return counts;
+    }
+
     // Get the combined name weight of the country's namepools.
     pub fn get_combined_name_weight(&self) -> u32 {
         self.names.get(&Gender::Male).unwrap().get("forenames").unwrap().total_weight +
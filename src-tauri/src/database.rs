@@ -1,15 +1,15 @@
 // The game database.
-use std::{collections::HashMap, sync::{LazyLock, Mutex}};
+use std::{collections::HashMap, hash::Hash, iter::zip, sync::{LazyLock, Mutex}};
 use rand::rngs::ThreadRng;
-use time::{macros::date, Date};
+use time::{macros::date, Date, Weekday};
 use lazy_static::lazy_static;
 
 use crate::{
     competition::{
-        format::{self}, knockout_generator, season::{ranking::RankCriteria, Season}, CompConnection, Competition, Seed
+        contract_rules::ContractRules, format::{self, knockout_round::{BracketType, KnockoutRound as KnockoutRoundFormat, TieBreaker}}, roster_rules::RosterRules, schedule_rules::ScheduleRules, knockout_generator, season::{ranking::RankCriteria, team::TeamCompData, Season}, CompConnection, Competition, Seed
     }, country::Country, event, io, match_event, person::{attribute::{Attribute, AttributeId}, manager::Manager, player::{
-        position::{Position, PositionId}, Player
-    }}, team::Team, time::{AnnualDate, AnnualWindow}, types::{CompetitionId, CountryId, ManagerId, PlayerId, TeamId}
+        position::{Position, PositionId}, population::Population, retirement::RetiredPlayer, Player
+    }}, team::Team, time::{AnnualDate, AnnualWindow}, types::{CompetitionId, CountryId, ManagerId, PlayerId, TeamId, convert}
 };
 
 // The current date in the game.
@@ -23,8 +23,95 @@ pub static SEASONS: LazyLock<Mutex<HashMap<CompetitionId, Vec<Season>>>> = LazyL
 
 pub static TEAMS: LazyLock<Mutex<HashMap<TeamId, Team>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
 pub static PLAYERS: LazyLock<Mutex<HashMap<PlayerId, Player>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+pub static RETIRED_PLAYERS: LazyLock<Mutex<HashMap<PlayerId, RetiredPlayer>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+pub static POPULATION: LazyLock<Mutex<Population>> = LazyLock::new(|| Mutex::new(Population::default()));
 pub static MANAGERS: LazyLock<Mutex<HashMap<ManagerId, Manager>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
 
+// The IDs handed out so far.
+pub static IDS: LazyLock<Mutex<IdAllocator>> = LazyLock::new(|| Mutex::new(IdAllocator::default()));
+
+// Look at an entry of a table without cloning it. None if the table has no entry with the ID.
+// The table stays locked until the function returns, so the function must not access the same table.
+pub fn read_entry<K: Eq + Hash, V, R>(table: &Mutex<HashMap<K, V>>, id: &K, f: impl FnOnce(&V) -> R) -> Option<R> {
+    table.lock().unwrap().get(id).map(f)
+}
+
+// Change an entry of a table in place without cloning it. None if the table has no entry with the ID.
+// The same rules apply as when reading an entry.
+pub fn update_entry<K: Eq + Hash, V, R>(table: &Mutex<HashMap<K, V>>, id: &K, f: impl FnOnce(&mut V) -> R) -> Option<R> {
+    table.lock().unwrap().get_mut(id).map(f)
+}
+
+// Get the IDs of the entries of a table that fulfil the condition, so that the entries can be handled one at a time.
+// The IDs are sorted, so that the entries are always handled in the same order.
+pub fn get_ids<K: Copy + Ord, V>(table: &Mutex<HashMap<K, V>>, condition: impl Fn(&V) -> bool) -> Vec<K> {
+    let mut ids: Vec<K> = table.lock().unwrap().iter().filter(|(_, a)| condition(a)).map(|(id, _)| *id).collect();
+    ids.sort();
+    return ids;
+}
+
+// The kinds of database entries that get their own IDs.
+pub enum IdKind {
+    Country,
+    Competition,
+    Team,
+    Player,
+    Manager,
+}
+
+// Hands out the IDs of new database entries.
+// IDs only ever go up, so the ID of a deleted entry is never given to another one.
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct IdAllocator {
+    country: usize,
+    competition: usize,
+    team: usize,
+    player: usize,
+    manager: usize,
+}
+
+impl IdAllocator {
+    // Get the last ID handed out for the given kind.
+    fn get_last_mut(&mut self, kind: IdKind) -> &mut usize {
+        match kind {
+            IdKind::Country => &mut self.country,
+            IdKind::Competition => &mut self.competition,
+            IdKind::Team => &mut self.team,
+            IdKind::Player => &mut self.player,
+            IdKind::Manager => &mut self.manager,
+        }
+    }
+
+    // Get a new ID for the given kind.
+    pub fn next(&mut self, kind: IdKind) -> usize {
+        let last = self.get_last_mut(kind);
+        *last += 1;
+        return *last;
+    }
+
+    // Make sure new IDs of the given kind come after the given ID.
+    fn move_past(&mut self, kind: IdKind, id: usize) {
+        let last = self.get_last_mut(kind);
+        *last = (*last).max(id);
+    }
+
+    // Make sure new IDs come after the ones already in the database.
+    // Call this after loading a save file, as the loaded entries keep the IDs they were saved with.
+    pub fn sync_with_database(&mut self) {
+        self.move_past(IdKind::Country, get_max_id(&COUNTRIES));
+        self.move_past(IdKind::Competition, get_max_id(&COMPETITIONS));
+        self.move_past(IdKind::Team, get_max_id(&TEAMS));
+        self.move_past(IdKind::Player, get_max_id(&PLAYERS).max(get_max_id(&RETIRED_PLAYERS)));
+        self.move_past(IdKind::Manager, get_max_id(&MANAGERS));
+    }
+}
+
+// Get the highest ID in a table, or 0 if the table is empty.
+fn get_max_id<K: Copy, V>(table: &Mutex<HashMap<K, V>>) -> usize where usize: TryFrom<K> {
+    table.lock().unwrap().keys().map(|a| convert::int::<K, usize>(*a)).max().unwrap_or_default()
+}
+
 lazy_static! {
     pub static ref POSITIONS: HashMap<PositionId, Position> = {
          HashMap::from([
@@ -88,73 +175,115 @@ lazy_static! {
 
 // Initialise the database.
 pub fn initialise() {
+    initialise_with_extra_leagues(0);
+}
+
+// Create the world with the given amount of extra leagues of generated teams, to see how the game copes with a larger world.
+pub fn initialise_with_extra_leagues(extra_leagues: usize) {
     let today = TODAY.lock().unwrap().clone();
     let mut rng = rand::rng();
+
+    // Whatever is already in the database keeps its ID.
+    IDS.lock().unwrap().sync_with_database();
+
+    // Creating the countries. The teams need them, so they go first.
+    let country_names = io::get_countries_from_name_files();
+    for name in country_names.iter() {
+        Country::build_and_save(name);
+    }
+
     add_competition_data(&today, &mut rng);
+    add_international_competitions(&today, &mut rng);
+    add_continental_competitions(&today, &mut rng);
+    add_junior_league(&today);
+    add_extra_leagues(extra_leagues, &today);
+    add_schedule_rules();
 
+    // Add parent IDs.
     let comps = COMPETITIONS.lock().unwrap().clone();
     for comp in comps.values() {
-        // Add parent IDs.
         comp.give_id_to_children_comps();
+    }
 
-        // Set up seasons, starting from the top level.
+    // Set up seasons, starting from the top level.
+    // Fetched again, so that child competitions know their parents and follow their rules.
+    let comps = COMPETITIONS.lock().unwrap().clone();
+    for comp in comps.values() {
         if comp.parent_comp_id == 0 {
             comp.setup_season(&mut Vec::new(), & mut rng);
         }
     }
 
-    // Creating the countries.
-    let country_names = io::get_countries_from_name_files();
-    for name in country_names.iter() {
-        Country::build_and_save(name);
-    }
+    add_affiliates();
 
-    // Generate 50 players per team.
-    for _ in 0..TEAMS.lock().unwrap().len() * 50 {
-        Player::build_and_save(&today, &mut rng, 16, 40);
+    add_roster_rules();
+
+    // Generate the players.
+    let players = Team::get_club_amount() * Population::PLAYERS_PER_TEAM;
+    let country_weights = Country::get_weights();
+    for _ in 0..players {
+        Player::build_and_save(&today, &mut rng, 16, 40, &country_weights);
     }
 
-    // Set up the teams.
+    // Set up the teams. National teams have no managers or boards of their own.
     let mut teams = TEAMS.lock().unwrap().clone();
+    teams.retain(|_, a| !a.is_national_team);
     for team in teams.values_mut() {
         team.setup(&today, &mut rng);
     }
+
+    // The boards can only know what to expect once every team has its players.
+    for id in teams.keys() {
+        let mut team = Team::fetch_from_db(id);
+        team.set_board_expectations();
+        team.save();
+    }
+
+    add_first_continental_cup_teams(&mut rng);
+
+    // The population is kept as it is at the start.
+    Population::set_baseline();
 }
 
 // Add competitions.
 // NOTE: Season window of the parent competition MUST go at least one day past the last day of the last stage.
 // Otherwise some contracts might expire before the last match day is played.
 fn add_competition_data(today: &Date, rng: &mut ThreadRng) {
+    let finland_id = Country::fetch_from_db_with_name("Finland").id;
+    let sweden_id = Country::fetch_from_db_with_name("Sweden").id;
+    let germany_id = Country::fetch_from_db_with_name("Germany").id;
+
     // 1: Liiga
-    Competition::build_and_save(
+    let mut phl = Competition::build_and_save(
         "PHL",
         vec![
-            Team::build_and_save("Ruiske"),     // 1
-            Team::build_and_save("Atomi"),      // 2
-            Team::build_and_save("Uupuneet"),   // 3
-            Team::build_and_save("SantaClaus"), // 4
-            Team::build_and_save("HardCore"),   // 5
-            Team::build_and_save("Ikirouta"),   // 6
-            Team::build_and_save("Kelarotat"),  // 7
-            Team::build_and_save("Vety"),       // 8
-            Team::build_and_save("Saappaat"),   // 9
-            Team::build_and_save("Siat"),       // 10
-            Team::build_and_save("Turmio"),     // 11
-            Team::build_and_save("Sirkus"),     // 12
-            Team::build_and_save("Polkka"),     // 13
-            Team::build_and_save("Teurastus"),  // 14
+            Team::build_and_save("Ruiske", finland_id),     // 1
+            Team::build_and_save("Atomi", finland_id),      // 2
+            Team::build_and_save("Uupuneet", finland_id),   // 3
+            Team::build_and_save("SantaClaus", finland_id), // 4
+            Team::build_and_save("HardCore", finland_id),   // 5
+            Team::build_and_save("Ikirouta", finland_id),   // 6
+            Team::build_and_save("Kelarotat", finland_id),  // 7
+            Team::build_and_save("Vety", finland_id),       // 8
+            Team::build_and_save("Saappaat", finland_id),   // 9
+            Team::build_and_save("Siat", finland_id),       // 10
+            Team::build_and_save("Turmio", finland_id),     // 11
+            Team::build_and_save("Sirkus", finland_id),     // 12
+            Team::build_and_save("Polkka", finland_id),     // 13
+            Team::build_and_save("Teurastus", finland_id),  // 14
         ],
         AnnualWindow::build(
             AnnualDate::build(9, 1),
             AnnualDate::build(6, 1)
         ),
-        Vec::new(),
+        vec![CompConnection::build([1, 4], 24, Seed::GetFromPosition, false)],
         0,
         None,
         vec![RankCriteria::ChildCompRanking],
         vec![2, 3],
         today
     );
+    phl.set_contract_rules(ContractRules::build(24, Some(100), true));
     // 2: Liiga Regular Season.
     Competition::build_and_save(
         "Regular Season",
@@ -168,7 +297,7 @@ fn add_competition_data(today: &Date, rng: &mut ThreadRng) {
         format::Format::build(
             Some(format::round_robin::RoundRobin::build(4, 0, 3, 2, 1, 1, 0)),
             None,
-            match_event::Rules::build(3, 1200, 300, false)
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
         ),
         vec![
             RankCriteria::Points,
@@ -184,6 +313,11 @@ fn add_competition_data(today: &Date, rng: &mut ThreadRng) {
         today
     );
     // 3: Liiga Playoffs.
+    // Seeds 7 to 10 play a best-of-three pity round, and the top six seeds get a bye to the quarter finals.
+    // After the pity round, the series are played in the 2-2-1-1-1 format.
+    // The best remaining seed picks its opponent on every round.
+    let mut best_of_seven = KnockoutRoundFormat::build(4);
+    best_of_seven.set_home_pattern(vec![2, 2, 1, 1, 1]);
     knockout_generator::build(
         "Playoffs",
         vec!["Pity Round"],
@@ -191,13 +325,617 @@ fn add_competition_data(today: &Date, rng: &mut ThreadRng) {
             AnnualDate::build(4, 1),
             AnnualDate::build(5, 31)
         ),
-        vec![match_event::Rules::build(3, 1200, 0, true)],
-        vec![2, 4],
-        vec![10],
+        vec![match_event::Rules::build(3, 1200, 0, true, 0.0)],
+        vec![KnockoutRoundFormat::build(2), best_of_seven],
+        BracketType::PickOpponent,
+        10,
+        Vec::new(),
         1,
+        0,
         Vec::new(),
         vec![RankCriteria::Seed],
         today,
         rng
+    ).unwrap();
+    // 8: Mestis
+    Competition::build_and_save(
+        "Mestis",
+        vec![
+            Team::build_and_save("Kirves", finland_id),     // 15
+            Team::build_and_save("Routa", finland_id),      // 16
+            Team::build_and_save("Pakkanen", finland_id),   // 17
+            Team::build_and_save("Kuokka", finland_id),     // 18
+            Team::build_and_save("Myrsky", finland_id),     // 19
+            Team::build_and_save("Tervas", finland_id),     // 20
+            Team::build_and_save("Hirvet", finland_id),     // 21
+            Team::build_and_save("Sammakot", finland_id),   // 22
+            Team::build_and_save("Kolina", finland_id),     // 23
+            Team::build_and_save("Rapakko", finland_id),    // 24
+            Team::build_and_save("Nuija", finland_id),      // 25
+            Team::build_and_save("Sohjo", finland_id),      // 26
+        ],
+        AnnualWindow::build(
+            AnnualDate::build(9, 1),
+            AnnualDate::build(6, 1)
+        ),
+        Vec::new(),
+        0,
+        None,
+        vec![RankCriteria::ChildCompRanking],
+        vec![9],
+        today
+    );
+    // 9: Mestis Regular Season.
+    Competition::build_and_save(
+        "Regular Season",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(9, 1),
+            AnnualDate::build(3, 31)
+        ),
+        Vec::new(),
+        12,
+        format::Format::build(
+            Some(format::round_robin::RoundRobin::build(4, 0, 3, 2, 1, 1, 0)),
+            None,
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
+        ),
+        vec![
+            RankCriteria::Points,
+            RankCriteria::GoalDifference,
+            RankCriteria::GoalsScored,
+            RankCriteria::TotalWins,
+            RankCriteria::RegularWins,
+            RankCriteria::OvertimeWins,
+            RankCriteria::Draws,
+            RankCriteria::RegularLosses,
+        ],
+        Vec::new(),
+        today
     );
-}
\ No newline at end of file
+    // 10: Elitserien
+    Competition::build_and_save(
+        "Elitserien",
+        vec![
+            Team::build_and_save("Algarna", sweden_id),     // 27
+            Team::build_and_save("Stormen", sweden_id),     // 28
+            Team::build_and_save("Frosten", sweden_id),     // 29
+            Team::build_and_save("Vargarna", sweden_id),    // 30
+            Team::build_and_save("Yxan", sweden_id),        // 31
+            Team::build_and_save("Askan", sweden_id),       // 32
+            Team::build_and_save("Bjornarna", sweden_id),   // 33
+            Team::build_and_save("Isbrytarna", sweden_id),  // 34
+            Team::build_and_save("Kylan", sweden_id),       // 35
+            Team::build_and_save("Tranorna", sweden_id),    // 36
+            Team::build_and_save("Lavinen", sweden_id),     // 37
+            Team::build_and_save("Orkanen", sweden_id),     // 38
+        ],
+        AnnualWindow::build(
+            AnnualDate::build(9, 15),
+            AnnualDate::build(5, 31)
+        ),
+        vec![CompConnection::build([1, 4], 24, Seed::GetFromPosition, false)],
+        0,
+        None,
+        vec![RankCriteria::ChildCompRanking],
+        vec![11, 12],
+        today
+    );
+    // 11: Elitserien Regular Season.
+    Competition::build_and_save(
+        "Regular Season",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(9, 15),
+            AnnualDate::build(3, 15)
+        ),
+        vec![CompConnection::build([1, 8], 12, Seed::GetFromPosition, false)],
+        12,
+        format::Format::build(
+            Some(format::round_robin::RoundRobin::build(4, 0, 3, 2, 1, 1, 0)),
+            None,
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
+        ),
+        vec![
+            RankCriteria::Points,
+            RankCriteria::GoalDifference,
+            RankCriteria::GoalsScored,
+            RankCriteria::TotalWins,
+            RankCriteria::RegularWins,
+            RankCriteria::OvertimeWins,
+            RankCriteria::Draws,
+            RankCriteria::RegularLosses,
+        ],
+        Vec::new(),
+        today
+    );
+    // 12: Elitserien Playoffs.
+    // The series are played in the 2-3-2 format, and the teams are reseeded after every round.
+    let mut best_of_seven = KnockoutRoundFormat::build(4);
+    best_of_seven.set_home_pattern(vec![2, 3, 2]);
+    knockout_generator::build(
+        "Playoffs",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(3, 20),
+            AnnualDate::build(5, 20)
+        ),
+        vec![match_event::Rules::build(3, 1200, 0, true, 0.0)],
+        vec![best_of_seven],
+        BracketType::Reseed,
+        8,
+        Vec::new(),
+        1,
+        0,
+        Vec::new(),
+        vec![RankCriteria::Seed],
+        today,
+        rng
+    ).unwrap();
+    // 16: Eisliga
+    Competition::build_and_save(
+        "Eisliga",
+        vec![
+            Team::build_and_save("Sturm", germany_id),      // 39
+            Team::build_and_save("Gletscher", germany_id),  // 40
+            Team::build_and_save("Lawine", germany_id),     // 41
+            Team::build_and_save("Frost", germany_id),      // 42
+            Team::build_and_save("Hammer", germany_id),     // 43
+            Team::build_and_save("Keiler", germany_id),     // 44
+            Team::build_and_save("Falken", germany_id),     // 45
+            Team::build_and_save("Pinguine", germany_id),   // 46
+            Team::build_and_save("Blitz", germany_id),      // 47
+            Team::build_and_save("Wolfe", germany_id),      // 48
+        ],
+        AnnualWindow::build(
+            AnnualDate::build(10, 1),
+            AnnualDate::build(5, 1)
+        ),
+        vec![CompConnection::build([1, 4], 24, Seed::GetFromPosition, false)],
+        0,
+        None,
+        vec![RankCriteria::ChildCompRanking],
+        vec![17],
+        today
+    );
+    // 17: Eisliga Regular Season.
+    Competition::build_and_save(
+        "Regular Season",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(10, 1),
+            AnnualDate::build(4, 15)
+        ),
+        Vec::new(),
+        10,
+        format::Format::build(
+            Some(format::round_robin::RoundRobin::build(4, 0, 3, 2, 1, 1, 0)),
+            None,
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
+        ),
+        vec![
+            RankCriteria::Points,
+            RankCriteria::GoalDifference,
+            RankCriteria::GoalsScored,
+            RankCriteria::TotalWins,
+            RankCriteria::RegularWins,
+            RankCriteria::OvertimeWins,
+            RankCriteria::Draws,
+            RankCriteria::RegularLosses,
+        ],
+        Vec::new(),
+        today
+    );
+}
+
+// Add the international tournaments and the national teams that play in them.
+// The countries most likely to produce players get to take part.
+fn add_international_competitions(today: &Date, rng: &mut ThreadRng) {
+    let mut weights = Country::get_weights();
+    weights.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let teams: Vec<Team> = weights.iter().take(8).map(|(id, _)| Team::build_and_save_national(&Country::fetch_from_db(id))).collect();
+
+    // 18: World Championship
+    Competition::build_and_save(
+        "World Championship",
+        teams,
+        AnnualWindow::build(
+            AnnualDate::build(5, 5),
+            AnnualDate::build(5, 25)
+        ),
+        Vec::new(),
+        0,
+        None,
+        vec![RankCriteria::ChildCompRanking],
+        vec![19, 20, 21],
+        today
+    );
+    // 19: World Championship Group A.
+    Competition::build_and_save(
+        "Group A",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(5, 5),
+            AnnualDate::build(5, 15)
+        ),
+        vec![CompConnection::build([1, 2], 21, Seed::GetFromPosition, false)],
+        4,
+        format::Format::build(
+            Some(format::round_robin::RoundRobin::build(2, 0, 3, 2, 1, 1, 0)),
+            None,
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
+        ),
+        vec![
+            RankCriteria::Points,
+            RankCriteria::GoalDifference,
+            RankCriteria::GoalsScored,
+            RankCriteria::TotalWins,
+            RankCriteria::RegularWins,
+            RankCriteria::OvertimeWins,
+            RankCriteria::Draws,
+            RankCriteria::RegularLosses,
+        ],
+        Vec::new(),
+        today
+    );
+    // 20: World Championship Group B.
+    Competition::build_and_save(
+        "Group B",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(5, 5),
+            AnnualDate::build(5, 15)
+        ),
+        vec![CompConnection::build([1, 2], 21, Seed::GetFromPosition, false)],
+        4,
+        format::Format::build(
+            Some(format::round_robin::RoundRobin::build(2, 0, 3, 2, 1, 1, 0)),
+            None,
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
+        ),
+        vec![
+            RankCriteria::Points,
+            RankCriteria::GoalDifference,
+            RankCriteria::GoalsScored,
+            RankCriteria::TotalWins,
+            RankCriteria::RegularWins,
+            RankCriteria::OvertimeWins,
+            RankCriteria::Draws,
+            RankCriteria::RegularLosses,
+        ],
+        Vec::new(),
+        today
+    );
+    // 21: World Championship Playoffs.
+    // The bracket is fixed, with the group winners facing the runners-up in the semi finals.
+    knockout_generator::build(
+        "Playoffs",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(5, 17),
+            AnnualDate::build(5, 25)
+        ),
+        vec![match_event::Rules::build(3, 1200, 0, true, 0.0)],
+        vec![KnockoutRoundFormat::build_neutral_single_game()],
+        BracketType::Fixed,
+        4,
+        Vec::new(),
+        1,
+        0,
+        Vec::new(),
+        vec![RankCriteria::Seed],
+        today,
+        rng
+    ).unwrap();
+}
+
+// Add the league for the junior teams of the PHL clubs.
+fn add_junior_league(today: &Date) {
+    let finland_id = Country::fetch_from_db_with_name("Finland").id;
+    let teams: Vec<Team> = Competition::fetch_from_db(&1).get_teams().iter().map(|a| Team::build_and_save(&format!("{} U20", a.name), finland_id)).collect();
+    let no_of_teams = convert::int::<usize, u8>(teams.len());
+
+    // 28: U20 SM-sarja
+    Competition::build_and_save(
+        "U20 SM-sarja",
+        teams,
+        AnnualWindow::build(
+            AnnualDate::build(9, 1),
+            AnnualDate::build(6, 1)
+        ),
+        Vec::new(),
+        0,
+        None,
+        vec![RankCriteria::ChildCompRanking],
+        vec![29],
+        today
+    );
+    // 29: U20 SM-sarja Regular Season.
+    Competition::build_and_save(
+        "Regular Season",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(9, 1),
+            AnnualDate::build(3, 31)
+        ),
+        Vec::new(),
+        no_of_teams,
+        format::Format::build(
+            Some(format::round_robin::RoundRobin::build(2, 0, 3, 2, 1, 1, 0)),
+            None,
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
+        ),
+        vec![
+            RankCriteria::Points,
+            RankCriteria::GoalDifference,
+            RankCriteria::GoalsScored,
+            RankCriteria::TotalWins,
+            RankCriteria::RegularWins,
+            RankCriteria::OvertimeWins,
+            RankCriteria::Draws,
+            RankCriteria::RegularLosses,
+        ],
+        Vec::new(),
+        today
+    );
+}
+
+// Add leagues of generated teams, each with a regular season of its own.
+fn add_extra_leagues(amount: usize, today: &Date) {
+    const TEAMS_PER_LEAGUE: usize = 16;
+
+    let finland_id = Country::fetch_from_db_with_name("Finland").id;
+    for i in 1..=amount {
+        let teams = (1..=TEAMS_PER_LEAGUE).map(|j| Team::build_and_save(&format!("Team {i}-{j}"), finland_id)).collect();
+        let mut league = Competition::build_and_save(
+            &format!("Extra League {i}"),
+            teams,
+            AnnualWindow::build(
+                AnnualDate::build(9, 1),
+                AnnualDate::build(6, 1)
+            ),
+            Vec::new(),
+            0,
+            None,
+            vec![RankCriteria::ChildCompRanking],
+            Vec::new(),
+            today
+        );
+
+        let regular_season = Competition::build_and_save(
+            "Regular Season",
+            Vec::new(),
+            AnnualWindow::build(
+                AnnualDate::build(9, 1),
+                AnnualDate::build(3, 31)
+            ),
+            Vec::new(),
+            convert::int::<usize, u8>(TEAMS_PER_LEAGUE),
+            format::Format::build(
+                Some(format::round_robin::RoundRobin::build(2, 0, 3, 2, 1, 1, 0)),
+                None,
+                match_event::Rules::build(3, 1200, 300, false, 0.0)
+            ),
+            vec![
+                RankCriteria::Points,
+                RankCriteria::GoalDifference,
+                RankCriteria::GoalsScored,
+            ],
+            Vec::new(),
+            today
+        );
+
+        league.child_comp_ids = vec![regular_season.id];
+        league.set_roster_rules(RosterRules::build(20, 30, 2, 4, 4));
+    }
+}
+
+// Give the PHL teams their farm teams from Mestis.
+// Teams without a pair in Mestis go without a farm team.
+fn add_affiliates() {
+    let parent_teams = Competition::fetch_from_db(&1).get_teams();
+    let farm_teams = Competition::fetch_from_db(&8).get_teams();
+
+    for (mut parent_team, mut farm_team) in zip(parent_teams, farm_teams) {
+        parent_team.set_farm_team(&mut farm_team);
+        parent_team.save();
+        farm_team.save();
+    }
+}
+
+// Add the club competitions played between the leagues.
+// The best four teams of each top league qualify for the next season.
+fn add_continental_competitions(today: &Date, rng: &mut ThreadRng) {
+    // 24: Champions Cup
+    Competition::build_and_save(
+        "Champions Cup",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(9, 1),
+            AnnualDate::build(2, 20)
+        ),
+        Vec::new(),
+        12,
+        None,
+        vec![RankCriteria::ChildCompRanking],
+        vec![25, 26, 27],
+        today
+    );
+    // 25: Champions Cup Group A.
+    Competition::build_and_save(
+        "Group A",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(9, 1),
+            AnnualDate::build(12, 15)
+        ),
+        vec![CompConnection::build([1, 2], 27, Seed::GetFromPosition, false)],
+        6,
+        format::Format::build(
+            Some(format::round_robin::RoundRobin::build(2, 0, 3, 2, 1, 1, 0)),
+            None,
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
+        ),
+        vec![
+            RankCriteria::Points,
+            RankCriteria::GoalDifference,
+            RankCriteria::GoalsScored,
+            RankCriteria::TotalWins,
+            RankCriteria::RegularWins,
+            RankCriteria::OvertimeWins,
+            RankCriteria::Draws,
+            RankCriteria::RegularLosses,
+        ],
+        Vec::new(),
+        today
+    );
+    // 26: Champions Cup Group B.
+    Competition::build_and_save(
+        "Group B",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(9, 1),
+            AnnualDate::build(12, 15)
+        ),
+        vec![CompConnection::build([1, 2], 27, Seed::GetFromPosition, false)],
+        6,
+        format::Format::build(
+            Some(format::round_robin::RoundRobin::build(2, 0, 3, 2, 1, 1, 0)),
+            None,
+            match_event::Rules::build(3, 1200, 300, false, 0.0)
+        ),
+        vec![
+            RankCriteria::Points,
+            RankCriteria::GoalDifference,
+            RankCriteria::GoalsScored,
+            RankCriteria::TotalWins,
+            RankCriteria::RegularWins,
+            RankCriteria::OvertimeWins,
+            RankCriteria::Draws,
+            RankCriteria::RegularLosses,
+        ],
+        Vec::new(),
+        today
+    );
+    // 27: Champions Cup Playoffs.
+    // The semi finals are two-legged ties, with overtime and a shootout in the second leg if needed.
+    // The final is a single game at a neutral venue, and so is the third place game played alongside it.
+    knockout_generator::build(
+        "Playoffs",
+        Vec::new(),
+        AnnualWindow::build(
+            AnnualDate::build(1, 13),
+            AnnualDate::build(2, 17)
+        ),
+        vec![match_event::Rules::build(3, 1200, 600, false, 0.0), match_event::Rules::build(3, 1200, 0, true, 0.0)],
+        vec![KnockoutRoundFormat::build_two_legged(TieBreaker::Overtime), KnockoutRoundFormat::build_neutral_single_game()],
+        BracketType::Draw,
+        4,
+        Vec::new(),
+        1,
+        3,
+        Vec::new(),
+        vec![RankCriteria::Seed],
+        today,
+        rng
+    ).unwrap();
+}
+
+// There are no final standings before the first season, so the teams expected to do best play in the first Champions Cup.
+fn add_first_continental_cup_teams(rng: &mut ThreadRng) {
+    let mut teams = Vec::new();
+    for league_id in [1, 10, 16] {
+        let mut league_teams = Competition::fetch_from_db(&league_id).get_teams();
+        league_teams.sort_by(|a, b| a.board.expected_rank.cmp(&b.board.expected_rank));
+        for (i, team) in league_teams.iter().take(4).enumerate() {
+            teams.push(TeamCompData::build(team.id, convert::int::<usize, u8>(i + 1)));
+        }
+    }
+
+    Competition::fetch_from_db(&24).setup_season(&mut teams, rng);
+}
+
+// Set the rules for when the games of the competitions are played.
+// These must be in place before the first schedules are generated.
+fn add_schedule_rules() {
+    // The leagues take a break over Christmas, and leave the start of the week free for the Champions Cup.
+    let christmas = AnnualWindow::build(AnnualDate::build(12, 23), AnnualDate::build(12, 26));
+    let league_game_days = [
+        (1, vec![Weekday::Thursday, Weekday::Friday, Weekday::Saturday]),
+        (8, vec![Weekday::Wednesday, Weekday::Friday, Weekday::Saturday]),
+        (10, vec![Weekday::Thursday, Weekday::Saturday, Weekday::Sunday]),
+        (16, vec![Weekday::Friday, Weekday::Sunday]),
+        (28, vec![Weekday::Saturday, Weekday::Sunday]),
+    ];
+
+    for (id, game_days) in league_game_days {
+        let mut rules = ScheduleRules::build(game_days, 0, Some(4));
+        rules.add_blackout(christmas.clone());
+        Competition::fetch_from_db(&id).set_schedule_rules(rules);
+    }
+
+    // The Champions Cup is played mid-week, with a day of rest around the league games.
+    Competition::fetch_from_db(&24).set_schedule_rules(ScheduleRules::build(vec![Weekday::Tuesday, Weekday::Wednesday], 1, None));
+}
+
+// Set the roster rules of the leagues.
+fn add_roster_rules() {
+    let finland_id = Country::fetch_from_db_with_name("Finland").id;
+
+    let mut phl_rules = RosterRules::build(22, 32, 2, 4, 4);
+    phl_rules.set_import_limit(finland_id, 14);
+    Competition::fetch_from_db(&1).set_roster_rules(phl_rules);
+
+    let mut mestis_rules = RosterRules::build(20, 30, 2, 3, 4);
+    mestis_rules.set_import_limit(finland_id, 10);
+    Competition::fetch_from_db(&8).set_roster_rules(mestis_rules);
+
+    let mut elitserien_rules = RosterRules::build(22, 30, 2, 4, 4);
+    elitserien_rules.set_import_limit(Country::fetch_from_db_with_name("Sweden").id, 12);
+    Competition::fetch_from_db(&10).set_roster_rules(elitserien_rules);
+
+    let mut eisliga_rules = RosterRules::build(20, 28, 2, 4, 4);
+    eisliga_rules.set_import_limit(Country::fetch_from_db_with_name("Germany").id, 9);
+    Competition::fetch_from_db(&16).set_roster_rules(eisliga_rules);
+
+    // Only juniors play in the junior league.
+    let mut junior_rules = RosterRules::build(20, 26, 2, 4, 4);
+    junior_rules.set_max_age(20);
+    Competition::fetch_from_db(&28).set_roster_rules(junior_rules);
+
+    // National teams have no imports.
+    Competition::fetch_from_db(&18).set_roster_rules(RosterRules::build(20, 25, 2, 4, 4));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::legacy;
+
+    #[test]
+    fn ids_only_go_up_for_each_kind() {
+        let mut ids = IdAllocator::default();
+        assert_eq!(ids.next(IdKind::Player), 1);
+        assert_eq!(ids.next(IdKind::Player), 2);
+        assert_eq!(ids.next(IdKind::Team), 1);
+        assert_eq!(ids.next(IdKind::Player), 3);
+        assert_eq!(ids.next(IdKind::Manager), 1);
+        assert_eq!(ids.next(IdKind::Team), 2);
+    }
+
+    #[test]
+    fn new_ids_come_after_loaded_ones() {
+        // Teams loaded from an older save file, with the narrow IDs converted.
+        let old_ids: [legacy::TeamId; 3] = [3, 250, 17];
+        let table: Mutex<HashMap<TeamId, &str>> = Mutex::new(old_ids.iter().map(|a| (convert::legacy_id::<legacy::TeamId, TeamId>(*a), "team")).collect());
+        assert_eq!(get_max_id(&table), 250);
+
+        let mut ids = IdAllocator::default();
+        ids.move_past(IdKind::Team, get_max_id(&table));
+        assert_eq!(ids.next(IdKind::Team), 251);
+
+        // Nothing loaded leaves the IDs as they are.
+        ids.move_past(IdKind::Team, get_max_id(&Mutex::new(HashMap::<TeamId, &str>::new())));
+        assert_eq!(ids.next(IdKind::Team), 252);
+    }
+}
@@ -27,9 +27,9 @@ impl Type {    // Basics.
         }
     }
 
-    // Fetch the EventType from the database.
-    pub fn fetch_from_db(id: &Id) -> Self {
-        database::EVENT_TYPES.get(id).expect(&format!("no EventType with id {id:?}")).clone()
+    // Get the EventType from the database without cloning it.
+    pub fn get(id: &Id) -> &'static Self {
+        database::EVENT_TYPES.get(id).unwrap_or_else(|| panic!("no EventType with id {id:?}"))
     }
 }
 
@@ -42,7 +42,7 @@ impl Type {
     min_boundary: The resulting likelihood when modifier is 0.0.
     max_boundary: The resulting likelihood when modifier is 1.0.
     */
-    fn calculate_likelihood(&self, modifier: f64) -> f64 {
+    pub fn calculate_likelihood(&self, modifier: f64) -> f64 {
         if modifier == 0.0 {return self.min_boundary}
         else if modifier == 0.5 {return self.equilibrium}
         else if modifier == 1.0 {return self.max_boundary}
@@ -61,7 +61,7 @@ impl Type {
     }
 
     // Get an outcome of the event that is either true or false.
-    pub fn get_outcome(&mut self, modifier: f64, rng: &mut ThreadRng) -> bool {
+    pub fn get_outcome(&self, modifier: f64, rng: &mut ThreadRng) -> bool {
         return rng.random_bool(self.calculate_likelihood(modifier))
     }
 }
\ No newline at end of file
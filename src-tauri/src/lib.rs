@@ -38,12 +38,31 @@ pub fn run() {
             commands::get_comp_select_package,
             commands::get_team_select_package,
             commands::get_comp_screen_package,
+            commands::get_projection_package,
             commands::get_team_screen_package,
             commands::get_player_package,
             commands::create_human_manager,
             commands::get_human_package,
             commands::get_free_agents_package,
             commands::offer_contract,
+            commands::counter_offer,
+            commands::accept_demands,
+            commands::withdraw_offer,
+            commands::tender_qualifying_offer,
+            commands::match_offer_sheet,
+            commands::decline_offer_sheet,
+            commands::release_player,
+            commands::buy_out_player,
+            commands::claim_waived_player,
+            commands::get_waiver_wire_package,
+            commands::get_retired_players_package,
+            commands::send_down_player,
+            commands::call_up_player,
+            commands::loan_player,
+            commands::get_job_market_package,
+            commands::apply_for_job,
+            commands::retire_human_manager,
+            commands::get_season_review_package,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,6 +1,6 @@
 // Game cache.
 
-use crate::{match_event::{self, team::{cache::TeamGameDataCache, TeamGameData}}, team::lineup::LineUp};
+use crate::{match_event::{self, table::{ProbabilityTable, ShiftOdds}, team::{cache::TeamGameDataCache, TeamGameData}}, team::lineup::LineUp};
 
 #[derive(Debug)]
 #[derive(Default, Clone)]
@@ -8,6 +8,7 @@ pub struct GameCache {
     pub home: TeamGameDataCache,
     pub away: TeamGameDataCache,
     pub rules: match_event::Rules,
+    pub table: ProbabilityTable,
 }
 
 impl GameCache {
@@ -16,11 +17,34 @@ impl GameCache {
             home: TeamGameDataCache::build(home),
             away: TeamGameDataCache::build(away),
             rules: rules.clone(),
+            ..Default::default()
         }
     }
 
-    pub fn build_lineups(&mut self, home: &LineUp, away: &LineUp) {
+    // Build the cache straight from the lineups, without the database.
+    #[cfg(test)]
+    pub fn build_for_tests(home: crate::team::lineup::cache::LineUpCache, away: crate::team::lineup::cache::LineUpCache, rules: &match_event::Rules) -> Self {
+        let table = ProbabilityTable::build(&home, &away, rules, false);
+        let mut cache = Self {
+            rules: rules.clone(),
+            table: table,
+            ..Default::default()
+        };
+
+        cache.home.lineup = home;
+        cache.away.lineup = away;
+        return cache;
+    }
+
+    // The probability table can only be built once the lineups are known.
+    pub fn build_lineups(&mut self, home: &LineUp, away: &LineUp, is_neutral_venue: bool) {
         self.home.build_lineup(home);
         self.away.build_lineup(away);
+        self.table = ProbabilityTable::build(&self.home.lineup, &self.away.lineup, &self.rules, is_neutral_venue);
     }
-}
\ No newline at end of file
+
+    // Get the chances of what happens with the lines that are currently on ice.
+    pub fn get_shift_odds(&self) -> &ShiftOdds {
+        self.table.get(self.home.lineup.get_line_index(), self.away.lineup.get_line_index())
+    }
+}
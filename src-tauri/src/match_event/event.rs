@@ -1,6 +1,6 @@
 // An event is anything worth of writing down that happens during a match.
 // Shot, goal, penalty, etc.
-use rand::{rngs::ThreadRng, seq::IndexedRandom};
+use rand::{Rng, rngs::ThreadRng};
 use crate::{event, match_event::Clock, person::player::Player, team::lineup::cache::PlayersOnIceCache, types::PlayerId};
 
 #[derive(Debug)]
@@ -66,17 +66,23 @@ impl Shot {
     }
 
     // Do the building, calculating, simulating, everything, here.
-    pub fn simulate(time: Clock, attackers: &PlayersOnIceCache, defenders: &PlayersOnIceCache, rng: &mut ThreadRng) -> Self {
+    // The chances of the skaters to score are given in the same order as the skaters.
+    pub fn simulate(time: Clock, attackers: &PlayersOnIceCache, defenders: &PlayersOnIceCache, goal_likelihoods: &[f64], rng: &mut ThreadRng) -> Self {
         let attacking_ids = attackers.get_ids();
         let defending_ids = defenders.get_ids();
         let mut shot = Self::build(time, attacking_ids, defending_ids);
 
-        let shooter_and_assisters = shot.create_shooter_and_assisters(attackers, rng);
-        shot.calculate_goal(&shooter_and_assisters, defenders, rng);
+        let shooter_index = shot.create_shooter_and_assisters(attackers, rng);
+        shot.is_goal = rng.random_bool(goal_likelihoods[shooter_index]);
 
         return shot;
     }
 
+    // Get the ID of the player who took the shot.
+    pub fn get_shooter_id(&self) -> PlayerId {
+        self.shooter_id
+    }
+
     // Get shooter object.
     fn get_shooter(&self) -> Player {
         Player::fetch_from_db(&self.shooter_id).unwrap()
@@ -97,56 +103,52 @@ impl Shot {
 }
 
 impl Shot {
-    // Determine who shoots and who assists.
+    // Determine who shoots and who assists, and return the index of the shooter among the skaters.
     // Completely random for now.
-    fn create_shooter_and_assisters(&mut self, attackers: &PlayersOnIceCache, rng: &mut ThreadRng) -> Vec<Player> {
-        let players = attackers.create_vector_of_skaters();
-        let mut shooter_and_assisters_ids = Vec::new();
-
-        let mut shooter_and_assisters = Vec::new();
+    fn create_shooter_and_assisters(&mut self, attackers: &PlayersOnIceCache, rng: &mut ThreadRng) -> usize {
+        let players = attackers.get_skaters();
+        if players.is_empty() {
+            panic!("could not choose Player, players on ice: {attackers:#?}");
+        }
 
+        let mut shooter_index = 0;
         for i in 0..3 {
-            let chosen = players.choose(rng)
-                .expect(&format!("could not choose Player. iteration: {i}, players.len(): {}, players on ice: {attackers:#?}", players.len()));
+            let index = rng.random_range(0..players.len());
+            let id = players[index].id;
 
-            let id = chosen.id;
-
-
-            if shooter_and_assisters_ids.contains(&id) {
+            if id == self.shooter_id || self.assister_ids.contains(&id) {
                 break;
             }
-            else {
-                shooter_and_assisters_ids.push(id);
-                shooter_and_assisters.push(chosen.clone());
-            }
-        }
-
-        for id in shooter_and_assisters_ids {
-            if self.shooter_id == 0 {
+            else if i == 0 {
                 self.shooter_id = id;
+                shooter_index = index;
             }
             else {
                 self.assister_ids.push(id);
             }
         }
 
-        return shooter_and_assisters;
+        return shooter_index;
     }
 
-    // Check if the shot ends up in goal.
+    // Get the chance of each skater to score with a shot, in the same order as the skaters.
     // Only taking shooter into account for now.
-    fn calculate_goal(&mut self, shooter_and_assisters: &[Player], defenders: &PlayersOnIceCache, rng: &mut ThreadRng) {
-        let gk_ability = defenders.gk.as_ref().unwrap().ability.get() as f64;
-        let shooter_ability = shooter_and_assisters[0].ability.get() as f64;
-        let total_ability = gk_ability + shooter_ability;
-        let modifier;
-
-        if total_ability == 0.0 { modifier = 0.5 }
-        else { modifier = shooter_ability / total_ability }
-
-        if event::Type::fetch_from_db(&event::Id::Goal).get_outcome(modifier, rng) {
-            self.is_goal = true;
-        }
+    pub fn get_goal_likelihoods(attackers: &PlayersOnIceCache, defenders: &PlayersOnIceCache) -> Vec<f64> {
+        let goal = event::Type::get(&event::Id::Goal);
+
+        // An empty net does not stop anything.
+        let gk_ability = defenders.gk.as_ref().map_or(0.0, |a| a.ability.get() as f64);
+
+        attackers.get_skaters().iter().map(|a| {
+            let shooter_ability = a.ability.get() as f64;
+            let total_ability = gk_ability + shooter_ability;
+            let modifier = match total_ability {
+                0.0 => 0.5,
+                _ => shooter_ability / total_ability
+            };
+
+            goal.calculate_likelihood(modifier)
+        }).collect()
     }
 }
 
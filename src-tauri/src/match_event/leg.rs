@@ -0,0 +1,104 @@
+// Games that are a part of a two-legged tie.
+
+use std::sync::Arc;
+
+use crate::{competition::format::knockout_round::TieBreaker, match_event::Game, types::TeamId};
+
+// Which leg of a two-legged tie the game is.
+#[derive(Debug)]
+#[derive(Default, Clone, PartialEq)]
+pub enum Leg {
+    #[default]
+    Single,             // The game is not a part of a two-legged tie.
+    First,              // Can end in a draw, as the tie is only decided in the second leg.
+    Second(TieBreaker), // Continues past regular time only if the tie is still even.
+}
+
+impl Leg {
+    // Check if the goals of the home and the away team leave the game even, so that it cannot be decided yet.
+    // In second legs, the goals of the earlier legs count too.
+    pub fn is_tied(&self, goals: [u16; 2], earlier_legs: &EarlierLegs) -> bool {
+        match self {
+            Self::Second(tie_breaker) => {
+                let is_aggregate_tied = goals[0] + earlier_legs.goals[0] == goals[1] + earlier_legs.goals[1];
+                let is_away_goals_tied = earlier_legs.away_goals[0] == goals[1] + earlier_legs.away_goals[1];
+                is_aggregate_tied && (*tie_breaker != TieBreaker::AwayGoals || is_away_goals_tied)
+            },
+            _ => goals[0] == goals[1]
+        }
+    }
+}
+
+// Goals the teams of a second leg scored in the earlier legs.
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct EarlierLegs {
+    pub goals: [u16; 2],        // Goals of the home and the away team of the second leg.
+    pub away_goals: [u16; 2],   // Goals of the home and the away team of the second leg in their away games.
+}
+
+impl EarlierLegs {
+    // Collect the goals from the earlier games between the two teams.
+    pub fn build(home_id: TeamId, away_id: TeamId, played_games: &[Arc<Game>]) -> Self {
+        let mut earlier_legs = Self::default();
+        for game in played_games.iter().filter(|a| a.is_team_playing(home_id) && a.is_team_playing(away_id)) {
+            for (i, id) in [home_id, away_id].into_iter().enumerate() {
+                if game.home.team_id == id {
+                    earlier_legs.goals[i] += game.home.get_goal_amount();
+                }
+                else {
+                    earlier_legs.goals[i] += game.away.get_goal_amount();
+                    earlier_legs.away_goals[i] += game.away.get_goal_amount();
+                }
+            }
+        }
+
+        return earlier_legs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_earlier_legs(goals: [u16; 2], away_goals: [u16; 2]) -> EarlierLegs {
+        EarlierLegs { goals: goals, away_goals: away_goals }
+    }
+
+    #[test]
+    fn single_games_and_first_legs_are_tied_on_even_goals() {
+        let earlier_legs = EarlierLegs::default();
+        for leg in [Leg::Single, Leg::First] {
+            assert!(leg.is_tied([2, 2], &earlier_legs));
+            assert!(!leg.is_tied([3, 2], &earlier_legs));
+        }
+    }
+
+    #[test]
+    fn second_legs_count_the_aggregate() {
+        // The home team of the second leg lost the first leg 1-2 away.
+        let earlier_legs = build_earlier_legs([1, 2], [1, 0]);
+        let leg = Leg::Second(TieBreaker::Overtime);
+
+        assert!(leg.is_tied([1, 0], &earlier_legs));
+        assert!(leg.is_tied([3, 2], &earlier_legs));
+        assert!(!leg.is_tied([1, 1], &earlier_legs));
+        assert!(!leg.is_tied([2, 0], &earlier_legs));
+    }
+
+    #[test]
+    fn away_goals_break_aggregate_ties() {
+        // The home team of the second leg lost the first leg 1-2 away.
+        let earlier_legs = build_earlier_legs([1, 2], [1, 0]);
+        let leg = Leg::Second(TieBreaker::AwayGoals);
+
+        // 2-2 on aggregate, but only the home team has scored away.
+        assert!(!leg.is_tied([1, 0], &earlier_legs));
+
+        // 3-3 on aggregate, one away goal each.
+        assert!(leg.is_tied([2, 1], &earlier_legs));
+
+        // Away goals do not matter when the aggregate is not even.
+        assert!(!leg.is_tied([1, 1], &earlier_legs));
+    }
+}
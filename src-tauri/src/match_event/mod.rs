@@ -1,18 +1,25 @@
 pub mod event;
 pub mod team;
+pub mod leg;
+pub mod prediction;
+pub mod table;
 mod cache;
 
-use rand::rngs::ThreadRng;
+use std::sync::Arc;
+
+use rand::{Rng, rngs::ThreadRng};
 use serde_json::json;
 
 use crate::{
-    competition::{season::team::TeamCompData, Competition}, database::COMPETITIONS, event as logic_event, match_event::cache::GameCache, types::{
-        convert, CompetitionId, TeamId
+    competition::{season::team::TeamCompData, Competition}, database::COMPETITIONS, match_event::cache::GameCache, person::player::Player, team::Team, types::{
+        convert, CompetitionId, PlayerId, TeamId
     }
 };
 use self::{
     team::TeamGameData,
-    event::Shot
+    event::Shot,
+    leg::{EarlierLegs, Leg},
+    prediction::{Prediction, draw_amount}
 };
 
 #[derive(Debug)]
@@ -24,6 +31,15 @@ enum Attacker {
     Away,
 }
 
+// How a game is played.
+#[derive(Debug)]
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum SimMode {
+    #[default]
+    Full,   // Second by second.
+    Quick,  // The regular time is resolved at once, with the same chances as second by second.
+}
+
 #[derive(Debug)]
 #[derive(Default, Clone)]
 pub struct Game {
@@ -34,6 +50,10 @@ pub struct Game {
     comp_id: CompetitionId,
     cache: Option<GameCache>,
     attacker: Attacker,
+    pub leg: Leg,
+    earlier_legs: EarlierLegs,  // Only used in second legs.
+    pub is_neutral_venue: bool,
+    pub prediction: Option<Prediction>, // None until the game has been predicted.
 }
 
 // Basics.
@@ -70,7 +90,10 @@ impl Game {
             "away": self.away.get_comp_screen_json(),
             "date": self.date,
             "had_overtime": self.has_overtime(),
-            "is_over": self.clock != Clock::default()
+            "had_shootout": self.has_shootout(),
+            "is_neutral_venue": self.is_neutral_venue,
+            "is_over": self.clock != Clock::default(),
+            "prediction": self.prediction
         })
     }
 }
@@ -82,6 +105,26 @@ impl Game {
         team_id == self.home.team_id || team_id == self.away.team_id
     }
 
+    // Collect the results of the earlier legs, if the game is a second leg.
+    pub fn set_earlier_legs(&mut self, played_games: &[Arc<Game>]) {
+        if let Leg::Second(_) = self.leg {
+            self.earlier_legs = EarlierLegs::build(self.home.team_id, self.away.team_id, played_games);
+        }
+    }
+
+    // Check if the game is even, so that it cannot be decided yet.
+    fn is_tied(&self) -> bool {
+        self.leg.is_tied([self.home.get_goal_amount(), self.away.get_goal_amount()], &self.earlier_legs)
+    }
+
+    // Check if the game must have a winner even when overtime runs out.
+    fn needs_winner(&self) -> bool {
+        match self.leg {
+            Leg::Second(_) => true,
+            _ => false
+        }
+    }
+
     // Call when both teams must submit their lineups.
     fn get_team_lineups(&mut self) {
         let cache = self.cache.as_mut().unwrap();
@@ -93,7 +136,7 @@ impl Game {
         self.home.lineup = cache.home.team.lineup.clone();
         self.away.lineup = cache.away.team.lineup.clone();
 
-        self.cache.as_mut().unwrap().build_lineups(&self.home.lineup, &self.away.lineup);
+        self.cache.as_mut().unwrap().build_lineups(&self.home.lineup, &self.away.lineup, self.is_neutral_venue);
     }
 
     // Do things like submitting lineups.
@@ -121,26 +164,100 @@ impl Game {
 
         // Update the teams' comp datas.
         // self.get_comp().update_teamdata(&self.home, &self.away, self.has_overtime());
+
+        self.update_ratings();
+    }
+
+    // Update the ratings of both teams with the result of the game.
+    fn update_ratings(&self) {
+        let advantage = match self.is_neutral_venue {
+            true => 0.0,
+            _ => self.get_rules().get_home_rating_advantage()
+        };
+
+        let away_rating = Team::read_from_db(&self.away.team_id, |a| a.rating.clone());
+        let expected_score = Team::read_from_db(&self.home.team_id, |a| a.rating.get_expected_score(&away_rating, advantage));
+        let score = self.get_home_rating_score();
+
+        Team::update_in_db(&self.home.team_id, |a| a.rating.update(expected_score, score, &self.date));
+        Team::update_in_db(&self.away.team_id, |a| a.rating.update(1.0 - expected_score, 1.0 - score, &self.date));
+    }
+
+    // Get the score of the home team for the ratings, from 0 for a loss to 1 for a win.
+    // Wins in overtime or in a shootout are worth less than wins in regular time.
+    fn get_home_rating_score(&self) -> f64 {
+        let home_won = match self.home.get_goal_amount().cmp(&self.away.get_goal_amount()) {
+            std::cmp::Ordering::Greater => Some(true),
+            std::cmp::Ordering::Less => Some(false),
+            _ if self.has_shootout() => Some(self.home.shootout_goals > self.away.shootout_goals),
+            _ => None
+        };
+
+        match (home_won, self.has_overtime() || self.has_shootout()) {
+            (Some(true), false) => 1.0,
+            (Some(true), true) => 0.75,
+            (Some(false), true) => 0.25,
+            (Some(false), false) => 0.0,
+            (None, _) => 0.5
+        }
     }
 
     // Play the game.
-    pub fn play(&mut self, rng: &mut ThreadRng) {
+    pub fn play(&mut self, mode: SimMode, rng: &mut ThreadRng) {
         self.do_pre_game_tasks();
-        self.simulate(rng);    // The actual game is played here.
+        self.simulate(mode, rng);    // The actual game is played here.
         self.do_post_game_tasks();
+        self.record_player_games();
+    }
+
+    // Record the game for the players who played in it.
+    // Games between national teams also count as caps.
+    fn record_player_games(&self) {
+        let is_international = Team::read_from_db(&self.home.team_id, |a| a.is_national_team);
+        let player_ids = self.home.lineup.get_player_ids().into_iter().chain(self.away.lineup.get_player_ids());
+
+        for id in player_ids {
+            let goals = self.get_goals_of_player(id);
+            Player::update_in_db(&id, |player| {
+                player.play_game();
+                if is_international {
+                    player.international_caps += 1;
+                    player.international_goals += goals;
+                }
+            }).unwrap();
+        }
+    }
+
+    // Get how many goals the given player scored in the game.
+    fn get_goals_of_player(&self, player_id: PlayerId) -> u16 {
+        let goals = self.home.shots.iter().chain(self.away.shots.iter())
+            .filter(|a| a.is_goal && a.get_shooter_id() == player_id)
+            .count();
+
+        return convert::int::<usize, u16>(goals);
     }
 
     // Simulate a game of ice hockey.
-    fn simulate(&mut self, rng: &mut ThreadRng) {
+    fn simulate(&mut self, mode: SimMode, rng: &mut ThreadRng) {
         // Regular time.
-        while !self.is_regular_time_over() {
-            self.simulate_regular_period(rng);
+        match mode {
+            SimMode::Full => {
+                while !self.is_regular_time_over() {
+                    self.simulate_regular_period(rng);
+                }
+            },
+            SimMode::Quick => self.quick_simulate_regular_time(rng)
         }
 
         // Overtime.
         while !self.is_overtime_over() {
             self.simulate_overtime_period(rng);
         }
+
+        // Shootout.
+        if self.needs_winner() && self.is_tied() {
+            self.simulate_shootout(rng);
+        }
     }
 
     // Simulate a period of ice hockey.
@@ -152,6 +269,44 @@ impl Game {
         self.clock.next_period();
     }
 
+    // Resolve the regular time at once.
+    // The shots of each pair of lines come at random at the same rates as second by second, and are placed at random times.
+    fn quick_simulate_regular_time(&mut self, rng: &mut ThreadRng) {
+        let cache = self.cache.as_ref().unwrap();
+        let regular_time = cache.rules.get_regular_time();
+        let home_weights = cache.home.lineup.get_line_weights();
+        let away_weights = cache.away.lineup.get_line_weights();
+        let total_weight = home_weights.iter().sum::<u8>() as f64 * away_weights.iter().sum::<u8>() as f64;
+
+        let mut shots = [Vec::new(), Vec::new()];
+        for (home_index, home_weight) in home_weights.iter().enumerate() {
+            for (away_index, away_weight) in away_weights.iter().enumerate() {
+                // How long the two lines are on ice against each other.
+                let seconds = regular_time as f64 * (*home_weight as f64) * (*away_weight as f64) / total_weight;
+                let expected_shots = cache.table.get_expected_shots(home_index, away_index, seconds);
+                let odds = cache.table.get(home_index, away_index);
+                let lines = [&cache.home.lineup.get_lines()[home_index], &cache.away.lineup.get_lines()[away_index]];
+
+                for (i, expected) in expected_shots.into_iter().enumerate() {
+                    for _ in 0..draw_amount(expected, u16::MAX, rng) {
+                        let second = rng.random_range(0..regular_time);
+                        let time = Clock::build(convert::int::<u16, u8>(second / cache.rules.period_length), second % cache.rules.period_length);
+                        shots[i].push(Shot::simulate(time, lines[i], lines[1 - i], &odds.goal[i], rng));
+                    }
+                }
+            }
+        }
+
+        for team_shots in shots.iter_mut() {
+            team_shots.sort_by_key(|a| (a.event.time.periods_completed, a.event.time.period_total_seconds));
+        }
+
+        let [home_shots, away_shots] = shots;
+        self.home.shots.extend(home_shots);
+        self.away.shots.extend(away_shots);
+        self.clock = Clock::build(cache.rules.periods, 0);
+    }
+
     fn simulate_overtime_period(&mut self, rng: &mut ThreadRng) {
         while !self.is_overtime_period_over() {
             self.simulate_second(rng);
@@ -169,6 +324,36 @@ impl Game {
         self.clock.advance();
     }
 
+    // Simulate a shootout. Both teams shoot three times, and then once each until the shootout is decided.
+    fn simulate_shootout(&mut self, rng: &mut ThreadRng) {
+        const ROUNDS: u8 = 3;
+
+        let mut round = 0;
+        loop {
+            round += 1;
+
+            // Each round has new shooters.
+            self.change_players_on_ice(rng);
+            let cache = self.cache.as_ref().unwrap();
+            let home = cache.home.lineup.get_players_on_ice();
+            let away = cache.away.lineup.get_players_on_ice();
+            let odds = cache.get_shift_odds();
+
+            if Shot::simulate(self.clock.clone(), home, away, &odds.goal[0], rng).is_goal {
+                self.home.shootout_goals += 1;
+            }
+            if Shot::simulate(self.clock.clone(), away, home, &odds.goal[1], rng).is_goal {
+                self.away.shootout_goals += 1;
+            }
+
+            // Stop when the team behind cannot catch up anymore.
+            let rounds_left = ROUNDS.saturating_sub(round);
+            if self.home.shootout_goals.abs_diff(self.away.shootout_goals) > rounds_left {
+                break;
+            }
+        }
+    }
+
     // Change the players on ice for home and away teams.
     fn change_players_on_ice(&mut self, rng: &mut ThreadRng) {
         self.cache.as_mut().unwrap().home.lineup.change_players_on_ice(rng);
@@ -177,11 +362,7 @@ impl Game {
 
     // Change which team has the puck.
     fn change_puck_possession(&mut self, rng: &mut ThreadRng) {
-        let modifier = self.cache.as_ref().unwrap().home.lineup.players_on_ice.get_skaters_ability_ratio(
-            &self.cache.as_ref().unwrap().away.lineup.players_on_ice
-        );
-
-        if logic_event::Type::fetch_from_db(&logic_event::Id::PuckPossessionChange).get_outcome(modifier, rng) {
+        if rng.random_bool(self.cache.as_ref().unwrap().get_shift_odds().home_possession) {
             self.attacker = Attacker::Home;
         }
         else {
@@ -191,39 +372,28 @@ impl Game {
 
     // The attacking team attempts to shoot the puck.
     fn attempt_shot(home: &mut TeamGameData, away: &mut TeamGameData, clock: &Clock, cache: &GameCache, attacker: &Attacker, rng: &mut ThreadRng) {
-        let (attacker, defender) = match attacker {
-            Attacker::Home => (&cache.home, &cache.away),
-            Attacker::Away => (&cache.away, &cache.home),
+        let (index, attacker, defender, shots) = match attacker {
+            Attacker::Home => (0, &cache.home, &cache.away, &mut home.shots),
+            Attacker::Away => (1, &cache.away, &cache.home, &mut away.shots),
             _ => panic!("attacker cannot be null when attempting a shot")
         };
 
-        let modifier = attacker.lineup.players_on_ice.get_skaters_ability_ratio(
-            &defender.lineup.players_on_ice
-        );
-
-        let success = logic_event::Type::fetch_from_db(&logic_event::Id::ShotAtGoal).get_outcome(modifier, rng);
-
-        if success {
-            let shot = Shot::simulate(clock.clone(), &attacker.lineup.players_on_ice, &defender.lineup.players_on_ice, rng);
-
-            if home.team_id == attacker.team.id {
-                home.shots.push(shot);
-            }
-            else {
-                away.shots.push(shot);
-            }
+        let odds = cache.get_shift_odds();
+        if rng.random_bool(odds.shot[index]) {
+            shots.push(Shot::simulate(clock.clone(), attacker.lineup.get_players_on_ice(), defender.lineup.get_players_on_ice(), &odds.goal[index], rng));
         }
     }
 
     // Get the home and away team names.
     pub fn get_name(&self) -> String {
-        format!("{} - {}", self.home.get_team().name, self.away.get_team().name)
+        format!("{} - {}", self.home.get_name(), self.away.get_name())
     }
 
     // Get the score of the game.
     fn get_score(&self) -> String {
-        let ot = match self.has_overtime() {
-            true => " OT",
+        let ot = match (self.has_overtime(), self.has_shootout()) {
+            (_, true) => " SO",
+            (true, _) => " OT",
             _ => ""
         };
 
@@ -232,7 +402,7 @@ impl Game {
 
     // Get the home and away team names, as well as the game score.
     pub fn get_name_and_score(&self) -> String {
-        format!("{} {} {}", self.home.get_team().name, self.get_score(), self.away.get_team().name)
+        format!("{} {} {}", self.home.get_name(), self.get_score(), self.away.get_name())
     }
 }
 
@@ -292,7 +462,12 @@ impl Game {
     // Check if the overtime is over.
     fn is_overtime_over(&self) -> bool {
         // Always ends if teams are not tied.
-        if self.home.get_goal_amount() != self.away.get_goal_amount() {
+        if !self.is_tied() {
+            return true;
+        }
+
+        // A first leg can end in a draw.
+        if self.leg == Leg::First {
             return true;
         }
 
@@ -324,6 +499,11 @@ impl Game {
     pub fn has_overtime(&self) -> bool {
         self.get_time_expired_in_overtime() > 0
     }
+
+    // Check if the game was decided in a shootout.
+    pub fn has_shootout(&self) -> bool {
+        self.home.shootout_goals != self.away.shootout_goals
+    }
 }
 
 // Tests.
@@ -338,8 +518,8 @@ impl Game {
             total_seconds: u32,
         }
 
-        let home_name = self.home.get_team().name;
-        let away_name = self.away.get_team().name;
+        let home_name = self.home.get_name();
+        let away_name = self.away.get_name();
 
         let mut events = Vec::new();
         for goal in self.home.shots.iter() {
@@ -391,27 +571,36 @@ pub struct Rules {
     period_length: u16,
     overtime_length: u16,
     continuous_overtime: bool,
+    home_advantage: f64,    // How much more likely the home team is to get the puck, unless the game is played at a neutral venue.
 }
 
 // Basics.
 impl Rules {
-    pub fn build(periods: u8, period_length: u16, overtime_length: u16, continous_overtime: bool) -> Self {
+    pub fn build(periods: u8, period_length: u16, overtime_length: u16, continous_overtime: bool, home_advantage: f64) -> Self {
         Self {
             periods: periods,
             period_length: period_length,
             overtime_length: overtime_length,
             continuous_overtime: continous_overtime,
+            home_advantage: home_advantage,
         }
     }
 
     // Make sure the rules do not contain illegal values.
     pub fn is_valid(&self) -> bool {
-        self.periods != 0 && self.period_length != 0
+        self.periods != 0 && self.period_length != 0 && (0.0..=1.0).contains(&self.home_advantage)
     }
 }
 
 // Functional.
 impl Rules {
+    // Get how many rating points playing at home is worth.
+    // The home team is rated as much more likely to win as it is to get the puck.
+    pub fn get_home_rating_advantage(&self) -> f64 {
+        let share = 0.5 + self.home_advantage.min(0.49);
+        return 400.0 * (share / (1.0 - share)).log10();
+    }
+
     // Get the total regular time of the game in seconds.
     fn get_regular_time(&self) -> u16 {
         (self.periods as u16) * self.period_length
@@ -477,4 +666,127 @@ impl Clock {
         self.period_total_seconds = 0;
         self.periods_completed = 0;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::{person::attribute::{AttributeId, PersonAttribute}, team::lineup::cache::LineUpCache};
+
+    const ITERATIONS: u32 = 3000;
+
+    fn build_player(id: PlayerId, ability: u16) -> Player {
+        Player {
+            id: id,
+            ability: PersonAttribute::build(AttributeId::General, ability),
+            ..Default::default()
+        }
+    }
+
+    // Build a full lineup whose lines get weaker from the first to the fourth.
+    fn build_lineup(first_id: PlayerId, ability: u16) -> LineUpCache {
+        let mut id = first_id;
+        let mut next = |ability: u16| {
+            id += 1;
+            build_player(id, ability)
+        };
+
+        let goalkeeper = next(ability);
+        let defence_pairs = (0..4).map(|a| [next(ability - a * 500), next(ability - a * 500)]).collect();
+        let forward_lines = (0..4).map(|a| [next(ability - a * 500), next(ability - a * 500), next(ability - a * 500)]).collect();
+        return LineUpCache::build_for_tests(goalkeeper, defence_pairs, forward_lines);
+    }
+
+    fn build_game(home_ability: u16, away_ability: u16) -> Game {
+        let rules = Rules::build(3, 1200, 0, false, 0.0);
+        Game {
+            cache: Some(GameCache::build_for_tests(build_lineup(0, home_ability), build_lineup(100, away_ability), &rules)),
+            ..Default::default()
+        }
+    }
+
+    // Play the regular time over and over in the given mode without saving anything.
+    // Return how many times the home and the away team scored each amount of goals, and how long the games took.
+    fn sample_regular_time_goals(game: &mut Game, mode: SimMode, iterations: u32) -> ([Vec<u32>; 2], Duration) {
+        let mut rng = rand::rng();
+        let mut goals = [Vec::new(), Vec::new()];
+        let start = Instant::now();
+
+        for _ in 0..iterations {
+            game.home.shots.clear();
+            game.away.shots.clear();
+            game.clock.reset();
+
+            match mode {
+                SimMode::Full => {
+                    while !game.is_regular_time_over() {
+                        game.simulate_regular_period(&mut rng);
+                    }
+                },
+                SimMode::Quick => game.quick_simulate_regular_time(&mut rng)
+            }
+
+            for (team_goals, team) in goals.iter_mut().zip([&game.home, &game.away]) {
+                let amount = team.shots.iter().filter(|a| a.is_goal).count();
+                if team_goals.len() <= amount {
+                    team_goals.resize(amount + 1, 0);
+                }
+                team_goals[amount] += 1;
+            }
+        }
+
+        return (goals, start.elapsed());
+    }
+
+    fn get_average(goals: &[u32]) -> f64 {
+        let games: u32 = goals.iter().sum();
+        let total: u32 = goals.iter().enumerate().map(|(amount, count)| amount as u32 * count).sum();
+        return total as f64 / games as f64;
+    }
+
+    fn get_share(goals: &[u32], amount: usize) -> f64 {
+        let games: u32 = goals.iter().sum();
+        return *goals.get(amount).unwrap_or(&0) as f64 / games as f64;
+    }
+
+    #[test]
+    fn quick_games_score_like_full_games() {
+        const AVERAGE_TOLERANCE: f64 = 0.25;
+        const SHARE_TOLERANCE: f64 = 0.05;
+
+        for (home_ability, away_ability) in [(6000, 6000), (8000, 5000)] {
+            let mut game = build_game(home_ability, away_ability);
+            let (full, _) = sample_regular_time_goals(&mut game, SimMode::Full, ITERATIONS);
+            let (quick, _) = sample_regular_time_goals(&mut game, SimMode::Quick, ITERATIONS);
+
+            for i in 0..2 {
+                let (full_average, quick_average) = (get_average(&full[i]), get_average(&quick[i]));
+                assert!((full_average - quick_average).abs() < AVERAGE_TOLERANCE, "average goals {full_average} in full games, {quick_average} in quick games");
+
+                for amount in 0..full[i].len().max(quick[i].len()) {
+                    let (full_share, quick_share) = (get_share(&full[i], amount), get_share(&quick[i], amount));
+                    assert!((full_share - quick_share).abs() < SHARE_TOLERANCE, "{amount} goals in {full_share} of full games, {quick_share} of quick games");
+                }
+            }
+        }
+    }
+
+    // Timings only mean something in an optimised build: cargo test --release -- --ignored quick_games_are_fast
+    #[test]
+    #[ignore]
+    fn quick_games_are_fast() {
+        const MAX_QUICK_MICROSECONDS: f64 = 100.0;
+        const MIN_SPEEDUP: f64 = 10.0;
+
+        let mut game = build_game(6000, 6000);
+        let (_, full) = sample_regular_time_goals(&mut game, SimMode::Full, ITERATIONS);
+        let (_, quick) = sample_regular_time_goals(&mut game, SimMode::Quick, ITERATIONS);
+
+        let quick_microseconds = quick.as_secs_f64() * 1e6 / ITERATIONS as f64;
+        let speedup = full.as_secs_f64() / quick.as_secs_f64();
+        assert!(quick_microseconds < MAX_QUICK_MICROSECONDS, "a quick game took {quick_microseconds:.1} microseconds on average");
+        assert!(speedup > MIN_SPEEDUP, "quick games were only {speedup:.1} times as fast as full games");
+    }
+}
@@ -0,0 +1,234 @@
+// Pre-game predictions, derived from the abilities of the lineups the same way the games are simulated.
+
+use std::cmp::Ordering;
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{event as logic_event, match_event::{Rules, leg::{EarlierLegs, Leg}}};
+
+// Abilities of a lineup that decide how well it is expected to do in a game.
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct GameStrength {
+    pub skaters: f64,       // Total ability of the skaters on ice, weighted by how often each line plays.
+    pub shooter: f64,       // Average ability of a skater on ice, weighted the same way.
+    pub goalkeeper: f64,    // Ability of the starting goalkeeper.
+}
+
+// Probabilities of the results after regular time.
+#[derive(Debug, serde::Serialize)]
+#[derive(Default, Clone)]
+pub struct Prediction {
+    pub home_win: f64,
+    pub draw: f64,  // The game is tied after regular time, and goes to overtime if the rules have it.
+    pub away_win: f64,
+}
+
+impl Prediction {
+    // Most goals a team is expected to score in any realistic game. Higher amounts are left out.
+    const MAX_GOALS: u16 = 20;
+
+    // Predict the results of a game from the strengths of the teams.
+    pub fn build(home: &GameStrength, away: &GameStrength, rules: &Rules, is_neutral_venue: bool) -> Self {
+        Self::build_from_expected_goals(get_expected_goals(home, away, rules, is_neutral_venue))
+    }
+
+    // Predict the results of a game from the goals the home and the away team are expected to score in regular time.
+    fn build_from_expected_goals([home_goals, away_goals]: [f64; 2]) -> Self {
+        let home_probabilities = get_goal_probabilities(home_goals);
+        let away_probabilities = get_goal_probabilities(away_goals);
+
+        let mut prediction = Self::default();
+        for (i, home_probability) in home_probabilities.iter().enumerate() {
+            for (j, away_probability) in away_probabilities.iter().enumerate() {
+                let probability = home_probability * away_probability;
+                match i.cmp(&j) {
+                    Ordering::Greater => prediction.home_win += probability,
+                    Ordering::Equal => prediction.draw += probability,
+                    Ordering::Less => prediction.away_win += probability
+                };
+            }
+        }
+
+        // Scale the results back to a total of 1, as the highest amounts of goals were left out.
+        let total = prediction.home_win + prediction.draw + prediction.away_win;
+        prediction.home_win /= total;
+        prediction.draw /= total;
+        prediction.away_win /= total;
+
+        return prediction;
+    }
+}
+
+// A result drawn at random for a game, instead of playing it second by second.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct DrawnResult {
+    pub goals: [u16; 2],    // Goals of the home and the away team.
+    pub shootout: Ordering, // Greater if the home team won the shootout, Equal if there was none.
+    pub had_overtime: bool,
+}
+
+impl DrawnResult {
+    // Draw the result of a game from the goals the teams are expected to score in regular time.
+    // The game goes to overtime and to a shootout the same way as when it is played.
+    pub fn draw(expected_goals: [f64; 2], rules: &Rules, leg: &Leg, earlier_legs: &EarlierLegs, rng: &mut ThreadRng) -> Self {
+        let mut result = Self {
+            goals: expected_goals.map(|a| draw_goals(a, rng)),
+            shootout: Ordering::Equal,
+            had_overtime: false
+        };
+
+        let has_overtime = rules.continuous_overtime || rules.overtime_length > 0;
+        if *leg == Leg::First || !has_overtime || !leg.is_tied(result.goals, earlier_legs) {
+            return result;
+        }
+
+        // The first goal of the overtime decides the game.
+        result.had_overtime = true;
+        let seconds = rules.periods as f64 * rules.period_length as f64;
+        let expected_overtime_goals = (expected_goals[0] + expected_goals[1]) * rules.overtime_length as f64 / seconds;
+        if rules.continuous_overtime || rng.random_bool(1.0 - (-expected_overtime_goals).exp()) {
+            let index = match rng.random_bool(get_ratio(expected_goals[0], expected_goals[1])) {
+                true => 0,
+                _ => 1
+            };
+            result.goals[index] += 1;
+        }
+
+        // Only second legs need a winner when the overtime runs out.
+        else if let Leg::Second(_) = leg {
+            result.shootout = match rng.random_bool(0.5) {
+                true => Ordering::Greater,
+                _ => Ordering::Less
+            };
+        }
+
+        return result;
+    }
+}
+
+// Get the amount of goals the home and the away team are expected to score in regular time.
+// Every second the puck goes to either team, which may then shoot, and the shot may go in.
+pub fn get_expected_goals(home: &GameStrength, away: &GameStrength, rules: &Rules, is_neutral_venue: bool) -> [f64; 2] {
+    let possession = logic_event::Type::get(&logic_event::Id::PuckPossessionChange);
+    let shot = logic_event::Type::get(&logic_event::Id::ShotAtGoal);
+    let goal = logic_event::Type::get(&logic_event::Id::Goal);
+
+    let skater_ratio = get_ratio(home.skaters, away.skaters);
+    let possession_modifier = match is_neutral_venue {
+        true => skater_ratio,
+        _ => (skater_ratio + rules.home_advantage).min(1.0)
+    };
+    let home_possession = possession.calculate_likelihood(possession_modifier);
+
+    let seconds = rules.periods as f64 * rules.period_length as f64;
+    let home_goals = seconds * home_possession * shot.calculate_likelihood(skater_ratio) * goal.calculate_likelihood(get_ratio(home.shooter, away.goalkeeper));
+    let away_goals = seconds * (1.0 - home_possession) * shot.calculate_likelihood(1.0 - skater_ratio) * goal.calculate_likelihood(get_ratio(away.shooter, home.goalkeeper));
+
+    return [home_goals, away_goals];
+}
+
+// Get the share of the first ability of both abilities together.
+// The shares are even if neither has any ability, as in the game itself.
+fn get_ratio(ability: f64, opponent_ability: f64) -> f64 {
+    match ability + opponent_ability {
+        0.0 => 0.5,
+        total => ability / total
+    }
+}
+
+// Draw an amount of goals, when goals come at random at the expected rate.
+fn draw_goals(expected_goals: f64, rng: &mut ThreadRng) -> u16 {
+    draw_amount(expected_goals, Prediction::MAX_GOALS, rng)
+}
+
+// Draw an amount of anything that comes at random at the expected rate, up to the given maximum.
+pub fn draw_amount(expected_amount: f64, max_amount: u16, rng: &mut ThreadRng) -> u16 {
+    let limit = (-expected_amount).exp();
+    let mut product: f64 = rng.random();
+    let mut amount = 0;
+    while product > limit && amount < max_amount {
+        product *= rng.random::<f64>();
+        amount += 1;
+    }
+
+    return amount;
+}
+
+// Get the probability of scoring each amount of goals from 0 to MAX_GOALS, when goals come at random at the expected rate.
+fn get_goal_probabilities(expected_goals: f64) -> Vec<f64> {
+    let mut probabilities = vec![(-expected_goals).exp()];
+    for goals in 1..=Prediction::MAX_GOALS {
+        let probability = probabilities.last().unwrap() * expected_goals / goals as f64;
+        probabilities.push(probability);
+    }
+
+    return probabilities;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 1e-9;
+
+    fn get_factorial(n: u16) -> f64 {
+        (1..=n).map(|a| a as f64).product()
+    }
+
+    #[test]
+    fn goal_probabilities_follow_poisson() {
+        let probabilities = get_goal_probabilities(2.5);
+        assert_eq!(probabilities.len(), Prediction::MAX_GOALS as usize + 1);
+
+        for (goals, probability) in probabilities.iter().enumerate() {
+            let expected = (-2.5_f64).exp() * 2.5_f64.powi(goals as i32) / get_factorial(goals as u16);
+            assert!((probability - expected).abs() < TOLERANCE, "{goals} goals: {probability} instead of {expected}");
+        }
+    }
+
+    #[test]
+    fn prediction_adds_up_to_one() {
+        for expected_goals in [[0.0, 0.0], [3.0, 2.5], [0.5, 6.0], [12.0, 1.0]] {
+            let prediction = Prediction::build_from_expected_goals(expected_goals);
+            let total = prediction.home_win + prediction.draw + prediction.away_win;
+            assert!((total - 1.0).abs() < TOLERANCE, "{expected_goals:?}: {prediction:?}");
+        }
+    }
+
+    #[test]
+    fn prediction_matches_closed_form() {
+        // Without goals, every game is a draw.
+        let prediction = Prediction::build_from_expected_goals([0.0, 0.0]);
+        assert!((prediction.draw - 1.0).abs() < TOLERANCE);
+
+        // When only the home team can score, it wins unless it does not score at all.
+        let prediction = Prediction::build_from_expected_goals([1.0, 0.0]);
+        assert!((prediction.draw - (-1.0_f64).exp()).abs() < TOLERANCE);
+        assert!((prediction.home_win - (1.0 - (-1.0_f64).exp())).abs() < TOLERANCE);
+        assert!(prediction.away_win.abs() < TOLERANCE);
+
+        // Evenly matched teams are as likely to win.
+        let prediction = Prediction::build_from_expected_goals([2.8, 2.8]);
+        assert!((prediction.home_win - prediction.away_win).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn home_advantage_favours_home_team() {
+        let strength = GameStrength { skaters: 500.0, shooter: 50.0, goalkeeper: 60.0 };
+
+        // Without a home advantage, the venue does not matter.
+        let rules = Rules::build(3, 1200, 300, false, 0.0);
+        let home = Prediction::build(&strength, &strength, &rules, false);
+        assert!((home.home_win - home.away_win).abs() < TOLERANCE);
+
+        let rules = Rules::build(3, 1200, 300, false, 0.02);
+        let neutral = Prediction::build(&strength, &strength, &rules, true);
+        assert!((neutral.home_win - neutral.away_win).abs() < TOLERANCE);
+
+        let home = Prediction::build(&strength, &strength, &rules, false);
+        assert!(home.home_win > home.away_win);
+    }
+}
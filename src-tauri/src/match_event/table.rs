@@ -0,0 +1,72 @@
+// Per-game probability table.
+// The chances of everything that can happen in a second are calculated before the game for every pair of lines on ice,
+// so that nothing needs to be looked up or compared while the game is played.
+
+use crate::{event as logic_event, match_event::{Rules, event::Shot}, team::lineup::cache::LineUpCache};
+
+// Chances of what happens in a second when a line of the home team faces a line of the away team.
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct ShiftOdds {
+    pub home_possession: f64,   // Chance of the home team having the puck.
+    pub shot: [f64; 2],         // Chance of the home and the away team shooting when they have the puck.
+    pub goal: [Vec<f64>; 2],    // Chance of each skater of the home and the away line scoring with a shot.
+}
+
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct ProbabilityTable {
+    away_lines: usize,
+    shifts: Vec<ShiftOdds>, // Every line of the away team for the first line of the home team, then for the second, and so on.
+}
+
+impl ProbabilityTable {
+    pub fn build(home: &LineUpCache, away: &LineUpCache, rules: &Rules, is_neutral_venue: bool) -> Self {
+        let possession = logic_event::Type::get(&logic_event::Id::PuckPossessionChange);
+        let shot = logic_event::Type::get(&logic_event::Id::ShotAtGoal);
+
+        let mut table = Self {
+            away_lines: away.get_lines().len(),
+            ..Default::default()
+        };
+
+        for home_line in home.get_lines() {
+            for away_line in away.get_lines() {
+                let home_ratio = home_line.get_skaters_ability_ratio(away_line);
+                let possession_modifier = match is_neutral_venue {
+                    true => home_ratio,
+                    _ => (home_ratio + rules.home_advantage).min(1.0)
+                };
+
+                table.shifts.push(ShiftOdds {
+                    home_possession: possession.calculate_likelihood(possession_modifier),
+                    shot: [
+                        shot.calculate_likelihood(home_ratio),
+                        shot.calculate_likelihood(away_line.get_skaters_ability_ratio(home_line))
+                    ],
+                    goal: [
+                        Shot::get_goal_likelihoods(home_line, away_line),
+                        Shot::get_goal_likelihoods(away_line, home_line)
+                    ],
+                });
+            }
+        }
+
+        return table;
+    }
+
+    // Get the chances when the lines with the given indexes are on ice.
+    pub fn get(&self, home_line: usize, away_line: usize) -> &ShiftOdds {
+        &self.shifts[home_line * self.away_lines + away_line]
+    }
+
+    // Get how many shots the home and the away team are expected to take in the given amount of seconds
+    // when the lines with the given indexes are on ice for the whole time.
+    pub fn get_expected_shots(&self, home_line: usize, away_line: usize, seconds: f64) -> [f64; 2] {
+        let odds = self.get(home_line, away_line);
+        [
+            seconds * odds.home_possession * odds.shot[0],
+            seconds * (1.0 - odds.home_possession) * odds.shot[1]
+        ]
+    }
+}
@@ -10,6 +10,7 @@ pub struct TeamGameData {
     pub team_id: TeamId,
     pub team_seed: u8,
     pub shots: Vec<Shot>,
+    pub shootout_goals: u8,
     pub lineup: LineUp,
     penalties: Vec<String>, // Placeholder.
 }
@@ -33,10 +34,15 @@ impl TeamGameData { // Basics.
         Team::fetch_from_db(&self.team_id)
     }
 
+    // Get the name of the team, without cloning the rest of it.
+    pub fn get_name(&self) -> String {
+        Team::read_from_db(&self.team_id, |a| a.name.clone())
+    }
+
     pub fn get_comp_screen_json(&self) -> serde_json::Value {
         json!({
             "id": self.team_id,
-            "name": self.get_team().name,
+            "name": self.get_name(),
             "seed": self.team_seed,
             "goals": self.get_goal_amount()
         })
@@ -117,6 +117,12 @@ impl PersonAttribute {
         self.set(changed_value as u16);
     }
 
+    // Move the attribute up or down by roughly the given amount of display points.
+    pub fn shift_display(&mut self, amount: f64) {
+        let factor = (amount / Self::DISPLAY_MULTIPLIER).exp2();
+        self.set((self.value as f64 * factor).clamp(0.0, Self::MAX as f64) as AttributeValue);
+    }
+
     // The daily update check on the attribute.
     pub fn update(&mut self, age_days: u16, rng: &mut ThreadRng) {
         let attribute = Attribute::fetch_from_db(&self.id);
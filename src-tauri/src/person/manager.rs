@@ -1,16 +1,22 @@
 // This is what a player is!
 
-use rand::rngs::ThreadRng;
+use rand::{Rng, rngs::ThreadRng};
 use serde_json::json;
 use time::Date;
 
-use crate::{database::MANAGERS, person::{Gender, Person}, types::ManagerId};
+use crate::{country::Country, database::{IDS, IdKind, MANAGERS}, person::{Contract, Gender, Person}, team::{Team, board::SeasonReview}, types::ManagerId};
 
 #[derive(Default, Clone)]
 pub struct Manager {
     pub id: ManagerId,
     pub person: Person,
     pub is_human: bool,
+
+    // How highly the boards of teams think of the manager. Between 0 and 100.
+    pub reputation: u8,
+
+    // The verdicts of the boards after each season the manager has been in charge.
+    pub season_reviews: Vec<SeasonReview>,
 }
 
 impl Manager {
@@ -33,15 +39,22 @@ impl Manager {
     // Create a manager and store it in the database. Return a clone of the Manager.
     fn build_and_save(person: Person) -> Self {
         let mut manager = Self::build(person);
-        manager.create_id(MANAGERS.lock().unwrap().len() + 1);
+        manager.create_id(IDS.lock().unwrap().next(IdKind::Manager));
         manager.save();
         return manager;
     }
 
     // Build a random manager.
     pub fn build_and_save_random(today: &Date, rng: &mut ThreadRng) -> Self {
-        let person = Person::create(today, rng, 30, 60, Gender::Male);
-        return Self::build_and_save(person);
+        let person = Person::create(today, rng, 30, 60, Gender::Male, &Country::get_weights());
+        let mut manager = Self::build_and_save(person);
+
+        // Older managers have had more time to make a name for themselves.
+        let age = manager.person.get_age_years(today) as u8;
+        manager.reputation = rng.random_range(0..=20) + age / 2;
+        manager.save();
+
+        return manager;
     }
 
     // Get a manager from the database.
@@ -68,10 +81,37 @@ impl Manager {
         return None;
     }
 
+    // Get all active AI managers who do not have a team.
+    pub fn get_unemployed() -> Vec<Self> {
+        MANAGERS.lock().unwrap().values().filter_map(|a| {
+            match !a.is_human && a.person.is_active && a.person.contract.is_none() {
+                true => Some(a.clone()),
+                _ => None
+            }
+        }).collect()
+    }
+
+    // Get the team the manager is currently employed by.
+    pub fn get_team(&self) -> Option<Team> {
+        match self.person.contract.as_ref() {
+            Some(contract) => Some(contract.get_team()),
+            _ => None
+        }
+    }
+
+    // Get the board's review of the manager's most recent season.
+    pub fn get_season_review_package(&self) -> serde_json::Value {
+        match self.season_reviews.last() {
+            Some(review) => review.get_package(),
+            _ => serde_json::Value::Null
+        }
+    }
+
     // Get relevant information to the team screen.
     pub fn get_team_screen_json(&self) -> serde_json::Value {
         json!({
-            "name": self.person.get_full_name()
+            "name": self.person.get_full_name(),
+            "reputation": self.reputation
         })
     }
 
@@ -79,10 +119,44 @@ impl Manager {
     // For human players only.
     pub fn get_package(&self) -> serde_json::Value {
         json!({
+            "reputation": self.reputation,
+            "is_active": self.person.is_active,
             "team": match self.person.contract.as_ref() {
                 Some(contract) => Some(contract.get_team().get_manager_package()),
                 _ => None
             }
         })
     }
+}
+
+// Functional.
+impl Manager {
+    // Change the reputation of the manager by the given amount.
+    pub fn change_reputation(&mut self, amount: i16) {
+        self.reputation = (self.reputation as i16 + amount).clamp(0, 100) as u8;
+    }
+
+    // Sign a contract with a team.
+    pub fn sign_contract(&mut self, contract: Contract) {
+        self.person.contract = Some(contract);
+        self.save();
+    }
+
+    // Leave the current team, whatever the reason.
+    pub fn leave_team(&mut self) {
+        self.person.contract = None;
+        self.save();
+    }
+
+    // Do the daily tasks of an unemployed manager.
+    // Teams do the hiring, so the only thing left to do is to consider retirement.
+    pub fn unemployed_checker(&mut self, today: &Date, rng: &mut ThreadRng) {
+        if self.is_human || self.person.get_age_years(today) < 65 { return; }
+
+        // Roughly once a year.
+        if rng.random_bool(1.0 / 365.0) {
+            self.person.is_active = false;
+            self.save();
+        }
+    }
 }
\ No newline at end of file
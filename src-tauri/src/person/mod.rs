@@ -7,7 +7,7 @@ use serde_json::json;
 use time::{Date, Duration};
 
 use crate::{
-    competition::Competition, country::Country, database::COUNTRIES, team::Team, time::{date_to_db_string, db_string_to_date, get_years_between, years_to_days}, types::{CountryId, TeamId}
+    competition::Competition, country::Country, team::Team, time::{date_to_db_string, db_string_to_date, get_years_between, years_to_days}, types::{CountryId, TeamId}
 };
 
 #[derive(Eq, Hash)]
@@ -27,7 +27,6 @@ pub struct Person {
     gender: Gender,
     country_id: CountryId,
     pub contract: Option<Contract>,
-    pub contract_offers: Vec<Contract>,
     birthday: Date,
     pub is_active: bool,
 }
@@ -40,7 +39,6 @@ impl Default for Person {
             gender: Gender::default(),
             country_id: CountryId::default(),
             contract: None,
-            contract_offers: Vec::default(),
             birthday: Date::MIN,
             is_active: bool::default(),
         }
@@ -63,31 +61,14 @@ impl Person {
     }
 
     // Make a random person.
-    pub fn create(today: &Date, rng: &mut ThreadRng, min_age: u8, max_age: u8, gender: Gender) -> Self {
-        let min_days = years_to_days(min_age);
-        let max_days = years_to_days(max_age);
-
-        let age = rng.random_range(min_days..=max_days);
-
+    // The country weights come from Country::get_weights.
+    pub fn create(today: &Date, rng: &mut ThreadRng, min_age: u8, max_age: u8, gender: Gender, country_weights: &[(CountryId, u32)]) -> Self {
         // First determining the person's nationality with weighted random.
-        let countries = COUNTRIES.lock().unwrap().clone();
-        let mut country_weights = Vec::new();
-        let mut total_weight = 0;
-        for country in countries.values() {
-            let weight = match country.name == "Finland" {
-                // Making Finns more likely to appear in what tries to emulate some kind of a Finnish league.
-                true => country.get_combined_name_weight() * 20,
-                _ => country.get_combined_name_weight(),
-            };
-
-            total_weight += weight;
-            country_weights.push((country.id, weight));
-        }
-
+        let total_weight: u32 = country_weights.iter().map(|(_, weight)| weight).sum();
         let random = rng.random_range(0..total_weight);
         let mut counter = 0;
         let mut country_id = 0;
-        for (id, weight) in country_weights {
+        for (id, weight) in country_weights.iter().copied() {
             counter += weight;
 
             if random < counter {
@@ -96,9 +77,23 @@ impl Person {
             }
         }
 
+        return Self::create_with_country(today, rng, min_age, max_age, country_id, gender);
+    }
+
+    // Make a random person from the given country.
+    pub fn create_with_country(today: &Date, rng: &mut ThreadRng, min_age: u8, max_age: u8, country_id: CountryId, gender: Gender) -> Self {
+        let min_days = years_to_days(min_age);
+        let max_days = years_to_days(max_age);
+
+        let age = rng.random_range(min_days..=max_days);
         return Self::build(today, rng, age, country_id, gender);
     }
 
+    // Get the ID of the person's country.
+    pub fn get_country_id(&self) -> CountryId {
+        self.country_id
+    }
+
     // Get the person's country as an object.
     fn get_country(&self) -> Country {
         Country::fetch_from_db(&self.country_id)
@@ -123,7 +118,7 @@ impl Person {
     }
 
     // Get the person's age in years.
-    fn get_age_years(&self, today: &Date) -> i8 {
+    pub fn get_age_years(&self, today: &Date) -> i8 {
         return get_years_between(&self.birthday, &today);
     }
 }
@@ -136,17 +131,18 @@ impl Person {
         if self.contract.is_none() { return false; }
         return self.contract.as_ref().unwrap().check_if_expired(today);
     }
+}
 
-    // Determine if the person is going to sign a contract now.
-    // Very simple still.
-    pub fn decide_to_sign(&self, today: &Date, rng: &mut ThreadRng) -> bool {
-        if self.contract_offers.is_empty() { return false; }
-        let days_since_earliest_offer = self.contract_offers[0].get_days_expired(today);
-
-        // Random chance for the person to sign, grows more likely the more time passes.
-        // Guaranteed to sign after 10 days.
-        return rng.random_range(1..10) < days_since_earliest_offer;
-    }
+// The role a contract promises the person in the team.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Default, Clone, PartialEq, PartialOrd)]
+pub enum ContractRole {
+    // The last one in the lineup for the position.
+    Depth,
+    #[default]
+    Regular,
+    // The go-to player in the position.
+    Key,
 }
 
 // Contract a person has with a club.
@@ -155,7 +151,11 @@ impl Person {
 pub struct Contract {
     start_date: String,
     end_date: String,
-    pub team_id: TeamId
+    pub team_id: TeamId,
+
+    // Yearly salary in thousands.
+    pub salary: u32,
+    pub role: ContractRole,
 }
 
 impl Contract {
@@ -165,6 +165,8 @@ impl Contract {
             start_date: start_date.to_string(),
             end_date: end_date.to_string(),
             team_id: team_id,
+            salary: 0,
+            role: ContractRole::default(),
         }
     }
 
@@ -176,6 +178,15 @@ impl Contract {
         return Self::build(&date_to_db_string(today), &date_to_db_string(&end_date), team.id);
     }
 
+    // Create a contract with the given terms.
+    pub fn build_with_terms(team: &Team, today: &Date, years: i32, salary: u32, role: ContractRole) -> Self {
+        let mut contract = Self::build_from_years(team, today, years);
+        contract.salary = salary;
+        contract.role = role;
+
+        return contract;
+    }
+
     // Get the team of the contract.
     fn get_team(&self) -> Team {
         Team::fetch_from_db(&self.team_id)
@@ -187,7 +198,7 @@ impl Contract {
     }
 
     // How many days have expired from the contract.
-    fn get_days_expired(&self, today: &Date) -> i64 {
+    pub fn get_days_expired(&self, today: &Date) -> i64 {
         return self.get_duration_expired(today).whole_days()
     }
 
@@ -198,6 +209,16 @@ impl Contract {
         return get_years_between(&today, &end_date) + 1;
     }
 
+    // Check if the contract is in its last season.
+    pub fn is_in_final_season(&self, today: &Date) -> bool {
+        self.get_seasons_left(today) <= 1
+    }
+
+    // Get the last day of the contract.
+    pub fn get_end_date(&self) -> Date {
+        db_string_to_date(&self.end_date)
+    }
+
     // Get how much is left of the contract.
     fn get_duration_left(&self, today: &Date) -> Duration {
         return db_string_to_date(&self.end_date) - *today;
@@ -219,6 +240,8 @@ impl Contract {
             "start_date": self.start_date,
             "end_date": self.end_date,
             "seasons_left": self.get_seasons_left(today),
+            "salary": self.salary,
+            "role": self.role,
             "team": self.get_team().get_contract_package()
         })
     }
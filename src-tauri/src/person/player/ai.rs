@@ -3,9 +3,12 @@
 use rand::{Rng, rngs::ThreadRng};
 use time::Date;
 
-use crate::{person::{Contract, player::Player}, team::{Team, lineup::cache::LineUpCache}, time::date_to_db_string, types::TeamId};
+use crate::{person::{Contract, player::{Player, free_agency::FreeAgentStatus, negotiation::NegotiationStatus}}, team::{Team, ai::PlayerNeed, lineup::cache::LineUpCache}, time::date_to_db_string, types::TeamId};
 
 impl Player {
+    // How much more a player likes an offer from a team in their home country.
+    const HOME_COUNTRY_BONUS: f64 = 1.2;
+
     // Sign a given contract.
     pub fn sign_contract(&mut self, mut contract: Contract, today: &Date) {
         contract.start_date = date_to_db_string(today);
@@ -13,39 +16,55 @@ impl Player {
         self.person.contract = Some(contract);
 
         let mut team = Team::fetch_from_db(&self.person.contract.as_ref().unwrap().team_id);
-        team.roster.push(self.id);
+
+        // Extended contracts do not need a new roster spot.
+        if !team.roster.contains(&self.id) {
+            team.roster.push(self.id);
+        }
         team.approached_players.retain(|id| *id != self.id);
         team.save();
 
         self.reject_contracts();
-        self.person.contract_offers.clear();
+        self.negotiations.clear();
+        self.free_agent_status = FreeAgentStatus::Unrestricted;
     }
 
     // Remove the player from all approached teams' player lists.
     pub fn reject_contracts(&self) {
-        for offer in self.person.contract_offers.iter() {
-            self.reject_contract(offer);
+        for negotiation in self.negotiations.iter() {
+            self.reject_contract(negotiation.team_id);
         }
     }
 
-    // Choose a contract to sign, if any.
-    // This method assumes there are existing contract offers.
+    // Choose a contract to sign from the ones the player has agreed on.
+    // This method assumes there are existing agreements.
     pub fn choose_contract(&mut self, today: &Date, rng: &mut ThreadRng) {
-        let mut offers: Vec<(f64, &Contract)> = self.person.contract_offers.iter().map(|a| (self.evaluate_offer(a), a)).collect();
+        // A team without room for the player in its roster cannot sign them.
+        let mut offers: Vec<(f64, &Contract)> = self.negotiations.iter().filter_map(|a| match a.status {
+            NegotiationStatus::Agreed if Team::fetch_from_db(&a.team_id).can_register(self, today) => Some((self.evaluate_offer(&a.offer), &a.offer)),
+            NegotiationStatus::Agreed => Some((-1000.0, &a.offer)),
+            _ => None
+        }).collect();
         offers.sort_by(|a, b| b.0.total_cmp(&a.0));
 
-        // If even the best offer is unacceptable, all should be rejected.
+        // If even the best offer is unacceptable, all agreements should be rejected.
         if offers[0].0 <= 0.0 {
-            self.reject_contracts();
-            self.person.contract_offers.clear();
+            let team_ids: Vec<TeamId> = offers.iter().map(|(_, a)| a.team_id).collect();
+            for team_id in team_ids {
+                self.break_off_negotiation(team_id, today);
+            }
             return;
         }
 
         let team_id = Self::get_best_offer(&offers, rng);
-        let index = self.person.contract_offers.iter().position(|a| a.team_id == team_id).unwrap();
 
-        let contract = self.person.contract_offers.swap_remove(index);
-        self.sign_contract(contract, today);
+        // The former team may want to match the offer.
+        match self.get_rights_holder() {
+            Some(holder_id) if holder_id != team_id && self.has_right_of_first_refusal(holder_id) => {
+                self.send_offer_sheet(team_id, today);
+            },
+            _ => self.sign_negotiated_contract(team_id, today)
+        }
     }
 
     // Return the ID of the team whose offer was most pleasing to the player.
@@ -67,26 +86,34 @@ impl Player {
         return best_offers[i];
     }
 
+    // Get the team's need for the player's position, without the player in it.
+    pub fn get_need_in_team(&self, team: &Team) -> Option<PlayerNeed> {
+        let mut need = team.player_needs.iter().find(|a| a.position == self.position_id)?.clone();
+
+        // Removing the player's ability from needs so the player does not compare against himself.
+        let is_rostered = (team.roster.contains(&self.id) && !self.is_on_loan()) || team.loaned_players.contains(&self.id);
+        if is_rostered || team.approached_players.contains(&self.id) {
+            let player_index = need.abilities.iter().position(|a| *a == self.ability.get_display() as f64);
+            if player_index.is_some() { need.abilities.remove(player_index.unwrap()); }
+        }
+
+        // Approached players are not part of the count.
+        if is_rostered {
+            need.rostered = need.rostered.saturating_sub(1);
+        }
+
+        return Some(need);
+    }
+
     // Evaluate a contract offer.
     fn evaluate_offer(&self, contract: &Contract) -> f64 {
         let mut team = Team::fetch_from_db(&contract.team_id);
-        let mut need_option = None;
-        for team_need in team.player_needs.iter() {
-            if team_need.position == self.position_id {
-                need_option = Some(team_need.clone());
-                break;
-            }
-        }
 
         // This should not be possible, but let's check against it just in case.
-        if need_option.is_none() { return -1000.0; }
-
-        // Let's unwrap so code becomes nicer.
-        let mut need = need_option.unwrap();
-
-        // Removing the player's ability from needs so the player does not compare against himself.
-        let player_index = need.abilities.iter().position(|a| *a == self.ability.get_display() as f64);
-        if player_index.is_some() { need.abilities.remove(player_index.unwrap()); }
+        let need = match self.get_need_in_team(&team) {
+            Some(need) => need,
+            None => return -1000.0
+        };
 
         // A player never wants to join a team where their playing time is uncertain.
         if self.ability.get_display() as f64 <= need.get_worst() {
@@ -102,43 +129,22 @@ impl Player {
         // Adding 10 so an empty roster is not that bad of a detriment.
         let avg_ability = 10.0 + lineup.get_average_ability();
 
-        return avg_ability / role_modifier;
-    }
+        // Money talks, too.
+        let salary_modifier = contract.salary as f64 / self.get_market_value() as f64;
 
-    // Reject a contract.
-    fn reject_contract(&self, offer: &Contract) {
-        let mut team = Team::fetch_from_db(&offer.team_id);
-        team.approached_players.retain(|id| *id != self.id);
-        team.save();
-    }
+        // Players would rather play at home, all else being equal.
+        let home_modifier = match team.country_id == self.person.get_country_id() {
+            true => Self::HOME_COUNTRY_BONUS,
+            _ => 1.0
+        };
 
-    // Check if any contract offers for the player have expired.
-    // Return if there are changes to the player.
-    pub fn check_expired_offers(&mut self, today: &Date) {
-        let mut expired_indexes = Vec::new();
-        for (i, offer) in self.person.contract_offers.iter().enumerate() {
-            if offer.check_if_expired(today) {
-                self.reject_contract(offer);
-                expired_indexes.push(i);
-            }
-        }
-
-        if !expired_indexes.is_empty() {
-            for index in expired_indexes.iter().rev() {
-                self.person.contract_offers.remove(*index);
-            }
-        }
+        return avg_ability * salary_modifier * home_modifier / role_modifier;
     }
 
-    // The player checks if they are going to retire.
-    pub fn retires(&self, rng: &mut ThreadRng) -> bool {
-        // A player under contract or receiving offers will never retire.
-        if self.person.contract.is_some() ||
-        !self.person.contract_offers.is_empty() {
-            return false;
-        }
-
-        // 1 in 2000 chance to retire.
-        return rng.random_bool(0.0005);
+    // Reject the contract of a team.
+    pub fn reject_contract(&self, team_id: TeamId) {
+        let mut team = Team::fetch_from_db(&team_id);
+        team.approached_players.retain(|id| *id != self.id);
+        team.save();
     }
 }
\ No newline at end of file
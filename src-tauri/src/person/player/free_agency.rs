@@ -0,0 +1,289 @@
+// Contract expiry, extensions and the rights teams keep to their former players.
+
+use serde_json::json;
+use time::Date;
+
+use crate::{database::PLAYERS, person::{Contract, player::Player}, team::Team, time::{date_to_db_string, db_string_to_date}, types::TeamId};
+
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub enum FreeAgentStatus {
+    // Free to sign with anyone.
+    #[default]
+    Unrestricted,
+
+    // The former team can match offers from other teams until the given date.
+    Restricted(TeamId, String),
+}
+
+impl Player {
+    // Get all players with an offer sheet the given team can match.
+    pub fn get_offer_sheets_for_team(team_id: TeamId) -> Vec<Self> {
+        PLAYERS.lock().unwrap().values().filter_map(|a| {
+            match a.get_rights_holder() == Some(team_id) && a.get_offer_sheet().is_some() {
+                true => Some(a.clone()),
+                _ => None
+            }
+        }).collect()
+    }
+
+    // Check if the player is under contract with the given team.
+    pub fn is_under_contract_with(&self, team_id: TeamId) -> bool {
+        match self.person.contract.as_ref() {
+            Some(contract) => contract.team_id == team_id,
+            _ => false
+        }
+    }
+
+    // Check if the team is allowed to start negotiating an extension with the player.
+    pub fn is_open_to_extension_from(&self, team_id: TeamId, today: &Date) -> bool {
+        self.is_under_contract_with(team_id) &&
+        self.waivers.is_none() &&
+        !self.announced_retirement &&
+        self.person.contract.as_ref().unwrap().is_in_final_season(today) &&
+        self.get_negotiation(team_id).is_none()
+    }
+
+    // Get the team that holds the rights to the player, if any.
+    pub fn get_rights_holder(&self) -> Option<TeamId> {
+        match self.free_agent_status {
+            FreeAgentStatus::Restricted(team_id, _) => Some(team_id),
+            _ => None
+        }
+    }
+
+    // Check if the team would have a right of first refusal on the player.
+    pub fn has_right_of_first_refusal(&self, team_id: TeamId) -> bool {
+        self.get_rights_holder() == Some(team_id) &&
+        Team::fetch_from_db(&team_id).get_primary_competition().contract_rules.right_of_first_refusal
+    }
+
+    // Check if the team can still tender a qualifying offer to the player.
+    pub fn can_receive_qualifying_offer(&self, team_id: TeamId, today: &Date) -> bool {
+        if !self.is_under_contract_with(team_id) || self.has_qualifying_offer { return false; }
+
+        let contract = self.person.contract.as_ref().unwrap();
+        let rules = Team::fetch_from_db(&team_id).get_primary_competition().contract_rules;
+
+        contract.is_in_final_season(today) &&
+        rules.qualifying_offer.is_some() &&
+        rules.is_restricted_age(self.person.get_age_years(&contract.get_end_date()))
+    }
+
+    // Let the player go from their team once their contract has ended.
+    pub fn become_free_agent(&mut self, today: &Date) {
+        let contract = self.person.contract.take().unwrap();
+        let mut team = Team::fetch_from_db(&contract.team_id);
+        team.roster.retain(|id| *id != self.id);
+
+        let tendered = self.has_qualifying_offer;
+        self.has_qualifying_offer = false;
+        self.waivers = None;
+        self.end_loan();
+
+        let comp = team.get_primary_competition();
+        let rules = &comp.contract_rules;
+
+        if rules.is_restricted_age(self.person.get_age_years(today)) {
+            match rules.get_qualifying_salary(contract.salary) {
+                // The team did not bother to keep its rights.
+                Some(_) if !tendered => {},
+
+                Some(salary) => {
+                    let offer = Contract::build_with_terms(&team, today, 1, salary, contract.role);
+                    self.receive_offer(offer, 1, today);
+                    team.approached_players.push(self.id);
+                    self.set_restricted(team.id, &comp.season_window.get_next_start_date(today));
+                },
+
+                None => self.set_restricted(team.id, &comp.season_window.get_next_start_date(today)),
+            }
+        }
+
+        team.save();
+    }
+
+    // Make the player a restricted free agent.
+    fn set_restricted(&mut self, team_id: TeamId, until: &Date) {
+        self.free_agent_status = FreeAgentStatus::Restricted(team_id, date_to_db_string(until));
+    }
+
+    // Lift the restrictions once their time is up.
+    pub fn check_free_agent_status(&mut self, today: &Date) {
+        let expired = match &self.free_agent_status {
+            FreeAgentStatus::Restricted(_, until) => *today >= db_string_to_date(until),
+            _ => false
+        };
+
+        if expired || self.person.contract.is_some() {
+            self.free_agent_status = FreeAgentStatus::Unrestricted;
+        }
+    }
+
+    // Get relevant information about the free agent status for the frontend.
+    pub fn get_free_agent_status_package(&self) -> serde_json::Value {
+        match &self.free_agent_status {
+            FreeAgentStatus::Unrestricted => json!({
+                "restricted": false,
+            }),
+            FreeAgentStatus::Restricted(team_id, until) => json!({
+                "restricted": true,
+                "team": Team::fetch_from_db(team_id).get_contract_package(),
+                "until": until,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{competition::{Competition, contract_rules::ContractRules}, person::{ContractRole, Person}, time::{AnnualDate, AnnualWindow}, types::PlayerId};
+
+    // The contracts end on the last day of the season, and the next season starts in September.
+    const TODAY: &str = "2026-06-30";
+    const NEXT_SEASON_START: &str = "2026-09-01";
+
+    // Save a league with the contract rules and a team in it.
+    // Each test uses its own IDs, as the tests share the database.
+    fn save_team(id: TeamId, rules: ContractRules) -> Team {
+        Competition {
+            id: id,
+            season_window: AnnualWindow::build(AnnualDate::build(9, 1), AnnualDate::build(6, 30)),
+            contract_rules: rules,
+            ..Default::default()
+        }.save();
+
+        let team = Team {
+            id: id,
+            primary_comp_id: id,
+            ..Default::default()
+        };
+        team.save();
+        return team;
+    }
+
+    // Save a player whose contract with the team ends today.
+    fn save_player(id: PlayerId, team: &mut Team, birthday: &str, has_qualifying_offer: bool) -> Player {
+        let mut contract = Contract::build("2024-07-01", TODAY, team.id);
+        contract.salary = 1000;
+        contract.role = ContractRole::Regular;
+
+        let player = Player {
+            id: id,
+            person: Person {
+                contract: Some(contract),
+                birthday: db_string_to_date(birthday),
+                ..Default::default()
+            },
+            has_qualifying_offer: has_qualifying_offer,
+            ..Default::default()
+        };
+        player.save();
+
+        team.roster.push(id);
+        team.save();
+        return player;
+    }
+
+    #[test]
+    fn untendered_player_becomes_unrestricted() {
+        let mut team = save_team(64001, ContractRules::build(25, Some(100), true));
+        let mut player = save_player(64001, &mut team, "2002-01-01", false);
+        player.become_free_agent(&db_string_to_date(TODAY));
+
+        assert!(player.person.contract.is_none());
+        assert_eq!(player.get_rights_holder(), None);
+        assert!(player.negotiations.is_empty());
+        assert!(!Team::fetch_from_db(&team.id).roster.contains(&player.id));
+    }
+
+    #[test]
+    fn tendered_player_gets_qualifying_offer() {
+        let mut team = save_team(64002, ContractRules::build(25, Some(105), true));
+        let mut player = save_player(64002, &mut team, "2002-01-01", true);
+        player.become_free_agent(&db_string_to_date(TODAY));
+
+        assert!(player.person.contract.is_none());
+        assert!(!player.has_qualifying_offer);
+        assert_eq!(player.get_rights_holder(), Some(team.id));
+        assert!(matches!(&player.free_agent_status, FreeAgentStatus::Restricted(_, until) if until == NEXT_SEASON_START));
+
+        let offer = &player.get_negotiation(team.id).unwrap().offer;
+        assert_eq!(offer.salary, 1050);
+        assert!(offer.role == ContractRole::Regular);
+
+        let team = Team::fetch_from_db(&team.id);
+        assert!(!team.roster.contains(&player.id));
+        assert!(team.approached_players.contains(&player.id));
+    }
+
+    #[test]
+    fn rights_are_kept_without_qualifying_offers() {
+        let mut team = save_team(64003, ContractRules::build(25, None, true));
+        let mut player = save_player(64003, &mut team, "2002-01-01", false);
+        player.become_free_agent(&db_string_to_date(TODAY));
+
+        assert_eq!(player.get_rights_holder(), Some(team.id));
+        assert!(player.negotiations.is_empty());
+    }
+
+    #[test]
+    fn older_player_becomes_unrestricted() {
+        let mut team = save_team(64004, ContractRules::build(25, Some(105), true));
+        let mut player = save_player(64004, &mut team, "1996-01-01", true);
+        player.become_free_agent(&db_string_to_date(TODAY));
+
+        assert_eq!(player.get_rights_holder(), None);
+        assert!(player.negotiations.is_empty());
+    }
+
+    // Make the player a restricted free agent of the first team, with an offer sheet from the second team.
+    fn save_offer_sheet(player_id: PlayerId, team_ids: [TeamId; 2]) -> (Player, [Team; 2]) {
+        let today = db_string_to_date(TODAY);
+        let mut rights_holder = save_team(team_ids[0], ContractRules::build(25, None, true));
+        let offering_team = save_team(team_ids[1], ContractRules::build(25, None, true));
+
+        let mut player = save_player(player_id, &mut rights_holder, "2002-01-01", false);
+        player.become_free_agent(&today);
+        player.receive_offer(Contract::build_with_terms(&offering_team, &today, 3, 1500, ContractRole::Key), 3, &today);
+        player.send_offer_sheet(offering_team.id, &today);
+        player.save();
+
+        assert!(player.has_right_of_first_refusal(rights_holder.id));
+        assert!(!player.has_right_of_first_refusal(offering_team.id));
+        assert!(Player::get_offer_sheets_for_team(rights_holder.id).iter().any(|a| a.id == player.id));
+
+        return (player, [rights_holder, offering_team]);
+    }
+
+    #[test]
+    fn matched_offer_sheet_keeps_player() {
+        let (mut player, [rights_holder, offering_team]) = save_offer_sheet(64005, [64005, 64006]);
+        player.match_offer_sheet(&rights_holder, &db_string_to_date(TODAY));
+
+        let contract = player.person.contract.as_ref().unwrap();
+        assert_eq!(contract.team_id, rights_holder.id);
+        assert_eq!(contract.salary, 1500);
+        assert!(contract.role == ContractRole::Key);
+        assert_eq!(player.get_rights_holder(), None);
+        assert!(player.get_offer_sheet().is_none());
+
+        assert!(Team::fetch_from_db(&rights_holder.id).roster.contains(&player.id));
+        assert!(!Team::fetch_from_db(&offering_team.id).roster.contains(&player.id));
+    }
+
+    #[test]
+    fn declined_offer_sheet_lets_player_go() {
+        let (mut player, [rights_holder, offering_team]) = save_offer_sheet(64007, [64007, 64008]);
+        player.sign_negotiated_contract(offering_team.id, &db_string_to_date(TODAY));
+
+        let contract = player.person.contract.as_ref().unwrap();
+        assert_eq!(contract.team_id, offering_team.id);
+        assert_eq!(contract.salary, 1500);
+        assert_eq!(player.get_rights_holder(), None);
+
+        assert!(!Team::fetch_from_db(&rights_holder.id).roster.contains(&player.id));
+        assert!(Team::fetch_from_db(&offering_team.id).roster.contains(&player.id));
+    }
+}
@@ -0,0 +1,112 @@
+// Players playing for another team while still under contract with their own.
+
+use serde_json::json;
+use time::Date;
+
+use crate::{person::player::Player, team::Team, time::{date_to_db_string, db_string_to_date}, types::TeamId};
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Loan {
+    // The team the player is loaned to.
+    pub team_id: TeamId,
+    start_date: String,
+    end_date: String,
+}
+
+impl Loan {
+    fn build(team_id: TeamId, start_date: &Date, end_date: &Date) -> Self {
+        Self {
+            team_id: team_id,
+            start_date: date_to_db_string(start_date),
+            end_date: date_to_db_string(end_date),
+        }
+    }
+
+    // Check if the loan has run its course.
+    fn is_over(&self, today: &Date) -> bool {
+        *today >= db_string_to_date(&self.end_date)
+    }
+
+    // Get relevant information for the frontend.
+    fn get_package(&self) -> serde_json::Value {
+        json!({
+            "team": Team::fetch_from_db(&self.team_id).get_contract_package(),
+            "start_date": self.start_date,
+            "end_date": self.end_date,
+        })
+    }
+}
+
+impl Player {
+    // Check if the player is currently playing for another team.
+    pub fn is_on_loan(&self) -> bool {
+        self.loan.is_some()
+    }
+
+    // Check if the player is on loan to the given team.
+    pub fn is_on_loan_to(&self, team_id: TeamId) -> bool {
+        match self.loan.as_ref() {
+            Some(loan) => loan.team_id == team_id,
+            _ => false
+        }
+    }
+
+    // Get the ID of the team the player is currently playing for, 0 if none.
+    pub fn get_club_id(&self) -> TeamId {
+        match (self.loan.as_ref(), self.person.contract.as_ref()) {
+            (Some(loan), _) => loan.team_id,
+            (_, Some(contract)) => contract.team_id,
+            _ => 0
+        }
+    }
+
+    // Check if the player is allowed to play for the given team today.
+    // A player who has just been loaned cannot play for their new team on the same day.
+    pub fn is_eligible_to_play_for(&self, team_id: TeamId, today: &Date) -> bool {
+        match self.loan.as_ref() {
+            Some(loan) => loan.team_id == team_id && db_string_to_date(&loan.start_date) < *today,
+            None => self.is_under_contract_with(team_id) && self.waivers.is_none()
+        }
+    }
+
+    // Send the player to play for another team until the given date.
+    // The loan never lasts longer than the player's contract.
+    pub fn start_loan(&mut self, team: &mut Team, today: &Date, end_date: &Date) {
+        let contract_end = self.person.contract.as_ref().unwrap().get_end_date();
+        let end_date = match *end_date < contract_end {
+            true => *end_date,
+            _ => contract_end
+        };
+
+        team.loaned_players.push(self.id);
+        self.loan = Some(Loan::build(team.id, today, &end_date));
+    }
+
+    // Bring the player back from their loan, if they are on one.
+    pub fn end_loan(&mut self) {
+        let loan = match self.loan.take() {
+            Some(loan) => loan,
+            None => return
+        };
+
+        let mut team = Team::fetch_from_db(&loan.team_id);
+        team.loaned_players.retain(|id| *id != self.id);
+        team.save();
+    }
+
+    // End the loan once its time is up.
+    pub fn check_loan(&mut self, today: &Date) {
+        if self.loan.as_ref().is_some_and(|a| a.is_over(today)) {
+            self.end_loan();
+        }
+    }
+
+    // Get relevant information about the player's loan for the frontend.
+    pub fn get_loan_package(&self) -> serde_json::Value {
+        match self.loan.as_ref() {
+            Some(loan) => loan.get_package(),
+            None => serde_json::Value::Null
+        }
+    }
+}
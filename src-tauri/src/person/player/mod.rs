@@ -1,4 +1,11 @@
 pub mod position;
+pub mod negotiation;
+pub mod personality;
+pub mod free_agency;
+pub mod waivers;
+pub mod loan;
+pub mod retirement;
+pub mod population;
 mod ai;
 
 use rand::{rngs::ThreadRng, Rng};
@@ -6,10 +13,10 @@ use serde_json::json;
 use time::Date;
 
 use crate::{
-    database::PLAYERS, person::{Gender, attribute::{AttributeId, PersonAttribute}}, time::date_to_db_string, types::{AttributeValue, PlayerId, TeamId}
+    database::{self, IDS, IdKind, PLAYERS}, person::{Gender, attribute::{AttributeId, PersonAttribute}}, time::date_to_db_string, types::{AttributeValue, CountryId, PlayerId, TeamId}
 };
 use super::Person;
-use self::position::{Position, PositionId};
+use self::{free_agency::FreeAgentStatus, loan::Loan, negotiation::Negotiation, personality::Personality, position::{Position, PositionId}, waivers::Waivers};
 
 #[derive(Debug)]
 #[derive(Default, Clone)]
@@ -18,6 +25,24 @@ pub struct Player {
     pub person: Person,
     pub ability: PersonAttribute,
     pub position_id: PositionId,
+    pub personality: Personality,
+    pub negotiations: Vec<Negotiation>,
+    pub free_agent_status: FreeAgentStatus,
+
+    // Whether the team has promised the player a qualifying offer once their contract ends.
+    pub has_qualifying_offer: bool,
+    pub waivers: Option<Waivers>,
+    pub loan: Option<Loan>,
+
+    // Career so far, for the retirement decision and the archive.
+    pub peak_ability: u8,
+    pub games_played: u16,
+    pub announced_retirement: bool,
+
+    // The national team the player is away with, 0 if none.
+    pub national_team_id: TeamId,
+    pub international_caps: u16,
+    pub international_goals: u16,
 }
 
 // Basics.
@@ -40,20 +65,37 @@ impl Player {
     }
 
     // Create a player and store it in the database. Return a clone of the Player.
-    pub fn build_and_save(today: &Date, rng: &mut ThreadRng, min_age: u8, max_age: u8) -> Self {
-        let player = Self::create(today, rng, min_age, max_age);
+    pub fn build_and_save(today: &Date, rng: &mut ThreadRng, min_age: u8, max_age: u8, country_weights: &[(CountryId, u32)]) -> Self {
+        let player = Self::create(today, rng, min_age, max_age, country_weights);
         player.save();
         return player;
     }
 
     // Just like build and save, but minimal arguments.
-    pub fn create(today: &Date, rng: &mut ThreadRng, min_age: u8, max_age: u8) -> Self {
-        let person = Person::create(today, rng, min_age, max_age, Gender::Male);
+    pub fn create(today: &Date, rng: &mut ThreadRng, min_age: u8, max_age: u8, country_weights: &[(CountryId, u32)]) -> Self {
+        let person = Person::create(today, rng, min_age, max_age, Gender::Male, country_weights);
+        return Self::create_from_person(today, rng, person, 0.0);
+    }
+
+    // Create a random player from the given country.
+    pub fn create_with_country(today: &Date, rng: &mut ThreadRng, min_age: u8, max_age: u8, country_id: CountryId) -> Self {
+        let person = Person::create_with_country(today, rng, min_age, max_age, country_id, Gender::Male);
+        return Self::create_from_person(today, rng, person, 0.0);
+    }
+
+    // Create a random player out of a person.
+    // The ability can be shifted up or down by the given amount of display points.
+    fn create_from_person(today: &Date, rng: &mut ThreadRng, person: Person, ability_shift: f64) -> Self {
         let position_id = PositionId::get_random(rng);
 
         let mut player = Self::build(person, position_id);
         player.create_ability(today, rng);
-        player.create_id(PLAYERS.lock().unwrap().len() + 1);
+        if ability_shift != 0.0 {
+            player.ability.shift_display(ability_shift);
+            player.peak_ability = (player.peak_ability as f64 + ability_shift).clamp(0.0, u8::MAX as f64) as u8;
+        }
+        player.personality = Personality::build_random(rng, player.ability.get_display());
+        player.create_id(IDS.lock().unwrap().next(IdKind::Player));
 
         return player;
     }
@@ -69,7 +111,19 @@ impl Player {
 
     // Get a player from the database.
     pub fn fetch_from_db(id: &PlayerId) -> Option<Self> {
-        PLAYERS.lock().unwrap().get(id).cloned()
+        Self::read_from_db(id, Self::clone)
+    }
+
+    // Look at the player in the database without cloning it.
+    // The players stay locked meanwhile, so the function must not access them.
+    pub fn read_from_db<R>(id: &PlayerId, f: impl FnOnce(&Self) -> R) -> Option<R> {
+        database::read_entry(&PLAYERS, id, f)
+    }
+
+    // Change the player in the database in place, without cloning it or saving it afterwards.
+    // The players stay locked meanwhile, so the function must not access them.
+    pub fn update_in_db<R>(id: &PlayerId, f: impl FnOnce(&mut Self) -> R) -> Option<R> {
+        database::update_entry(&PLAYERS, id, f)
     }
 
     // Update the Team to database.
@@ -90,22 +144,8 @@ impl Player {
     // Get all free agents from the database with given positions, which the given team has not approached yet.
     pub fn get_free_agents_for_team(positions: Vec<&PositionId>, team_id: TeamId) -> Vec<Self> {
         PLAYERS.lock().unwrap().iter().filter_map(|(_, a)| {
-            match a.person.contract.is_none() && a.person.is_active && positions.contains(&&a.position_id) {
-                true => {
-                    let mut team_has_offer = false;
-                    for offer in a.person.contract_offers.iter() {
-                        if offer.team_id == team_id {
-                            team_has_offer = true;
-                            break;
-                        }
-                    }
-
-                    match team_has_offer {
-                        true => None,
-                        _ => Some(a.clone())
-                    }
-                },
-
+            match a.is_open_to_offers_from(team_id) && positions.contains(&&a.position_id) {
+                true => Some(a.clone()),
                 _ => None
             }
         }).collect()
@@ -137,7 +177,7 @@ impl Player {
             _ => None
         };
 
-        let contract_offers: Vec<serde_json::Value> = self.person.contract_offers.iter().map(|a| a.get_package(today)).collect();
+        let negotiations: Vec<serde_json::Value> = self.negotiations.iter().map(|a| a.get_package(today)).collect();
 
         json!({
             "id": self.id,
@@ -148,8 +188,17 @@ impl Player {
             "birthday": date_to_db_string(&self.person.birthday),
             "ability": self.ability.get_display(),
             "real_ability": self.ability.get(),
+            "has_agent": self.personality.has_agent,
             "contract": contract,
-            "offers": contract_offers
+            "free_agent_status": self.get_free_agent_status_package(),
+            "has_qualifying_offer": self.has_qualifying_offer,
+            "waivers": self.get_waivers_package(),
+            "loan": self.get_loan_package(),
+            "announced_retirement": self.announced_retirement,
+            "games_played": self.games_played,
+            "international_caps": self.international_caps,
+            "international_goals": self.international_goals,
+            "negotiations": negotiations
         })
     }
 
@@ -167,8 +216,14 @@ impl Player {
         self.train(rng, self.person.get_age_days(today));
     }
 
+    // Record a game the player played in.
+    pub fn play_game(&mut self) {
+        self.games_played += 1;
+    }
+
     // Do the training (also used in player generation).
     fn train(&mut self, rng: &mut ThreadRng, age_days: u16) {
         self.ability.update(age_days, rng);
+        self.peak_ability = self.peak_ability.max(self.ability.get_display());
     }
 }
\ No newline at end of file
@@ -0,0 +1,488 @@
+// Contract negotiations between a player and a team.
+
+use rand::{Rng, rngs::ThreadRng};
+use serde_json::json;
+use time::Date;
+
+use crate::{database::PLAYERS, person::{Contract, ContractRole, player::Player}, team::Team, time::{date_to_db_string, db_string_to_date}, types::TeamId};
+
+#[derive(Debug, serde::Serialize)]
+#[derive(Default, Clone, PartialEq)]
+pub enum NegotiationStatus {
+    // The team has made an offer and waits for the player to answer.
+    #[default]
+    Offered,
+
+    // The player has answered with demands of their own.
+    Countered,
+
+    // The team has left the player's demands unanswered for too long.
+    Stalled,
+
+    // The player is happy with the offer and ready to sign.
+    Agreed,
+
+    // The player has agreed, but the team holding their rights can still match the offer.
+    AwaitingMatch,
+
+    // Either side has walked away.
+    BrokenDown,
+}
+
+// The terms the player wants.
+#[derive(Debug, serde::Serialize)]
+#[derive(Clone)]
+pub struct Demand {
+    pub years: i32,
+    pub salary: u32,
+    pub role: ContractRole,
+}
+
+impl Demand {
+    // Check if the offer is good enough for the demand.
+    fn is_met_by(&self, offer: &Contract, years: i32) -> bool {
+        // Being a few percent short on salary is not worth arguing about.
+        offer.salary as f64 >= self.salary as f64 * 0.95 &&
+        offer.role >= self.role &&
+        (years - self.years).abs() <= 1
+    }
+
+    // Give in a bit towards the offer.
+    // The length and the role move one step at a time, so they meet the offer if the talks go on long enough.
+    fn soften(&mut self, offer: &Contract, years: i32, concession: f64) {
+        if offer.salary < self.salary {
+            self.salary -= ((self.salary - offer.salary) as f64 * concession) as u32;
+        }
+
+        if (years - self.years).abs() > 1 {
+            self.years += (years - self.years).signum();
+        }
+
+        if offer.role < self.role {
+            self.role = match self.role {
+                ContractRole::Key => ContractRole::Regular,
+                _ => ContractRole::Depth
+            };
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Negotiation {
+    pub team_id: TeamId,
+
+    // The latest offer from the team.
+    pub offer: Contract,
+    pub years: i32,
+
+    // The latest demands from the player.
+    pub demand: Option<Demand>,
+
+    // How many times the player has made demands.
+    pub round: u8,
+    pub status: NegotiationStatus,
+
+    // When either side last made a move.
+    last_move: String,
+}
+
+// Basics.
+impl Negotiation {
+    // Days the team has to answer the player's demands before talks stall.
+    const STALL_DAYS: i64 = 7;
+
+    // Days a stalled negotiation lasts before it breaks down.
+    const BREAKDOWN_DAYS: i64 = 14;
+
+    // Days before a team can try again with a player who has walked away from them.
+    const COOLDOWN_DAYS: i64 = 30;
+
+    // Days the team holding the player's rights has to match an offer.
+    const MATCH_DAYS: i64 = 7;
+
+    fn build(offer: Contract, years: i32, today: &Date) -> Self {
+        Self {
+            team_id: offer.team_id,
+            offer: offer,
+            years: years,
+            demand: None,
+            round: 0,
+            status: NegotiationStatus::default(),
+            last_move: date_to_db_string(today),
+        }
+    }
+
+    // Check if the terms make sense as an offer at all.
+    pub fn is_valid_offer(years: i32, salary: u32) -> bool {
+        years >= 1 && salary > 0
+    }
+
+    // Get how many days have passed since the last move.
+    fn get_days_since_last_move(&self, today: &Date) -> i64 {
+        (*today - db_string_to_date(&self.last_move)).whole_days()
+    }
+
+    // Check if the negotiation is still going on.
+    pub fn is_open(&self) -> bool {
+        self.status != NegotiationStatus::BrokenDown
+    }
+
+    // Check if it is the team's turn to make a move.
+    pub fn awaits_team(&self) -> bool {
+        self.status == NegotiationStatus::Countered || self.status == NegotiationStatus::Stalled
+    }
+
+    // Replace the offer on the table with a new one.
+    fn update_offer(&mut self, offer: Contract, years: i32, today: &Date) {
+        self.offer = offer;
+        self.years = years;
+        self.status = NegotiationStatus::Offered;
+        self.last_move = date_to_db_string(today);
+    }
+
+    // Set the status and mark the move.
+    fn set_status(&mut self, status: NegotiationStatus, today: &Date) {
+        self.status = status;
+        self.last_move = date_to_db_string(today);
+    }
+
+    // Get relevant information for the frontend.
+    pub fn get_package(&self, today: &Date) -> serde_json::Value {
+        json!({
+            "status": self.status,
+            "round": self.round,
+            "years": self.years,
+            "offer": self.offer.get_package(today),
+            "demand": self.demand,
+            "last_move": self.last_move,
+        })
+    }
+}
+
+// Player's side of the negotiations.
+impl Player {
+    // Get the yearly salary the player is worth, in thousands.
+    pub fn get_market_value(&self) -> u32 {
+        let ability = self.ability.get_display() as u32;
+        return 40 + ability * ability / 5;
+    }
+
+    // Get the average yearly salary the players in the game are worth, in thousands.
+    pub fn get_average_market_value() -> u32 {
+        let players = PLAYERS.lock().unwrap();
+        if players.is_empty() { return 0; }
+
+        let total: u32 = players.values().map(|a| a.get_market_value()).sum();
+        return total / players.len() as u32;
+    }
+
+    // Get the role the player would have in the team.
+    // None if the player would not make the lineup.
+    pub fn get_expected_role(&self, team: &Team) -> Option<ContractRole> {
+        let need = self.get_need_in_team(team)?;
+
+        if self.ability.get_display() as f64 <= need.get_worst() {
+            return None;
+        }
+
+        let role_value = need.get_role_of_player(self);
+        let role = if role_value >= 1.0 {
+            ContractRole::Key
+        }
+        else if role_value <= 1.0 / need.get_lineup_places() as f64 {
+            ContractRole::Depth
+        }
+        else {
+            ContractRole::Regular
+        };
+
+        return Some(role);
+    }
+
+    // Get the terms the player would ideally want from the team.
+    fn get_ideal_demand(&self, team: &Team, today: &Date) -> Option<Demand> {
+        let role = self.get_expected_role(team)?;
+
+        let role_modifier = match role {
+            ContractRole::Key => 1.1,
+            ContractRole::Regular => 1.0,
+            ContractRole::Depth => 0.9,
+        };
+
+        let mut greed_modifier = 1.0 + self.personality.greed as f64 / 400.0;
+        if self.personality.has_agent {
+            greed_modifier += 0.1;
+        }
+
+        // Loyal players give a discount to their own team.
+        if self.is_under_contract_with(team.id) || self.get_rights_holder() == Some(team.id) {
+            greed_modifier -= self.personality.loyalty as f64 / 500.0;
+        }
+
+        // Young players want to cash in on their development soon, veterans want security.
+        let years = match self.person.get_age_years(today) {
+            ..=22 => 2,
+            23..=29 => 3,
+            _ => 4
+        };
+
+        return Some(Demand {
+            years: years,
+            salary: (self.get_market_value() as f64 * role_modifier * greed_modifier) as u32,
+            role: role,
+        });
+    }
+
+    // Get the share of the gap between demand and offer the player gives in each round.
+    fn get_concession(&self) -> f64 {
+        let concession = (100 - self.personality.greed) as f64 / 400.0;
+        match self.personality.has_agent {
+            true => concession / 2.0,
+            _ => concession
+        }
+    }
+
+    // Get how many rounds the player is willing to negotiate.
+    fn get_patience(&self) -> u8 {
+        match self.personality.has_agent {
+            true => self.personality.patience + 1,
+            _ => self.personality.patience
+        }
+    }
+
+    // Get the negotiation with the given team.
+    pub fn get_negotiation(&self, team_id: TeamId) -> Option<&Negotiation> {
+        self.negotiations.iter().find(|a| a.team_id == team_id)
+    }
+
+    fn get_negotiation_mut(&mut self, team_id: TeamId) -> Option<&mut Negotiation> {
+        self.negotiations.iter_mut().find(|a| a.team_id == team_id)
+    }
+
+    // Get how many teams are negotiating with the player.
+    pub fn get_open_negotiations_amount(&self) -> usize {
+        self.negotiations.iter().filter(|a| a.is_open()).count()
+    }
+
+    // Check if the team is allowed to start negotiating with the player.
+    pub fn is_open_to_offers_from(&self, team_id: TeamId) -> bool {
+        self.person.contract.is_none() && self.person.is_active && !self.announced_retirement && self.get_negotiation(team_id).is_none()
+    }
+
+    // Start negotiating with a team.
+    pub fn receive_offer(&mut self, offer: Contract, years: i32, today: &Date) {
+        self.negotiations.push(Negotiation::build(offer, years, today));
+    }
+
+    // Receive a new offer from a team the player is already negotiating with.
+    // Return whether the offer was received.
+    pub fn receive_counter_offer(&mut self, offer: Contract, years: i32, today: &Date) -> bool {
+        match self.get_negotiation_mut(offer.team_id) {
+            Some(negotiation) if negotiation.awaits_team() => {
+                negotiation.update_offer(offer, years, today);
+                true
+            },
+            _ => false
+        }
+    }
+
+    // Stop negotiating with a team.
+    pub fn break_off_negotiation(&mut self, team_id: TeamId, today: &Date) {
+        let negotiation = match self.get_negotiation_mut(team_id) {
+            Some(n) => n,
+            None => return
+        };
+
+        negotiation.set_status(NegotiationStatus::BrokenDown, today);
+        self.reject_contract(team_id);
+    }
+
+    // Go through the negotiations of the day.
+    pub fn handle_negotiations(&mut self, today: &Date, rng: &mut ThreadRng) {
+        // Teams that have been walked away from can try again after a while.
+        self.negotiations.retain(|a| a.is_open() || a.get_days_since_last_move(today) < Negotiation::COOLDOWN_DAYS);
+
+        for i in 0..self.negotiations.len() {
+            let negotiation = &self.negotiations[i];
+            let team_id = negotiation.team_id;
+            let days = negotiation.get_days_since_last_move(today);
+
+            if !negotiation.is_open() { continue; }
+            if negotiation.offer.check_if_expired(today) {
+                self.break_off_negotiation(team_id, today);
+                continue;
+            }
+
+            match negotiation.status {
+                NegotiationStatus::Offered => {
+                    // Random chance for the player to answer, grows more likely the more time passes.
+                    // Guaranteed to answer after 10 days.
+                    if rng.random_range(1..10) < days {
+                        self.respond_to_offer(i, today, rng);
+                    }
+                },
+                NegotiationStatus::Countered if days >= Negotiation::STALL_DAYS => {
+                    self.negotiations[i].set_status(NegotiationStatus::Stalled, today);
+                },
+                NegotiationStatus::Stalled if days >= Negotiation::BREAKDOWN_DAYS => {
+                    self.break_off_negotiation(team_id, today);
+                },
+                // No answer from the team holding the rights, so the player is free to go.
+                NegotiationStatus::AwaitingMatch if days >= Negotiation::MATCH_DAYS => {
+                    self.sign_negotiated_contract(team_id, today);
+                    return;
+                },
+                _ => {}
+            }
+        }
+
+        // Nothing is signed while waiting for the offer to be matched.
+        if self.get_offer_sheet().is_some() { return; }
+
+        if self.negotiations.iter().any(|a| a.status == NegotiationStatus::Agreed) {
+            self.choose_contract(today, rng);
+        }
+    }
+
+    // Sign the contract negotiated with the given team.
+    pub fn sign_negotiated_contract(&mut self, team_id: TeamId, today: &Date) {
+        let index = self.negotiations.iter().position(|a| a.team_id == team_id).unwrap();
+        let contract = self.negotiations.swap_remove(index).offer;
+        self.sign_contract(contract, today);
+    }
+
+    // Get the agreed negotiation that waits for the rights holder to match it.
+    pub fn get_offer_sheet(&self) -> Option<&Negotiation> {
+        self.negotiations.iter().find(|a| a.status == NegotiationStatus::AwaitingMatch)
+    }
+
+    // Give the team holding the player's rights a chance to match the agreed offer.
+    pub fn send_offer_sheet(&mut self, team_id: TeamId, today: &Date) {
+        self.get_negotiation_mut(team_id).unwrap().set_status(NegotiationStatus::AwaitingMatch, today);
+    }
+
+    // The team holding the player's rights matches the offer sheet.
+    pub fn match_offer_sheet(&mut self, team: &Team, today: &Date) {
+        let sheet = self.get_offer_sheet().unwrap().clone();
+        let contract = Contract::build_with_terms(team, today, sheet.years, sheet.offer.salary, sheet.offer.role);
+        self.sign_contract(contract, today);
+    }
+
+    // Give an answer to the offer in the negotiation of the given index.
+    fn respond_to_offer(&mut self, index: usize, today: &Date, rng: &mut ThreadRng) {
+        let team_id = self.negotiations[index].team_id;
+        let team = Team::fetch_from_db(&team_id);
+
+        // A player never wants to join a team where their playing time is uncertain.
+        let ideal = match self.get_ideal_demand(&team, today) {
+            Some(demand) => demand,
+            None => {
+                self.break_off_negotiation(team_id, today);
+                return;
+            }
+        };
+
+        let concession = self.get_concession();
+        let patience = self.get_patience();
+        let greed = self.personality.greed;
+
+        let negotiation = &mut self.negotiations[index];
+        let demand = match negotiation.demand.as_mut() {
+            Some(demand) => {
+                demand.soften(&negotiation.offer, negotiation.years, concession);
+                demand.clone()
+            },
+            None => ideal
+        };
+
+        if demand.is_met_by(&negotiation.offer, negotiation.years) {
+            negotiation.demand = Some(demand);
+            negotiation.set_status(NegotiationStatus::Agreed, today);
+            return;
+        }
+
+        // Lowball offers can insult greedy players.
+        let insulted = negotiation.offer.salary * 2 < demand.salary && rng.random_bool(greed as f64 / 100.0);
+        if insulted || negotiation.round >= patience {
+            negotiation.demand = Some(demand);
+            self.break_off_negotiation(team_id, today);
+            return;
+        }
+
+        negotiation.demand = Some(demand);
+        negotiation.round += 1;
+        negotiation.set_status(NegotiationStatus::Countered, today);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_offer(salary: u32, role: ContractRole) -> Contract {
+        let mut offer = Contract::build("2025-07-01", "2027-06-30", 1);
+        offer.salary = salary;
+        offer.role = role;
+        return offer;
+    }
+
+    // Soften the demand until the offer meets it, and return how many rounds it took.
+    fn rounds_to_agree(demand: &mut Demand, offer: &Contract, years: i32, concession: f64) -> Option<u8> {
+        for round in 0..=50 {
+            if demand.is_met_by(offer, years) {
+                return Some(round);
+            }
+            demand.soften(offer, years, concession);
+        }
+
+        return None;
+    }
+
+    #[test]
+    fn demand_meets_offer_of_any_length() {
+        for (demanded_years, offered_years) in [(2, 3), (2, 4), (4, 1), (1, 5)] {
+            let mut demand = Demand { years: demanded_years, salary: 500, role: ContractRole::Regular };
+            let offer = build_offer(500, ContractRole::Regular);
+
+            assert!(rounds_to_agree(&mut demand, &offer, offered_years, 0.2).is_some());
+            assert!((demand.years - offered_years).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn demand_settles_for_smaller_role() {
+        let mut demand = Demand { years: 3, salary: 500, role: ContractRole::Key };
+        let offer = build_offer(500, ContractRole::Depth);
+
+        assert_eq!(rounds_to_agree(&mut demand, &offer, 3, 0.2), Some(2));
+        assert!(demand.role == ContractRole::Depth);
+    }
+
+    #[test]
+    fn demand_approaches_offered_salary() {
+        let mut demand = Demand { years: 3, salary: 1000, role: ContractRole::Regular };
+        let offer = build_offer(600, ContractRole::Key);
+
+        let mut previous = demand.salary;
+        for _ in 0..10 {
+            demand.soften(&offer, 3, 0.25);
+            assert!(demand.salary < previous && demand.salary >= offer.salary);
+            previous = demand.salary;
+        }
+
+        assert!(rounds_to_agree(&mut demand, &offer, 3, 0.25).is_some());
+
+        // Players who never concede stick to their salary.
+        let mut demand = Demand { years: 3, salary: 1000, role: ContractRole::Regular };
+        assert_eq!(rounds_to_agree(&mut demand, &offer, 3, 0.0), None);
+        assert_eq!(demand.salary, 1000);
+    }
+
+    #[test]
+    fn empty_offers_are_invalid() {
+        assert!(Negotiation::is_valid_offer(1, 1));
+        assert!(!Negotiation::is_valid_offer(0, 500));
+        assert!(!Negotiation::is_valid_offer(2, 0));
+    }
+}
@@ -0,0 +1,32 @@
+// How a player behaves off the ice.
+
+use rand::{Rng, rngs::ThreadRng};
+
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct Personality {
+    // How much more than their worth the player asks for, between 0 and 100.
+    pub greed: u8,
+
+    // How many rounds of negotiations the player puts up with before walking away.
+    pub patience: u8,
+
+    // How much of a discount the player gives to the team they already play for, between 0 and 100.
+    pub loyalty: u8,
+
+    // Players with an agent bargain harder and give in slower.
+    pub has_agent: bool,
+}
+
+impl Personality {
+    // Create a random personality.
+    // Better players are more likely to have an agent.
+    pub fn build_random(rng: &mut ThreadRng, ability: u8) -> Self {
+        Self {
+            greed: rng.random_range(0..=100),
+            patience: rng.random_range(2..=5),
+            loyalty: rng.random_range(0..=100),
+            has_agent: rng.random_bool((ability as f64 / 120.0).clamp(0.05, 0.95)),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// Keeping the player population alive over the decades, as players retire and new ones come through.
+
+use rand::rngs::ThreadRng;
+use time::Date;
+
+use crate::{
+    country::Country, database::{PLAYERS, POPULATION}, person::{Gender, Person, player::Player}, team::Team, time::AnnualDate, types::{CountryId, convert}
+};
+
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct Population {
+    // How many active players the game should have.
+    target_size: usize,
+
+    // How good the best players were at the start of the game.
+    baseline_quality: f64,
+}
+
+impl Population {
+    // How many players there are per team in the game.
+    pub const PLAYERS_PER_TEAM: usize = 50;
+
+    // The day after the retirements, when the new generation of players comes through.
+    const YOUTH_INTAKE_DATE: AnnualDate = AnnualDate { month: 6, day: 2 };
+
+    // The ages of the players in the youth intake.
+    const MIN_YOUTH_AGE: u8 = 16;
+    const MAX_YOUTH_AGE: u8 = 17;
+
+    // How many display points the youth intake can be made better or worse by at most.
+    const MAX_ABILITY_SHIFT: f64 = 3.0;
+
+    // How many players per team count towards the quality of the population.
+    // Roughly the ones who get to play.
+    const QUALITY_PLAYERS_PER_TEAM: usize = 20;
+
+    // Take note of the current state of the population, so it can be kept that way.
+    pub fn set_baseline() {
+        let population = Self {
+            target_size: Team::get_club_amount() * Self::PLAYERS_PER_TEAM,
+            baseline_quality: Self::get_quality(),
+        };
+
+        *POPULATION.lock().unwrap() = population;
+    }
+
+    // Get the average ability of the players good enough to play in the leagues.
+    fn get_quality() -> f64 {
+        let mut abilities: Vec<u8> = PLAYERS.lock().unwrap().values().map(|a| a.ability.get_display()).collect();
+        abilities.sort_by(|a, b| b.cmp(a));
+        abilities.truncate(Team::get_club_amount() * Self::QUALITY_PLAYERS_PER_TEAM);
+
+        match abilities.len() {
+            0 => 0.0,
+            n => abilities.iter().map(|a| *a as f64).sum::<f64>() / convert::usize_to_f64(n)
+        }
+    }
+
+    // Get how many players each country should get out of the given amount, in proportion to the country weights.
+    fn get_players_per_country(amount: usize, weights: &[(CountryId, u32)]) -> Vec<(CountryId, usize)> {
+        let total_weight: u32 = weights.iter().map(|(_, weight)| weight).sum();
+        if total_weight == 0 { return Vec::new(); }
+
+        // Everyone gets their whole share first, and the leftovers go to those with the biggest remainders.
+        let mut shares: Vec<(CountryId, usize, f64)> = weights.iter().map(|(id, weight)| {
+            let share = amount as f64 * *weight as f64 / total_weight as f64;
+            (*id, share as usize, share.fract())
+        }).collect();
+
+        let handed_out: usize = shares.iter().map(|(_, n, _)| n).sum();
+        shares.sort_by(|a, b| b.2.total_cmp(&a.2));
+        for share in shares.iter_mut().take(amount - handed_out) {
+            share.1 += 1;
+        }
+
+        return shares.into_iter().map(|(id, n, _)| (id, n)).collect();
+    }
+
+    // Bring in a new generation of players to replace the ones who have retired.
+    pub fn handle_youth_intake(today: &Date, rng: &mut ThreadRng) {
+        if *today != Self::YOUTH_INTAKE_DATE.get_date(today.year()) { return; }
+
+        let population = POPULATION.lock().unwrap().clone();
+        let size = PLAYERS.lock().unwrap().len();
+        let shortfall = population.target_size.saturating_sub(size);
+        if shortfall == 0 { return; }
+
+        // If the best players are worse than they used to be, the youngsters are a bit more talented, and vice versa.
+        let ability_shift = (population.baseline_quality - Self::get_quality()).clamp(-Self::MAX_ABILITY_SHIFT, Self::MAX_ABILITY_SHIFT);
+
+        for (country_id, amount) in Self::get_players_per_country(shortfall, &Country::get_weights()) {
+            for _ in 0..amount {
+                Player::create_youth(today, rng, country_id, ability_shift).save();
+            }
+        }
+    }
+}
+
+impl Player {
+    // Create a youth player from the given country.
+    fn create_youth(today: &Date, rng: &mut ThreadRng, country_id: CountryId, ability_shift: f64) -> Self {
+        let person = Person::create_with_country(today, rng, Population::MIN_YOUTH_AGE, Population::MAX_YOUTH_AGE, country_id, Gender::Male);
+        return Self::create_from_person(today, rng, person, ability_shift);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{self, RETIRED_PLAYERS, TODAY};
+
+    #[test]
+    fn players_are_split_by_weight() {
+        let weights = [(1, 50), (2, 30), (3, 20)];
+        assert_eq!(Population::get_players_per_country(100, &weights), [(1, 50), (2, 30), (3, 20)]);
+
+        // The leftovers go to the biggest remainders, and nothing is lost.
+        let shares = Population::get_players_per_country(7, &weights);
+        assert_eq!(shares.iter().map(|(_, n)| n).sum::<usize>(), 7);
+        for (id, n) in shares.iter() {
+            let weight = weights.iter().find(|a| a.0 == *id).unwrap().1;
+            let exact = 7.0 * weight as f64 / 100.0;
+            assert!((*n as f64 - exact).abs() < 1.0, "country {id} got {n} players instead of {exact}");
+        }
+
+        assert!(Population::get_players_per_country(10, &[(1, 0), (2, 0)]).is_empty());
+        assert!(Population::get_players_per_country(10, &[]).is_empty());
+    }
+
+    // Let the players of the default world train, retire and be replaced for 50 seasons,
+    // and fail if the size or the quality of the population drifts away from the start.
+    // Teams do not act, so only the population itself is checked.
+    // Slow, so only run on demand: cargo test --release population_stays_stable -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn population_stays_stable() {
+        const SEASONS: i32 = 50;
+        const SIZE_TOLERANCE: f64 = 0.05;
+        const QUALITY_TOLERANCE: f64 = 5.0;
+
+        database::initialise();
+        let mut rng = rand::rng();
+        let mut today = TODAY.lock().unwrap().clone();
+        let end = today.replace_year(today.year() + SEASONS).unwrap();
+        let population = POPULATION.lock().unwrap().clone();
+
+        while today < end {
+            for id in database::get_ids(&PLAYERS, |a| a.person.is_active) {
+                let mut player = match Player::fetch_from_db(&id) {
+                    Some(a) => a,
+                    _ => continue
+                };
+
+                player.consider_retirement(&today, &mut rng);
+                if player.retires_today(&today) {
+                    player.retire(&today);
+                    continue;
+                }
+
+                player.daily_training(&today, &mut rng);
+                player.save();
+            }
+
+            Population::handle_youth_intake(&today, &mut rng);
+
+            // Check the population once the new generation has come through.
+            if today == Population::YOUTH_INTAKE_DATE.get_date(today.year()) {
+                let size = PLAYERS.lock().unwrap().len() as f64;
+                let quality = Population::get_quality();
+                println!("{}: {size} players, quality {quality:.1}, {} retired in total", today.year(), RETIRED_PLAYERS.lock().unwrap().len());
+
+                assert!((size / population.target_size as f64 - 1.0).abs() < SIZE_TOLERANCE, "{size} players in {}", today.year());
+                assert!((quality - population.baseline_quality).abs() < QUALITY_TOLERANCE, "quality {quality:.1} in {}, {:.1} at the start", today.year(), population.baseline_quality);
+            }
+
+            today = today.next_day().unwrap();
+        }
+
+        // Everyone from the start has retired by now.
+        assert!(RETIRED_PLAYERS.lock().unwrap().len() >= population.target_size);
+    }
+}
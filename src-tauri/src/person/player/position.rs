@@ -17,6 +17,11 @@ pub enum PositionId {
 }
 
 impl PositionId {
+    // Get every position a player can have.
+    pub fn get_all() -> Vec<Self> {
+        vec![Self::Goalkeeper, Self::LeftDefender, Self::RightDefender, Self::LeftWinger, Self::Centre, Self::RightWinger]
+    }
+
     // Get a random position, weighted by need.
     pub fn get_random(rng: &mut ThreadRng) -> Self {
         let weights = vec![
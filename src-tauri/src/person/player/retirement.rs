@@ -0,0 +1,216 @@
+// Players deciding to end their careers, and the archive of those who have.
+
+use rand::{Rng, rngs::ThreadRng};
+use serde_json::json;
+use time::Date;
+
+use crate::{
+    database::{PLAYERS, RETIRED_PLAYERS}, person::{Person, player::{Player, position::{Position, PositionId}}}, team::Team, time::{AnnualDate, date_to_db_string}, types::{PlayerId, TeamId}
+};
+
+// A player who has ended their career.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct RetiredPlayer {
+    pub id: PlayerId,
+    pub person: Person,
+    position_id: PositionId,
+    retirement_date: String,
+
+    // The team the player last played for, 0 if they retired as a free agent.
+    last_team_id: TeamId,
+    peak_ability: u8,
+    games_played: u16,
+    pub is_hall_of_famer: bool,
+}
+
+impl RetiredPlayer {
+    // How high a player has to have peaked among the active players to get into the hall of fame.
+    const HALL_OF_FAME_RANK: usize = 10;
+
+    fn build(player: &Player, last_team_id: TeamId, today: &Date) -> Self {
+        Self {
+            id: player.id,
+            person: player.person.clone(),
+            position_id: player.position_id.clone(),
+            retirement_date: date_to_db_string(today),
+            last_team_id: last_team_id,
+            peak_ability: player.peak_ability,
+            games_played: player.games_played,
+            is_hall_of_famer: Self::is_worthy_of_hall_of_fame(player),
+        }
+    }
+
+    // Get a retired player from the database.
+    pub fn fetch_from_db(id: &PlayerId) -> Option<Self> {
+        RETIRED_PLAYERS.lock().unwrap().get(id).cloned()
+    }
+
+    // Update the retired player to database.
+    fn save(&self) {
+        RETIRED_PLAYERS.lock().unwrap().insert(self.id, self.clone());
+    }
+
+    // The best of the best get into the hall of fame.
+    // The bar is set by the active players, as what counts as great changes over time.
+    fn is_worthy_of_hall_of_fame(player: &Player) -> bool {
+        let better_players = PLAYERS.lock().unwrap().values()
+            .filter(|a| a.id != player.id && a.ability.get_display() >= player.peak_ability)
+            .count();
+
+        return better_players < Self::HALL_OF_FAME_RANK;
+    }
+
+    // Get relevant information for the frontend.
+    fn get_package(&self) -> serde_json::Value {
+        let last_team = match self.last_team_id {
+            0 => None,
+            id => Some(Team::fetch_from_db(&id).get_contract_package())
+        };
+
+        json!({
+            "id": self.id,
+            "name": self.person.get_full_name(),
+            "country": self.person.get_country().name,
+            "position": Position::fetch_from_db(&self.position_id).abbreviation,
+            "birthday": date_to_db_string(&self.person.birthday),
+            "retirement_date": self.retirement_date,
+            "last_team": last_team,
+            "peak_ability": self.peak_ability,
+            "games_played": self.games_played,
+            "is_hall_of_famer": self.is_hall_of_famer,
+        })
+    }
+
+    // Get every retired player for the frontend, the hall of famers first and the most recent first.
+    pub fn get_archive_package() -> serde_json::Value {
+        let mut players: Vec<Self> = RETIRED_PLAYERS.lock().unwrap().values().cloned().collect();
+        players.sort_by(|a, b| b.is_hall_of_famer.cmp(&a.is_hall_of_famer).then(b.retirement_date.cmp(&a.retirement_date)));
+
+        players.iter().map(|a| a.get_package()).collect()
+    }
+}
+
+impl Player {
+    // The day players let everyone know if they are going to retire at the end of the season.
+    const RETIREMENT_ANNOUNCEMENT_DATE: AnnualDate = AnnualDate { month: 4, day: 1 };
+
+    // The day the players who have announced their retirement hang up their skates.
+    const RETIREMENT_DATE: AnnualDate = AnnualDate { month: 6, day: 1 };
+
+    // The age at which players start to think about retiring.
+    const MIN_RETIREMENT_AGE: i8 = 30;
+
+    // The age by which every player has retired.
+    const MAX_RETIREMENT_AGE: i8 = 40;
+
+    // Get the chance of the player deciding to retire at the end of this season.
+    // Injuries are not modelled yet, so they play no part.
+    fn get_retirement_chance(&self, today: &Date) -> f64 {
+        let age = self.person.get_age_years(today);
+        if age < Self::MIN_RETIREMENT_AGE { return 0.0; }
+        if age >= Self::MAX_RETIREMENT_AGE { return 1.0; }
+
+        // The closer to the end of a career, the likelier it is to end.
+        let age_modifier = (age - Self::MIN_RETIREMENT_AGE) as f64 / (Self::MAX_RETIREMENT_AGE - Self::MIN_RETIREMENT_AGE) as f64;
+        let mut chance = age_modifier.powi(2);
+
+        // Players who are not what they used to be are more ready to call it a day.
+        let decline = self.peak_ability.saturating_sub(self.ability.get_display());
+        chance *= 1.0 + decline as f64 / 5.0;
+
+        // A contract for the next season keeps a player going, and going without one makes them think.
+        chance *= match self.person.contract.as_ref() {
+            Some(contract) if !contract.is_in_final_season(today) => 0.5,
+            Some(_) => 1.0,
+            None => 1.5
+        };
+
+        return chance.clamp(0.0, 1.0);
+    }
+
+    // The player decides whether to retire once the regular season is over.
+    pub fn consider_retirement(&mut self, today: &Date, rng: &mut ThreadRng) {
+        if self.announced_retirement || *today != Self::RETIREMENT_ANNOUNCEMENT_DATE.get_date(today.year()) { return; }
+
+        if rng.random_bool(self.get_retirement_chance(today)) {
+            self.announced_retirement = true;
+
+            // A player who is leaving the game does not need a new contract.
+            self.reject_contracts();
+            self.negotiations.clear();
+        }
+    }
+
+    // Check if it is time for the player to retire.
+    // Players on waivers have to wait until their fate is decided.
+    pub fn retires_today(&self, today: &Date) -> bool {
+        self.announced_retirement &&
+        self.waivers.is_none() &&
+        *today >= Self::RETIREMENT_DATE.get_date(today.year())
+    }
+
+    // End the player's career and move them to the archive.
+    // Whatever is left of the contract is void, so the team does not have to pay for it.
+    pub fn retire(&mut self, today: &Date) {
+        self.end_loan();
+        self.reject_contracts();
+        self.negotiations.clear();
+
+        let last_team_id = match self.person.contract.take() {
+            Some(contract) => {
+                let mut team = Team::fetch_from_db(&contract.team_id);
+                team.roster.retain(|id| *id != self.id);
+                team.save();
+                contract.team_id
+            },
+            None => 0
+        };
+
+        self.person.is_active = false;
+        RetiredPlayer::build(self, last_team_id, today).save();
+        self.delete_from_db();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    use super::*;
+    use crate::{person::attribute::{AttributeId, PersonAttribute}, time::{db_string_to_date, years_to_days}};
+
+    fn build_player(age: u8, ability: u16, today: &Date) -> Player {
+        let mut player = Player {
+            ability: PersonAttribute::build(AttributeId::General, ability),
+            ..Default::default()
+        };
+
+        // A day extra, so that the player has surely had their birthday.
+        player.person.birthday = *today - Duration::days(years_to_days(age) as i64 + 1);
+        player.peak_ability = player.ability.get_display();
+        return player;
+    }
+
+    #[test]
+    fn retirement_chance_grows_with_age() {
+        let today = db_string_to_date("2025-04-01");
+        let chances: Vec<f64> = [25, 30, 33, 36, 39, 40, 45].iter().map(|a| build_player(*a, 5000, &today).get_retirement_chance(&today)).collect();
+
+        assert_eq!(chances[0], 0.0);
+        assert_eq!(chances[1], 0.0);
+        assert!(chances[1..5].windows(2).all(|a| a[0] < a[1]));
+        assert_eq!(chances[5], 1.0);
+        assert_eq!(chances[6], 1.0);
+    }
+
+    #[test]
+    fn declining_players_retire_sooner() {
+        let today = db_string_to_date("2025-04-01");
+        let player = build_player(34, 5000, &today);
+        let mut declined = player.clone();
+        declined.peak_ability += 10;
+
+        assert!(declined.get_retirement_chance(&today) > player.get_retirement_chance(&today));
+    }
+}
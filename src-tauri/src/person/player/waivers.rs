@@ -0,0 +1,124 @@
+// The waiver wire, where other teams can claim the players a team has released.
+
+use serde_json::json;
+use time::Date;
+
+use crate::{database::PLAYERS, person::player::{Player, free_agency::FreeAgentStatus}, team::Team, time::{date_to_db_string, db_string_to_date}, types::TeamId};
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Waivers {
+    // The team that released the player.
+    pub team_id: TeamId,
+    placed: String,
+
+    // Teams that want the player.
+    pub claims: Vec<TeamId>,
+}
+
+impl Waivers {
+    // How many days other teams have to claim the player.
+    const CLAIM_DAYS: i64 = 2;
+
+    fn build(team_id: TeamId, today: &Date) -> Self {
+        Self {
+            team_id: team_id,
+            placed: date_to_db_string(today),
+            claims: Vec::new(),
+        }
+    }
+
+    // Check if the time to claim the player is over.
+    fn is_over(&self, today: &Date) -> bool {
+        (*today - db_string_to_date(&self.placed)).whole_days() >= Self::CLAIM_DAYS
+    }
+
+    // Get relevant information for the frontend.
+    fn get_package(&self) -> serde_json::Value {
+        json!({
+            "team": Team::fetch_from_db(&self.team_id).get_contract_package(),
+            "placed": self.placed,
+            "claims": self.claims.len(),
+        })
+    }
+}
+
+impl Player {
+    // Get all players currently on waivers.
+    pub fn get_waived_players() -> Vec<Self> {
+        PLAYERS.lock().unwrap().values().filter_map(|a| match a.waivers.is_some() {
+            true => Some(a.clone()),
+            _ => None
+        }).collect()
+    }
+
+    // Get all players on waivers for the frontend.
+    pub fn get_waiver_wire_package(today: &Date) -> serde_json::Value {
+        Self::get_waived_players().iter().map(|a| a.get_package(today)).collect()
+    }
+
+    // Put the player on waivers.
+    // The player leaves the roster, but the contract stays with the team until the player is claimed or cleared.
+    pub fn place_on_waivers(&mut self, team: &mut Team, today: &Date) {
+        team.roster.retain(|id| *id != self.id);
+        self.end_loan();
+        self.reject_contracts();
+        self.negotiations.clear();
+        self.has_qualifying_offer = false;
+        self.waivers = Some(Waivers::build(team.id, today));
+    }
+
+    // A team puts in a claim for the player.
+    // Return whether the claim was accepted.
+    pub fn receive_waiver_claim(&mut self, team_id: TeamId) -> bool {
+        let waivers = match self.waivers.as_mut() {
+            Some(w) => w,
+            None => return false
+        };
+
+        if waivers.team_id == team_id || waivers.claims.contains(&team_id) { return false; }
+
+        waivers.claims.push(team_id);
+        return true;
+    }
+
+    // Give the player to the claiming team, or let them go if no-one wants them.
+    pub fn check_waivers(&mut self, today: &Date) {
+        match self.waivers.as_ref() {
+            Some(waivers) if waivers.is_over(today) => {},
+            _ => return
+        };
+
+        let waivers = self.waivers.take().unwrap();
+
+        // Worst teams in the standings get the first pick, if they still have room for the player.
+        let mut claims: Vec<Team> = waivers.claims.iter().map(|id| Team::fetch_from_db(id)).collect();
+        claims.retain(|a| a.can_register(self, today));
+        claims.sort_by(|a, b| b.get_waiver_priority().cmp(&a.get_waiver_priority()));
+
+        match claims.first_mut() {
+            Some(team) => {
+                self.person.contract.as_mut().unwrap().team_id = team.id;
+                team.roster.push(self.id);
+                team.save();
+            },
+            None => {
+                // The team that released the player keeps paying the contract in full.
+                let contract = self.person.contract.take().unwrap();
+                let mut team = Team::fetch_from_db(&waivers.team_id);
+                team.add_dead_money(self.id, &contract, 1.0);
+                team.save();
+
+                self.free_agent_status = FreeAgentStatus::Unrestricted;
+            }
+        }
+    }
+
+    // Get relevant information about the player's waivers for the frontend.
+    pub fn get_waivers_package(&self) -> serde_json::Value {
+        match self.waivers.as_ref() {
+            Some(waivers) => waivers.get_package(),
+            None => serde_json::Value::Null
+        }
+    }
+}
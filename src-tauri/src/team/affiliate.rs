@@ -0,0 +1,80 @@
+// Affiliate farm teams, and loaning players to other teams.
+
+use time::Date;
+
+use crate::{person::player::Player, team::Team, types::TeamId};
+
+impl Team {
+    // Make the given team the farm team of this team.
+    pub fn set_farm_team(&mut self, farm_team: &mut Team) {
+        self.farm_team_id = farm_team.id;
+        farm_team.parent_team_id = self.id;
+    }
+
+    // Get the farm team of the team, if any.
+    pub fn get_farm_team(&self) -> Option<Team> {
+        match self.farm_team_id {
+            0 => None,
+            id => Some(Team::fetch_from_db(&id))
+        }
+    }
+
+    // Get the team this team is the farm team of, if any.
+    pub fn get_parent_team(&self) -> Option<Team> {
+        match self.parent_team_id {
+            0 => None,
+            id => Some(Team::fetch_from_db(&id))
+        }
+    }
+
+    // Check if the team is willing to take the player on loan.
+    // Farm teams take whoever their parent team sends them, as long as the rules allow it.
+    pub fn accepts_loan(&self, player: &Player, today: &Date) -> bool {
+        self.can_register(player, today) && (
+            self.parent_team_id == player.person.contract.as_ref().unwrap().team_id ||
+            player.get_expected_role(self).is_some()
+        )
+    }
+
+    // Loan a player of the roster to another team until the end of that team's season.
+    // Return whether the player was loaned.
+    pub fn loan_out_player(&self, player: &mut Player, team_id: TeamId, today: &Date) -> bool {
+        if team_id == self.id || !self.roster.contains(&player.id) || player.is_on_loan() || player.waivers.is_some() {
+            return false;
+        }
+
+        let mut team = Team::fetch_from_db(&team_id);
+        if !team.accepts_loan(player, today) { return false; }
+
+        let end_date = team.get_primary_competition().season_window.get_next_end_date(today);
+        player.start_loan(&mut team, today, &end_date);
+        team.save();
+        player.save();
+
+        return true;
+    }
+
+    // Send a player down to the farm team.
+    // Return whether the player was sent down.
+    pub fn send_down_player(&self, player: &mut Player, today: &Date) -> bool {
+        match self.farm_team_id {
+            0 => false,
+            id => self.loan_out_player(player, id, today)
+        }
+    }
+
+    // Bring a player back from the farm team, if the roster has room for them.
+    // Players loaned to other teams have to see their loans through.
+    // Return whether the player was called up.
+    pub fn call_up_player(&self, player: &mut Player, today: &Date) -> bool {
+        if self.farm_team_id == 0 || !self.roster.contains(&player.id) || !player.is_on_loan_to(self.farm_team_id) {
+            return false;
+        }
+        if !self.can_register(player, today) { return false; }
+
+        player.end_loan();
+        player.save();
+
+        return true;
+    }
+}
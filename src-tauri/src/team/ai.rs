@@ -5,16 +5,23 @@ use std::collections::HashMap;
 use rand::{Rng, rngs::ThreadRng, seq::IndexedRandom};
 use time::Date;
 
-use crate::{person::{Contract, player::{Player, position::PositionId}}, team::Team, types::{PlayerId, convert}};
+use crate::{competition::roster_rules::RosterRules, person::{Contract, ContractRole, player::{Player, position::PositionId}}, team::Team, types::{PlayerId, convert}};
 
 #[derive(Debug)]
 #[derive(Default, Clone)]
 pub struct PlayerNeed {
     pub position: PositionId,
 
-    // Abilities of the players, from highest to lowest.
+    // Abilities of the players in lineup, from highest to lowest.
     pub abilities: Vec<f64>,
 
+    // How many players the team has signed for the position, including those who do not fit in the lineup.
+    // Players who have only been approached are not counted.
+    pub rostered: usize,
+
+    // How many players of the position the team's league dresses for a game.
+    lineup_places: i8,
+
     // Calculated and set in get_urgency
     // f64::MAX: Must have this type of player at all costs.
     // Negative: Will not acquire a player of this type (unless maybe if one is *really* good).
@@ -23,16 +30,17 @@ pub struct PlayerNeed {
 
 impl PlayerNeed {
     // Build the element.
-    fn build(position: PositionId) -> Self {
+    fn build(position: PositionId, rules: &RosterRules) -> Self {
         Self {
+            lineup_places: rules.get_lineup_places(&position),
             position: position,
             ..Default::default()
         }
     }
 
     // Get how many players the team has that have to be left outside a match lineup.
-    fn get_surplus(&self) -> i8 {
-        convert::int::<usize, i8>(self.abilities.len()) - self.get_lineup_places()
+    pub fn get_surplus(&self) -> i8 {
+        convert::int::<usize, i8>(self.rostered) - self.get_lineup_places()
     }
 
     // Get the average ability of the players.
@@ -43,7 +51,7 @@ impl PlayerNeed {
 
     // Get the worst ability of a player in lineup.
     pub fn get_worst(&self) -> f64 {
-        match self.get_surplus() < 0 {
+        match self.abilities.len() < self.get_lineup_places() as usize {
             true => 0.0,
             _ => *self.abilities.last().unwrap()
         }
@@ -73,20 +81,25 @@ impl PlayerNeed {
         return 1.0 - (index / (self.get_lineup_places()) as f64);
     }
 
+    // Check if the team's players, approached ones included, do not fill the lineup.
+    fn has_open_places(&self) -> bool {
+        self.abilities.len() < self.get_lineup_places() as usize
+    }
+
+    // Check if the team has so many players of the position that some should be let go.
+    // Players on loan go back to their own team sooner or later, so they are left out.
+    fn has_too_many(&self, loaned_in: usize) -> bool {
+        self.get_surplus() - convert::int::<usize, i8>(loaned_in) > self.get_lineup_places() / 2
+    }
+
     // Calculate how much a team wants this type of player.
     fn calculate_urgency(&mut self, needs: &[Self]) {
         // If the team does not have enough players to play, something ought to be done about it...
-        if self.get_surplus() < 0 {
+        if self.has_open_places() {
             self.urgency = 10000.0; // Arbitrary
             return;
         }
 
-        // Team will not hire more players if they have double of what they can fit in lineup.
-        else if self.get_surplus() >= self.get_lineup_places() {
-            self.urgency = -10000.0;    // Arbitrary.
-            return;
-        }
-
         let total_position_ability: f64 = needs.iter().map(|a| a.get_avg_ability()).sum();
         let avg_position_ability = total_position_ability / convert::usize_to_f64(needs.len());
 
@@ -105,37 +118,41 @@ impl PlayerNeed {
     }
 
     // Get how many players of this particular position are allowed in lineup.
-    // Does not take into account the possible variable lineup sizes of different competitions.
-    fn get_lineup_places(&self) -> i8 {
-        match self.position {
-            PositionId::Goalkeeper => 2,
-            _ => 4
-        }
+    pub fn get_lineup_places(&self) -> i8 {
+        self.lineup_places
     }
 
     // Evaluate the team's desire to acquire given player.
     // Do not bother if the value is negative.
     fn evaluate_player(&self, player: &Player) -> f64 {
-        let worst = match self.get_surplus() {
-            i8::MIN..0 => 0.0,
-            _ => self.get_worst()
-        };
-
-        (player.ability.get_display() as f64 - worst) * self.urgency * (1.0 / (player.person.contract_offers.len() + 1) as f64)
+        (player.ability.get_display() as f64 - self.get_worst()) * self.urgency * (1.0 / (player.get_open_negotiations_amount() + 1) as f64)
     }
 }
 
 impl Team {
+    // How much over a player's market value the AI is willing to pay.
+    const MAX_OVERPAY: f64 = 1.3;
+
+    // The oldest age at which the AI still offers its players an extension.
+    const MAX_EXTENSION_AGE: i8 = 34;
+
+    // Daily chance of the AI starting extension talks with a player.
+    const EXTENSION_CHANCE: f64 = 1.0 / 30.0;
+
+    // The oldest age at which the AI still sends its players to the farm team to develop.
+    const MAX_LOAN_AGE: i8 = 21;
+
     // Team evaluates what kind of players it might need, and how desperately.
     // Needs rework once player development becomes a thing.
     pub fn evaluate_player_needs(&mut self) {
-        let mut roster_build = self.get_players();
+        let mut roster_build = self.get_available_players();
+        let rostered = get_players_per_position(roster_build.clone());
         roster_build.append(&mut self.get_approached_players());
         roster_build.sort_by(|a, b| b.ability.get_display().cmp(&a.ability.get_display()));
         let players = get_players_per_position(roster_build);
+        let rules = self.get_roster_rules();
 
-
-        self.player_needs = players.iter().map(|(k, v)| evaluate_position_needs(k, v)).collect();
+        self.player_needs = players.iter().map(|(k, v)| evaluate_position_needs(k, v, rostered[k].len(), &rules)).collect();
 
         let needs_clone = self.player_needs.clone();
         for i in 0..self.player_needs.len() {
@@ -149,18 +166,21 @@ impl Team {
     // Offer contract to a player, if the team needs one.
     // Return whether contract was offered or not.
     pub fn offer_contract(&mut self, today: &Date, rng: &mut ThreadRng) -> bool {
-        let mut player = self.select_player_from_shortlist(rng);
+        // Do not go over the budget the board has set.
+        if !self.has_room_in_budget() { return false; }
+
+        let mut player = self.select_player_from_shortlist(today, rng);
         if player.is_none() { return false; }
 
-        let contract = self.create_contract_offer(player.as_ref().unwrap(), today, rng);
-        self.offer_contract_to_player(player.as_mut().unwrap(), contract);
+        let (contract, years) = self.create_contract_offer(player.as_ref().unwrap(), today, rng);
+        self.offer_contract_to_player(player.as_mut().unwrap(), contract, years, today);
         return true;
     }
 
     // Give the team an opportunity to offer a contract to a player.
     // Assumes that self.player_needs is up-to-date!
-    fn select_player_from_shortlist(&self, rng: &mut ThreadRng) -> Option<Player> {
-        let free_agents = self.get_player_shortlist();
+    fn select_player_from_shortlist(&self, today: &Date, rng: &mut ThreadRng) -> Option<Player> {
+        let free_agents = self.get_player_shortlist(today);
 
         // Do not offer any contracts if there is no-one the team wants.
         if free_agents.is_empty() { return None; }
@@ -198,23 +218,266 @@ impl Team {
     }
 
     // Offer contract to a given player.
-    pub fn offer_contract_to_player(&mut self, player: &mut Player, contract: Contract) {
-        player.person.contract_offers.push(contract);
-        self.approached_players.push(player.id);
+    pub fn offer_contract_to_player(&mut self, player: &mut Player, contract: Contract, years: i32, today: &Date) {
+        player.receive_offer(contract, years, today);
+
+        // Own players are already counted in the roster.
+        if !self.roster.contains(&player.id) {
+            self.approached_players.push(player.id);
+        }
         player.save();
 
         self.actions_remaining -= 1;
     }
 
+    // Build a contract offer for a player.
+    // Extensions start counting the years after the current contract ends.
+    pub fn build_offer(&self, player: &Player, today: &Date, years: i32, salary: u32, role: ContractRole) -> Contract {
+        let contract_years = match player.is_under_contract_with(self.id) {
+            true => years + 1,
+            _ => years
+        };
+
+        return Contract::build_with_terms(self, today, contract_years, salary, role);
+    }
+
     // AI makes a contract offer to a player.
-    // The player itself should affect the contract offer at some point.
-    pub fn create_contract_offer(&self, player: &Player, today: &Date, rng: &mut ThreadRng) -> Contract {
+    // Return the contract and its length in years.
+    pub fn create_contract_offer(&self, player: &Player, today: &Date, rng: &mut ThreadRng) -> (Contract, i32) {
         let years = rng.random_range(1..=4);  // 1-4 year contract offers, just like MHM.
-        return Contract::build_from_years(self, today, years);
+        let salary = (player.get_market_value() as f64 * rng.random_range(0.8..=1.0)) as u32;
+        let role = player.get_expected_role(self).unwrap_or_default();
+
+        return (self.build_offer(player, today, years, salary, role), years);
+    }
+
+    // Try to keep the players whose contracts are about to end.
+    pub fn offer_extensions(&mut self, today: &Date, rng: &mut ThreadRng) {
+        let rules = self.get_roster_rules();
+        for mut player in self.get_players() {
+            if self.actions_remaining == 0 { break; }
+            if !player.is_open_to_extension_from(self.id, today) { continue; }
+
+            // Only players who are still worth a spot in the lineup, and young enough for the league next season, are kept.
+            let age = player.person.get_age_years(today);
+            let wanted = player.get_expected_role(self).is_some() && age <= Self::MAX_EXTENSION_AGE && rules.allows_age(age + 1);
+            if !wanted { continue; }
+
+            // Keep the rights for later, in case the talks go nowhere.
+            if player.can_receive_qualifying_offer(self.id, today) {
+                player.has_qualifying_offer = true;
+                player.save();
+            }
+
+            // Talks are spread out over the season.
+            if !rng.random_bool(Self::EXTENSION_CHANCE) { continue; }
+
+            let (contract, years) = self.create_contract_offer(&player, today, rng);
+            self.offer_contract_to_player(&mut player, contract, years, today);
+        }
+    }
+
+    // Decide whether to match the offers other teams have made to the team's restricted free agents.
+    pub fn respond_to_offer_sheets(&mut self, today: &Date) {
+        for mut player in Player::get_offer_sheets_for_team(self.id) {
+            let salary = player.get_offer_sheet().unwrap().offer.salary;
+            let max_salary = (player.get_market_value() as f64 * Self::MAX_OVERPAY) as u32;
+
+            match player.get_expected_role(self).is_some() && salary <= max_salary {
+                true => player.match_offer_sheet(self, today),
+                _ => player.sign_negotiated_contract(player.get_offer_sheet().unwrap().team_id, today)
+            }
+
+            // Signing the player saves the team, so get the changes.
+            let saved = Team::fetch_from_db(&self.id);
+            self.roster = saved.roster;
+            self.approached_players = saved.approached_players;
+            player.save();
+        }
+    }
+
+    // Answer the demands of the players the team is negotiating with.
+    pub fn respond_to_demands(&mut self, today: &Date, rng: &mut ThreadRng) {
+        // Own players may be negotiating an extension.
+        let player_ids: Vec<PlayerId> = self.approached_players.iter().chain(self.roster.iter()).cloned().collect();
+
+        for player_id in player_ids {
+            let mut player = Player::fetch_from_db(&player_id).unwrap();
+            let negotiation = match player.get_negotiation(self.id) {
+                Some(n) if n.awaits_team() => n.clone(),
+                _ => continue
+            };
+
+            let demand = negotiation.demand.unwrap();
+            let max_salary = (player.get_market_value() as f64 * Self::MAX_OVERPAY) as u32;
+            let role = match player.get_expected_role(self) {
+                Some(role) => role,
+                None => ContractRole::Depth
+            };
+
+            // Not worth the trouble, if the team has already offered all it can.
+            if demand.salary > max_salary && negotiation.offer.salary >= max_salary {
+                self.withdraw_offer(&mut player, today);
+                continue;
+            }
+
+            // Meet the player somewhere between the offer and the demand.
+            let gap = demand.salary.saturating_sub(negotiation.offer.salary) as f64;
+            let salary = negotiation.offer.salary + (gap * rng.random_range(0.5..=1.0)) as u32;
+            let role = match demand.role <= role {
+                true => demand.role,
+                _ => role
+            };
+
+            self.counter_offer(&mut player, demand.years, salary.min(max_salary), role, today);
+        }
+    }
+
+    // Make a new offer to a player the team is negotiating with.
+    // Return whether the offer was made.
+    pub fn counter_offer(&self, player: &mut Player, years: i32, salary: u32, role: ContractRole, today: &Date) -> bool {
+        let contract = self.build_offer(player, today, years, salary, role);
+        let received = player.receive_counter_offer(contract, years, today);
+        player.save();
+
+        return received;
+    }
+
+    // Stop negotiating with a player.
+    pub fn withdraw_offer(&mut self, player: &mut Player, today: &Date) {
+        player.break_off_negotiation(self.id, today);
+        self.approached_players.retain(|id| *id != player.id);
+        player.save();
+    }
+
+    // Let go of the worst players in positions where the team has far too many,
+    // or in any position with a surplus when the roster is bigger than the league allows.
+    pub fn cut_surplus_players(&mut self, today: &Date) {
+        let mut has_changes = false;
+
+        let rules = self.get_roster_rules();
+
+        // Players who have grown too old for the league have to go.
+        let too_old: Vec<Player> = self.get_players().into_iter()
+            .filter(|a| a.waivers.is_none() && !a.is_on_loan() && !rules.allows_age(a.person.get_age_years(today)))
+            .collect();
+        for mut player in too_old {
+            if self.actions_remaining == 0 { break; }
+            has_changes |= self.release_player(&mut player, today);
+            self.actions_remaining -= 1;
+        }
+
+        if self.actions_remaining > 0 && self.get_available_players().len() > rules.max_roster_size as usize {
+            let positions: Vec<PositionId> = self.player_needs.iter().filter(|a| a.get_surplus() > 0).map(|a| a.position.clone()).collect();
+            let worst = self.get_players().into_iter()
+                .filter(|a| positions.contains(&a.position_id) && a.waivers.is_none() && !a.is_on_loan())
+                .min_by_key(|a| a.ability.get_display());
+
+            if let Some(mut player) = worst {
+                has_changes |= self.release_player(&mut player, today);
+                self.actions_remaining -= 1;
+            }
+        }
+
+        for need in self.player_needs.clone() {
+            if self.actions_remaining == 0 { break; }
+
+            let loaned_in = self.loaned_players.iter().filter(|id| Player::fetch_from_db(id).unwrap().position_id == need.position).count();
+            if !need.has_too_many(loaned_in) { continue; }
+
+            let worst = self.get_players().into_iter()
+                .filter(|a| a.position_id == need.position && a.waivers.is_none() && !a.is_on_loan())
+                .min_by_key(|a| a.ability.get_display());
+
+            // Players whose contracts are about to end leave soon enough anyway.
+            match worst {
+                Some(player) if player.person.contract.as_ref().unwrap().is_in_final_season(today) => {},
+                Some(mut player) => {
+                    has_changes |= self.release_player(&mut player, today);
+                    self.actions_remaining -= 1;
+                },
+                None => {}
+            }
+        }
+
+        if has_changes {
+            self.evaluate_player_needs();
+        }
+    }
+
+    // Call players up from the farm team when the lineup is short,
+    // and send down young players who would not get to play otherwise.
+    pub fn manage_farm_team(&mut self, today: &Date) {
+        let farm_team = match self.get_farm_team() {
+            Some(team) => team,
+            None => return
+        };
+
+        let mut players = self.get_players();
+        players.sort_by(|a, b| b.ability.get_display().cmp(&a.ability.get_display()));
+        let mut has_changes = false;
+
+        // Players the team has only approached cannot play yet, so they are not counted here.
+        let available = |players: &[Player], position: &PositionId| {
+            players.iter().filter(|a| a.position_id == *position && !a.is_on_loan() && a.waivers.is_none()).count()
+        };
+
+        for need in self.player_needs.clone() {
+            if available(&players, &need.position) >= need.get_lineup_places() as usize { continue; }
+
+            let best = players.iter_mut().find(|a| a.position_id == need.position && a.is_on_loan_to(farm_team.id));
+            if let Some(player) = best {
+                has_changes |= self.call_up_player(player, today);
+            }
+        }
+
+        // Only one player at a time, and only as many as the farm team can give playing time to.
+        let lineup = self.get_best_lineup().get_player_ids();
+        let rules = farm_team.get_roster_rules();
+        let prospect = players.iter().rposition(|a| {
+            let lineup_places = rules.get_lineup_places(&a.position_id) as usize;
+            let sent_down = players.iter().filter(|b| b.position_id == a.position_id && b.is_on_loan_to(farm_team.id)).count();
+
+            !a.is_on_loan() &&
+            a.waivers.is_none() &&
+            !lineup.contains(&a.id) &&
+            available(&players, &a.position_id) > lineup_places &&
+            sent_down < lineup_places / 2 &&
+            a.person.get_age_years(today) <= Self::MAX_LOAN_AGE
+        });
+
+        if let Some(i) = prospect {
+            has_changes |= self.send_down_player(&mut players[i], today);
+        }
+
+        if has_changes {
+            self.evaluate_player_needs();
+        }
+    }
+
+    // Claim the players on waivers who would get a role in the team.
+    // Only one claim per position at a time, as the claims are not yet part of the roster.
+    pub fn claim_waived_players(&mut self, today: &Date) {
+        let waived_players = Player::get_waived_players();
+        let mut positions: Vec<PositionId> = waived_players.iter()
+            .filter(|a| a.waivers.as_ref().unwrap().claims.contains(&self.id))
+            .map(|a| a.position_id.clone())
+            .collect();
+
+        for mut player in waived_players {
+            if self.actions_remaining == 0 || !self.has_room_in_budget() { break; }
+            if positions.contains(&player.position_id) || player.get_expected_role(self).is_none() { continue; }
+
+            if self.claim_waived_player(&mut player, today) {
+                positions.push(player.position_id.clone());
+                self.actions_remaining -= 1;
+            }
+        }
     }
 
     // Get a player shortlist of possible hirelings.
-    fn get_player_shortlist(&self) -> Vec<Player> {
+    // Players the team could not register are left out.
+    fn get_player_shortlist(&self, today: &Date) -> Vec<Player> {
         let mut positions = vec![&self.player_needs[0].position];
         let highest_urgency = self.player_needs[0].urgency;
 
@@ -228,6 +491,7 @@ impl Team {
         }
 
         let mut free_agents = Player::get_free_agents_for_team(positions, self.id);
+        free_agents.retain(|a| self.can_register(a, today));
         free_agents.sort_by(|a, b| b.ability.get_display().cmp(&a.ability.get_display()));
 
         return free_agents;
@@ -252,10 +516,69 @@ fn get_players_per_position(players: Vec<Player>) -> HashMap<PositionId, Vec<Pla
 }
 
 // Evaluate the need for a specific position.
-fn evaluate_position_needs(position: &PositionId, players: &[Player]) -> PlayerNeed {
-    let mut need = PlayerNeed::build(position.clone());
+// Players are the rostered and the approached players, best first. Only the rostered ones count towards surplus.
+fn evaluate_position_needs(position: &PositionId, players: &[Player], rostered: usize, rules: &RosterRules) -> PlayerNeed {
+    let mut need = PlayerNeed::build(position.clone(), rules);
     let players_in_lineup = players.len().clamp(0, need.get_lineup_places() as usize);
     need.abilities = players[0..players_in_lineup].iter().map(|a| a.ability.get_display() as f64).collect();
+    need.rostered = rostered;
 
     return need;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::person::attribute::{AttributeId, PersonAttribute};
+
+    fn build_goalkeepers(values: &[u16]) -> Vec<Player> {
+        values.iter().map(|a| Player {
+            position_id: PositionId::Goalkeeper,
+            ability: PersonAttribute::build(AttributeId::General, *a),
+            ..Default::default()
+        }).collect()
+    }
+
+    #[test]
+    fn approached_players_are_not_surplus() {
+        let rules = RosterRules::build(0, 30, 2, 4, 4);
+        let players = build_goalkeepers(&[4000, 3000, 2000, 1000]);
+
+        // Two signed, two only approached.
+        let need = evaluate_position_needs(&PositionId::Goalkeeper, &players, 2, &rules);
+        assert_eq!(need.abilities.len(), 2);
+        assert_eq!(need.get_surplus(), 0);
+        assert!(!need.has_too_many(0));
+    }
+
+    #[test]
+    fn cuts_only_when_far_over_lineup() {
+        let rules = RosterRules::build(0, 30, 2, 4, 4);
+        let players = build_goalkeepers(&[4000, 3000, 2000, 1000]);
+
+        let need = evaluate_position_needs(&PositionId::Goalkeeper, &players, 4, &rules);
+        assert_eq!(need.get_surplus(), 2);
+        assert!(need.has_too_many(0));
+
+        // A player on loan leaves anyway.
+        assert!(!need.has_too_many(1));
+
+        let need = evaluate_position_needs(&PositionId::Goalkeeper, &players, 3, &rules);
+        assert!(!need.has_too_many(0));
+    }
+
+    #[test]
+    fn urgency_follows_open_lineup_places() {
+        let rules = RosterRules::build(0, 30, 2, 4, 4);
+
+        let mut need = evaluate_position_needs(&PositionId::Goalkeeper, &build_goalkeepers(&[4000]), 1, &rules);
+        need.calculate_urgency(&[need.clone()]);
+        assert_eq!(need.urgency, 10000.0);
+
+        // An approached player fills the lineup for now, and a big roster does not stop the team from improving.
+        let players = build_goalkeepers(&[4000, 3000, 2000, 1000]);
+        let mut need = evaluate_position_needs(&PositionId::Goalkeeper, &players, 4, &rules);
+        need.calculate_urgency(&[need.clone()]);
+        assert!(need.urgency > 0.0 && need.urgency < 10000.0);
+    }
+}
@@ -0,0 +1,439 @@
+// The board of a team sets expectations for the manager, and hires and fires them.
+pub mod objective;
+
+use rand::{Rng, rngs::ThreadRng};
+use serde_json::json;
+use time::Date;
+
+use crate::{
+    competition::{Competition, season::Season}, database::TEAMS, person::{Contract, manager::Manager}, team::Team, time::date_to_db_string, types::{ManagerId, TeamId, convert}
+};
+use self::objective::{Objective, ObjectiveType, generate_objectives};
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Board {
+    // The final position the board expects the team to reach. 0 means there are no expectations yet.
+    pub expected_rank: u8,
+
+    // How much the board trusts the current manager. Between 0 and 100.
+    pub confidence: u8,
+
+    // How many days the team has been without a manager.
+    vacancy_days: u8,
+
+    // Managers who have applied for the job while it is vacant.
+    pub applicants: Vec<ManagerId>,
+
+    // What the board wants from the team this season.
+    pub objectives: Vec<Objective>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            expected_rank: 0,
+            confidence: Self::DEFAULT_CONFIDENCE,
+            vacancy_days: 0,
+            applicants: Vec::new(),
+            objectives: Vec::new(),
+        }
+    }
+}
+
+// Basics.
+impl Board {
+    // The confidence a new manager starts with.
+    const DEFAULT_CONFIDENCE: u8 = 50;
+
+    // The manager is fired if confidence drops below this.
+    const FIRING_THRESHOLD: u8 = 20;
+
+    // The board offers a new contract to a manager whose contract is expiring if confidence is at least this.
+    const RENEWAL_THRESHOLD: u8 = 45;
+
+    // How many days the board waits for applications before hiring.
+    const MIN_VACANCY_DAYS: u8 = 7;
+
+    // How many days a new manager gets before the board starts judging them in the middle of the season.
+    const GRACE_PERIOD_DAYS: i64 = 90;
+
+    // Change the confidence by the given amount.
+    fn change_confidence(&mut self, amount: i16) {
+        self.confidence = (self.confidence as i16 + amount).clamp(0, 100) as u8;
+    }
+
+    // Check if the board has lost its confidence in the manager.
+    fn wants_to_fire(&self) -> bool {
+        self.confidence < Self::FIRING_THRESHOLD
+    }
+
+    // Get how much better (positive) or worse (negative) the team did than expected.
+    fn get_performance(&self, rank: u8) -> i16 {
+        if self.expected_rank == 0 { return 0; }
+        self.expected_rank as i16 - rank as i16
+    }
+
+    // Get relevant information of the board for the human.
+    pub fn get_package(&self) -> serde_json::Value {
+        let objectives: Vec<serde_json::Value> = self.objectives.iter().map(|a| a.get_package()).collect();
+        json!({
+            "confidence": self.confidence,
+            "expected_rank": self.expected_rank,
+            "objectives": objectives,
+        })
+    }
+}
+
+// Functional.
+impl Team {
+    // Daily checks the board does regarding the manager.
+    pub fn board_checker(&mut self, today: &Date, rng: &mut ThreadRng) {
+        if self.is_season_start_date(today) {
+            self.set_board_expectations();
+        }
+        else if self.is_season_end_date(today) {
+            self.do_season_review(today, rng);
+        }
+        else if today.day() == 1 {
+            self.do_monthly_review(today);
+        }
+    }
+
+    // Set the rank the board expects the team to finish in, based on the strength of the rosters in the competition.
+    // Set the objectives for the season accordingly.
+    pub fn set_board_expectations(&mut self) {
+        let own_strength = self.get_lineup_strength();
+        let comp = self.get_primary_competition();
+        let teams = comp.get_teams();
+
+        let mut rank = 1;
+        for team in teams.iter() {
+            if team.id != self.id && team.get_lineup_strength() > own_strength {
+                rank += 1;
+            }
+        }
+
+        self.board.expected_rank = rank;
+        self.board.objectives = generate_objectives(
+            rank,
+            convert::int::<usize, u8>(teams.len()),
+            comp.get_playoff_spots(),
+            self.get_payroll().max(self.get_full_roster_cost())
+        );
+    }
+
+    // Get the team's position in its primary competition, or the current standings of the stage that is being played.
+    pub fn get_current_rank(&self) -> Option<u8> {
+        let comp = self.get_primary_competition();
+        let (is_over, position) = Season::read_from_db(&comp.id, comp.get_seasons_amount() - 1, |a| {
+            (a.is_over, a.teams.iter().position(|b| b.team_id == self.id))
+        });
+
+        if is_over {
+            return position.map(|i| convert::int::<usize, u8>(i + 1));
+        }
+
+        return self.get_stage_rank();
+    }
+
+    // Get the team's position in the first round robin stage of its primary competition that has started.
+    pub fn get_stage_rank(&self) -> Option<u8> {
+        let comp = self.get_primary_competition();
+        for id in comp.child_comp_ids.iter() {
+            let child_comp = Competition::fetch_from_db(id);
+            let (has_started, position) = Season::read_from_db(id, child_comp.get_seasons_amount() - 1, |a| {
+                (a.round_robin.is_some() && !a.played_games.is_empty(), a.teams.iter().position(|b| b.team_id == self.id))
+            });
+
+            if !has_started { continue; }
+            if position.is_some() {
+                return Some(convert::int::<usize, u8>(position.unwrap() + 1));
+            }
+        }
+
+        return None;
+    }
+
+    // The board looks at the standings every month and may lose its patience.
+    fn do_monthly_review(&mut self, today: &Date) {
+        let manager = match self.get_manager() {
+            Some(m) => m,
+            _ => return
+        };
+
+        // Keep track of the objectives even if the manager is new.
+        let mut objectives = self.board.objectives.clone();
+        for objective in objectives.iter_mut() {
+            objective.track(self, today);
+        }
+        self.board.objectives = objectives;
+
+        if manager.person.contract.as_ref().unwrap().get_days_expired(today) < Board::GRACE_PERIOD_DAYS { return; }
+
+        let rank = match self.get_current_rank() {
+            Some(r) => r,
+            _ => return
+        };
+
+        // Only terrible runs are punished during the season, and good ones slowly rebuild trust.
+        let performance = self.board.get_performance(rank);
+        if performance <= -4 {
+            self.board.change_confidence(performance + 2);
+        }
+        else if performance >= 0 {
+            self.board.change_confidence(1);
+        }
+
+        if self.board.wants_to_fire() {
+            self.fire_manager();
+        }
+    }
+
+    // The board evaluates the manager after the season, and decides whether to keep them.
+    fn do_season_review(&mut self, today: &Date, rng: &mut ThreadRng) {
+        let mut manager = match self.get_manager() {
+            Some(m) => m,
+            _ => return
+        };
+
+        let rank = self.get_current_rank().unwrap_or(self.board.expected_rank);
+        let performance = self.board.get_performance(rank);
+
+        self.board.change_confidence(performance * 2);
+        manager.change_reputation(performance * 2);
+
+        // Winning it all is noticed by everyone.
+        if rank == 1 {
+            self.board.change_confidence(10);
+            manager.change_reputation(10);
+        }
+
+        let mut objectives = self.board.objectives.clone();
+        for objective in objectives.iter_mut() {
+            let change = objective.evaluate(self, today);
+            self.board.change_confidence(change);
+        }
+        self.board.objectives = objectives;
+
+        let mut review = SeasonReview::build(self, rank, today);
+
+        let contract = manager.person.contract.as_ref().unwrap();
+        if self.board.wants_to_fire() {
+            review.outcome = ReviewOutcome::Fired;
+        }
+        else if !contract.check_if_expired(today) {
+            review.outcome = ReviewOutcome::Continues;
+        }
+        else if self.board.confidence >= Board::RENEWAL_THRESHOLD {
+            review.outcome = ReviewOutcome::Renewed;
+        }
+        else {
+            review.outcome = ReviewOutcome::NotRenewed;
+        }
+
+        manager.season_reviews.push(review.clone());
+        manager.save();
+
+        match review.outcome {
+            ReviewOutcome::Fired => self.fire_manager(),
+            ReviewOutcome::Renewed => {
+                let years = rng.random_range(1..=3);
+                manager.sign_contract(Contract::build_from_years(self, today, years));
+            },
+            ReviewOutcome::NotRenewed => self.release_manager(&mut manager),
+            ReviewOutcome::Continues => {}
+        };
+    }
+
+    // Fire the manager of the team.
+    pub fn fire_manager(&mut self) {
+        let mut manager = match self.get_manager() {
+            Some(m) => m,
+            _ => return
+        };
+
+        manager.change_reputation(-10);
+        self.release_manager(&mut manager);
+    }
+
+    // Let the manager go and open the vacancy.
+    pub fn release_manager(&mut self, manager: &mut Manager) {
+        manager.leave_team();
+
+        self.manager_id = 0;
+        self.board.confidence = Board::DEFAULT_CONFIDENCE;
+        self.board.vacancy_days = 0;
+        self.board.applicants.clear();
+    }
+
+    // Hire a manager for the team.
+    pub fn hire_manager(&mut self, manager: &mut Manager, today: &Date, rng: &mut ThreadRng) {
+        // A manager can only have one job at a time.
+        if manager.person.contract.is_some() {
+            let mut old_team = manager.get_team().unwrap();
+            old_team.release_manager(manager);
+            old_team.save();
+        }
+
+        let years = rng.random_range(1..=3);
+        manager.sign_contract(Contract::build_from_years(self, today, years));
+
+        self.manager_id = manager.id;
+        self.board.confidence = Board::DEFAULT_CONFIDENCE;
+        self.board.vacancy_days = 0;
+        self.board.applicants.clear();
+    }
+
+    // Get the reputation the board would like its manager to have.
+    // The better the team is expected to do, the more demanding the board is.
+    pub fn get_required_reputation(&self) -> u8 {
+        if self.board.expected_rank == 0 { return 30; }
+
+        let teams = self.get_primary_competition().get_teams().len().max(1);
+        let rank_ratio = (self.board.expected_rank - 1) as f64 / teams as f64;
+        return (70.0 - rank_ratio * 60.0) as u8;
+    }
+
+    // Get information about the vacant job for the job market.
+    pub fn get_vacancy_package(&self, applicant_id: ManagerId) -> serde_json::Value {
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "competition": self.get_primary_competition().name,
+            "expected_rank": self.board.expected_rank,
+            "required_reputation": self.get_required_reputation(),
+            "has_applied": self.board.applicants.contains(&applicant_id),
+        })
+    }
+
+    // Check if the board allows the team to approach more players.
+    // The salaries the team has offered count towards the budget, as the players may still accept them.
+    // The board always lets the team fill its roster to the minimum the league requires.
+    pub fn has_room_in_budget(&self) -> bool {
+        if self.roster.len() < self.get_roster_rules().min_roster_size as usize { return true; }
+
+        for objective in self.board.objectives.iter() {
+            if let ObjectiveType::StayWithinBudget(n) = objective.objective_type {
+                return self.get_payroll_with_offers() < n;
+            }
+        }
+
+        return true;
+    }
+
+    // Let a manager apply for the vacant job.
+    // Return whether the application was accepted for consideration.
+    pub fn receive_application(&mut self, manager_id: ManagerId) -> bool {
+        if self.manager_id != 0 || self.board.applicants.contains(&manager_id) { return false; }
+
+        self.board.applicants.push(manager_id);
+        return true;
+    }
+
+    // Look for a new manager if the team does not have one.
+    pub fn fill_vacancy(&mut self, today: &Date, rng: &mut ThreadRng) {
+        if self.manager_id != 0 { return; }
+
+        // Give some time for everyone to apply.
+        if self.board.vacancy_days < Board::MIN_VACANCY_DAYS {
+            self.board.vacancy_days += 1;
+            return;
+        }
+
+        let required = self.get_required_reputation() as i16;
+        let applicants: Vec<Manager> = self.board.applicants.iter().filter_map(|id| Manager::fetch_from_db(id)).collect();
+        let unemployed = Manager::get_unemployed();
+
+        // Unemployed managers who applied are only listed once, so that applying does not double the chance.
+        // Candidates far below the requirements are only considered if there is no-one else.
+        let others: Vec<Manager> = unemployed.iter().filter(|a| !self.board.applicants.contains(&a.id)).cloned().collect();
+        let mut candidates: Vec<Manager> = applicants.into_iter().chain(others)
+            .filter(|a| a.reputation as i16 + 15 >= required).collect();
+        if candidates.is_empty() { candidates = unemployed; }
+
+        let mut manager = match choose_candidate(candidates, rng) {
+            Some(m) => m,
+            _ => Manager::build_and_save_random(today, rng)
+        };
+
+        self.hire_manager(&mut manager, today, rng);
+    }
+}
+
+// What the board decided to do with the manager after the season.
+#[derive(Debug, serde::Serialize)]
+#[derive(Default, Clone)]
+pub enum ReviewOutcome {
+    #[default]
+    Continues,
+    Renewed,
+    NotRenewed,
+    Fired,
+}
+
+// The board's verdict on a season of a manager.
+#[derive(Debug)]
+#[derive(Default, Clone)]
+pub struct SeasonReview {
+    team_id: TeamId,
+    date: String,
+    expected_rank: u8,
+    final_rank: u8,
+    objectives: Vec<Objective>,
+    confidence: u8,
+    pub outcome: ReviewOutcome,
+}
+
+impl SeasonReview {
+    // Build the review from the current state of the team.
+    fn build(team: &Team, final_rank: u8, today: &Date) -> Self {
+        Self {
+            team_id: team.id,
+            date: date_to_db_string(today),
+            expected_rank: team.board.expected_rank,
+            final_rank: final_rank,
+            objectives: team.board.objectives.clone(),
+            confidence: team.board.confidence,
+            ..Default::default()
+        }
+    }
+
+    // Get relevant information for the frontend.
+    pub fn get_package(&self) -> serde_json::Value {
+        let objectives: Vec<serde_json::Value> = self.objectives.iter().map(|a| a.get_package()).collect();
+        json!({
+            "team": Team::fetch_from_db(&self.team_id).get_contract_package(),
+            "date": self.date,
+            "expected_rank": self.expected_rank,
+            "final_rank": self.final_rank,
+            "objectives": objectives,
+            "confidence": self.confidence,
+            "outcome": self.outcome,
+        })
+    }
+}
+
+// Choose the candidate the board likes the most. Reputation matters, but so does the job interview.
+fn choose_candidate(candidates: Vec<Manager>, rng: &mut ThreadRng) -> Option<Manager> {
+    let mut best: Option<(u8, Manager)> = None;
+    for candidate in candidates.into_iter() {
+        let score = candidate.reputation + rng.random_range(0..=20);
+        if best.is_none() || best.as_ref().unwrap().0 < score {
+            best = Some((score, candidate));
+        }
+    }
+
+    return best.map(|(_, manager)| manager);
+}
+
+// Get all teams that are looking for a manager.
+pub fn get_vacant_teams() -> Vec<Team> {
+    let mut teams: Vec<Team> = TEAMS.lock().unwrap().values().filter_map(|a| match a.manager_id {
+        0 if !a.is_national_team => Some(a.clone()),
+        _ => None
+    }).collect();
+
+    teams.sort_by(|a, b| a.name.cmp(&b.name));
+    return teams;
+}
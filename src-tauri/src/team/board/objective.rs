@@ -0,0 +1,157 @@
+// Objectives the board sets for the team at the start of each season.
+
+use serde_json::json;
+use time::Date;
+
+use crate::team::Team;
+
+#[derive(Debug, serde::Serialize)]
+#[derive(Clone, PartialEq)]
+pub enum ObjectiveType {
+    // Finish high enough in the regular season to qualify for the playoffs.
+    MakePlayoffs,
+
+    // Finish the season in one of the given amount of top positions.
+    FinishTop(u8),
+
+    // Stay out of the given amount of bottom positions.
+    AvoidRelegation(u8),
+
+    // Keep the yearly payroll at most this big, in thousands.
+    StayWithinBudget(u32),
+
+    // Have at least this many junior players in the best lineup.
+    DevelopJuniors(u8),
+}
+
+#[derive(Debug, serde::Serialize)]
+#[derive(Default, Clone, PartialEq)]
+pub enum ObjectiveStatus {
+    #[default]
+    OnTrack,
+    Behind,
+    Achieved,
+    Failed,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[derive(Clone)]
+pub struct Objective {
+    pub objective_type: ObjectiveType,
+
+    // How much the board cares about the objective, between 1 and 3.
+    pub importance: u8,
+    pub status: ObjectiveStatus,
+}
+
+// Basics.
+impl Objective {
+    // The oldest age at which a player still counts as a junior.
+    const JUNIOR_MAX_AGE: i8 = 21;
+
+    pub fn build(objective_type: ObjectiveType, importance: u8) -> Self {
+        Self {
+            objective_type: objective_type,
+            importance: importance,
+            status: ObjectiveStatus::default(),
+        }
+    }
+
+    // Get a description of the objective for the player to read.
+    fn get_description(&self) -> String {
+        match self.objective_type {
+            ObjectiveType::MakePlayoffs => "Make the playoffs".to_string(),
+            ObjectiveType::FinishTop(n) => format!("Finish in the top {n}"),
+            ObjectiveType::AvoidRelegation(n) => format!("Stay out of the bottom {n}"),
+            ObjectiveType::StayWithinBudget(n) => format!("Keep the payroll at {n}k or less"),
+            ObjectiveType::DevelopJuniors(1) => format!("Have a player aged {} or younger in the lineup", Self::JUNIOR_MAX_AGE),
+            ObjectiveType::DevelopJuniors(n) => format!("Have {n} players aged {} or younger in the lineup", Self::JUNIOR_MAX_AGE),
+        }
+    }
+
+    // Get relevant information for the frontend.
+    pub fn get_package(&self) -> serde_json::Value {
+        json!({
+            "description": self.get_description(),
+            "importance": self.importance,
+            "status": self.status,
+        })
+    }
+}
+
+// Functional.
+impl Objective {
+    // Check whether the objective is fulfilled with the current situation of the team.
+    // None if it cannot be told yet, as the team has no rank before its games start.
+    fn is_met(&self, team: &Team, today: &Date) -> Option<bool> {
+        match self.objective_type {
+            ObjectiveType::MakePlayoffs => {
+                let spots = team.get_primary_competition().get_playoff_spots();
+                team.get_stage_rank().map(|rank| rank <= spots)
+            },
+            ObjectiveType::FinishTop(n) => team.get_current_rank().map(|rank| rank <= n),
+            ObjectiveType::AvoidRelegation(n) => {
+                let teams = team.get_primary_competition().get_teams().len() as u8;
+                team.get_current_rank().map(|rank| rank + n <= teams)
+            },
+            ObjectiveType::StayWithinBudget(n) => Some(team.get_payroll() <= n),
+            ObjectiveType::DevelopJuniors(n) => Some(team.get_juniors_in_lineup(today, Self::JUNIOR_MAX_AGE) >= n),
+        }
+    }
+
+    // Update the status of the objective during the season.
+    // Nothing changes while the objective cannot be told yet.
+    pub fn track(&mut self, team: &Team, today: &Date) {
+        self.status = match self.is_met(team, today) {
+            Some(true) => ObjectiveStatus::OnTrack,
+            Some(false) => ObjectiveStatus::Behind,
+            None => return
+        };
+    }
+
+    // Give the final verdict at the end of the season.
+    // An objective that cannot be told by then is failed.
+    // Return the change in the board's confidence.
+    pub fn evaluate(&mut self, team: &Team, today: &Date) -> i16 {
+        if self.is_met(team, today) == Some(true) {
+            self.status = ObjectiveStatus::Achieved;
+            return 3 * self.importance as i16;
+        }
+
+        self.status = ObjectiveStatus::Failed;
+        return -4 * self.importance as i16;
+    }
+}
+
+// Generate the objectives for a season based on how good the board thinks the team is.
+// The budget is based on what the roster costs or is worth, whichever is more.
+pub fn generate_objectives(expected_rank: u8, teams: u8, playoff_spots: u8, roster_cost: u32) -> Vec<Objective> {
+    let mut objectives = Vec::new();
+
+    // Contenders are expected to contend.
+    if expected_rank <= 3 {
+        objectives.push(Objective::build(ObjectiveType::FinishTop(expected_rank + 1), 3));
+    }
+
+    if playoff_spots > 0 && expected_rank <= playoff_spots + 2 {
+        let importance = match expected_rank <= playoff_spots {
+            true => 3,
+            _ => 1
+        };
+        objectives.push(Objective::build(ObjectiveType::MakePlayoffs, importance));
+    }
+
+    // Weak teams just want to survive, and to give their youngsters a chance.
+    if expected_rank + 4 > teams {
+        objectives.push(Objective::build(ObjectiveType::AvoidRelegation(2), 2));
+        objectives.push(Objective::build(ObjectiveType::DevelopJuniors(3), 2));
+    }
+    else {
+        objectives.push(Objective::build(ObjectiveType::DevelopJuniors(1), 1));
+    }
+
+    // Some room to strengthen the team, but not endlessly.
+    objectives.push(Objective::build(ObjectiveType::StayWithinBudget((roster_cost as f64 * 1.15) as u32), 1));
+
+    return objectives;
+}
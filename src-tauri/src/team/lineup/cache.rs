@@ -2,53 +2,118 @@
 
 use rand::rngs::ThreadRng;
 
-use crate::{match_event::event::PlayersOnIce, misc::random_with_weights, person::player::Player, team::lineup::{DefencePair, ForwardLine, LineUp}, types::PlayerId};
+use crate::{match_event::{event::PlayersOnIce, prediction::GameStrength}, misc::random_with_weights, person::player::Player, team::lineup::{DefencePair, ForwardLine, LineUp}, types::PlayerId};
+
+// How often each line plays.
+// 1st line: 40%, 2nd line: 30%, 3rd line: 20%, 4th line: 10%
+const LINE_WEIGHTS: [u8; 4] = [4, 3, 2, 1];
 
 #[derive(Debug)]
 #[derive(Default, Clone)]
 pub struct LineUpCache {
-    goalkeepers: [Option<Player>; 2],
-    defence_pairs: [DefencePairCache; 4],
-    forward_lines: [ForwardLineCache; 4],
-    pub players_on_ice: PlayersOnIceCache,
+    goalkeepers: Vec<Option<Player>>,
+    defence_pairs: Vec<DefencePairCache>,
+    forward_lines: Vec<ForwardLineCache>,
+    lines: Vec<PlayersOnIceCache>,  // The players on ice when each line is playing, built once per game.
+    line_index: usize,              // The line that is currently on ice.
 }
 
 impl LineUpCache {
     pub fn build(lineup: &LineUp) -> Self {
-        let mut cache = Self::default();
-
-        for (i, gk) in lineup.gk_ids.iter().enumerate() {
-            cache.goalkeepers[i] = Player::fetch_from_db(gk);
-        }
-
-        for (i, pair) in lineup.defence_pairs.iter().enumerate() {
-            cache.defence_pairs[i] = DefencePairCache::build(pair);
-        }
-
-        for (i, line) in lineup.forward_lines.iter().enumerate() {
-            cache.forward_lines[i] = ForwardLineCache::build(line);
-        }
+        let mut cache = Self {
+            goalkeepers: lineup.gk_ids.iter().map(Player::fetch_from_db).collect(),
+            defence_pairs: lineup.defence_pairs.iter().map(DefencePairCache::build).collect(),
+            forward_lines: lineup.forward_lines.iter().map(ForwardLineCache::build).collect(),
+            ..Default::default()
+        };
+
+        cache.lines = (0..cache.get_line_weights().len()).map(|a| cache.build_players_on_ice(a)).collect();
+        return cache;
+    }
 
+    // Build the cache straight from players, without the database.
+    #[cfg(test)]
+    pub fn build_for_tests(goalkeeper: Player, defence_pairs: Vec<[Player; 2]>, forward_lines: Vec<[Player; 3]>) -> Self {
+        let mut cache = Self {
+            goalkeepers: vec![Some(goalkeeper)],
+            defence_pairs: defence_pairs.into_iter().map(|[ld, rd]| DefencePairCache { ld: Some(ld), rd: Some(rd) }).collect(),
+            forward_lines: forward_lines.into_iter().map(|[lw, c, rw]| ForwardLineCache { lw: Some(lw), c: Some(c), rw: Some(rw) }).collect(),
+            ..Default::default()
+        };
+
+        cache.lines = (0..cache.get_line_weights().len()).map(|a| cache.build_players_on_ice(a)).collect();
         return cache;
     }
 
     // Determine who should go on ice next.
     pub fn change_players_on_ice(&mut self, rng: &mut ThreadRng) {
-        self.players_on_ice = PlayersOnIceCache::default();
+        // Simple randomness to determine which line is playing.
+        // This should be player-editable in the future.
+        self.line_index = random_with_weights(self.get_line_weights(), None, rng);
+    }
+
+    // Get the players who are currently on ice.
+    pub fn get_players_on_ice(&self) -> &PlayersOnIceCache {
+        &self.lines[self.line_index]
+    }
+
+    // Get the players on ice for every line, in the order of the line weights.
+    pub fn get_lines(&self) -> &[PlayersOnIceCache] {
+        &self.lines
+    }
+
+    // Get the index of the line that is currently on ice.
+    pub fn get_line_index(&self) -> usize {
+        self.line_index
+    }
+
+    // Get the weights of the lines the lineup has.
+    // Leagues that dress fewer lines only use as many weights.
+    pub fn get_line_weights(&self) -> &'static [u8] {
+        let lines = self.defence_pairs.len().max(self.forward_lines.len()).clamp(1, LINE_WEIGHTS.len());
+        return &LINE_WEIGHTS[..lines];
+    }
+
+    // Build the players on ice when the line with the given index is playing.
+    fn build_players_on_ice(&self, index: usize) -> PlayersOnIceCache {
+        let mut players_on_ice = PlayersOnIceCache::default();
 
         // The better goalkeeper is always on ice (for now).
-        self.players_on_ice.gk = self.goalkeepers[0].clone();
+        players_on_ice.gk = self.goalkeepers.first().cloned().flatten();
 
-        // Simple randomness to determine which line is playing.
-        // This should be player-editable in the future.
-        // 1st line: 40%, 2nd line: 30%, 3rd line: 20%, 4th line: 10%
-        let index = random_with_weights(&[4, 3, 2, 1], None, rng);
+        if let Some(pair) = self.defence_pairs.get(index.min(self.defence_pairs.len().saturating_sub(1))) {
+            players_on_ice.ld = pair.ld.clone();
+            players_on_ice.rd = pair.rd.clone();
+        }
+        if let Some(line) = self.forward_lines.get(index.min(self.forward_lines.len().saturating_sub(1))) {
+            players_on_ice.lw = line.lw.clone();
+            players_on_ice.c = line.c.clone();
+            players_on_ice.rw = line.rw.clone();
+        }
 
-        self.players_on_ice.ld = self.defence_pairs[index].ld.clone();
-        self.players_on_ice.rd = self.defence_pairs[index].rd.clone();
-        self.players_on_ice.lw = self.forward_lines[index].lw.clone();
-        self.players_on_ice.c = self.forward_lines[index].c.clone();
-        self.players_on_ice.rw = self.forward_lines[index].rw.clone();
+        return players_on_ice;
+    }
+
+    // Get the abilities that decide how well the lineup is expected to do in a game.
+    // Each line counts as much as it plays.
+    pub fn get_game_strength(&self) -> GameStrength {
+        let mut strength = GameStrength::default();
+        let weights = self.get_line_weights();
+        let total_weight: u8 = weights.iter().sum();
+
+        for (players_on_ice, weight) in self.lines.iter().zip(weights.iter()) {
+            let share = *weight as f64 / total_weight as f64;
+            let skaters_ability = players_on_ice.get_skaters_ability() as f64;
+            let skaters = players_on_ice.get_skaters().len();
+
+            strength.skaters += skaters_ability * share;
+            if skaters != 0 {
+                strength.shooter += skaters_ability / skaters as f64 * share;
+            }
+        }
+
+        strength.goalkeeper = self.goalkeepers.first().cloned().flatten().map_or(0.0, |a| a.ability.get() as f64);
+        return strength;
     }
 
     // Get the average ability of the lineup.
@@ -190,16 +255,8 @@ impl PlayersOnIceCache {
         return player.unwrap().id;
     }
 
-    // Create a vector of the skaters.
-    pub fn create_vector_of_skaters(&self) -> Vec<Player> {
-        let mut vector = Vec::new();
-        if self.ld.is_some() { vector.push(self.ld.as_ref().unwrap().clone()); }
-        if self.rd.is_some() { vector.push(self.rd.as_ref().unwrap().clone()); }
-        if self.lw.is_some() { vector.push(self.lw.as_ref().unwrap().clone()); }
-        if self.c.is_some() { vector.push(self.c.as_ref().unwrap().clone()); }
-        if self.rw.is_some() { vector.push(self.rw.as_ref().unwrap().clone()); }
-        if self.extra_attacker.is_some() { vector.push(self.extra_attacker.as_ref().unwrap().clone()); }
-
-        return vector;
+    // Get the skaters, without cloning them.
+    pub fn get_skaters(&self) -> Vec<&Player> {
+        [&self.ld, &self.rd, &self.lw, &self.c, &self.rw, &self.extra_attacker].into_iter().flatten().collect()
     }
 }
@@ -1,6 +1,7 @@
 pub mod cache;
 
 use crate::{
+    competition::roster_rules::RosterRules,
     types::PlayerId,
     person::player::{
         Player,
@@ -10,17 +11,33 @@ use crate::{
 
 // A line-up of players used in a match.
 #[derive(Debug, serde::Serialize)]
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct LineUp {
-    gk_ids: [PlayerId; 2],
-    pub defence_pairs: [DefencePair; 4],
-    pub forward_lines: [ForwardLine; 4],
+    gk_ids: Vec<PlayerId>,
+    pub defence_pairs: Vec<DefencePair>,
+    pub forward_lines: Vec<ForwardLine>,
+}
+
+// An empty lineup with as many places as leagues have unless their rules say otherwise.
+impl Default for LineUp {
+    fn default() -> Self {
+        Self::build(&RosterRules::default())
+    }
 }
 
 impl LineUp {
+    // Build an empty lineup with as many places as the rules allow.
+    pub fn build(rules: &RosterRules) -> Self {
+        Self {
+            gk_ids: vec![0; rules.goalkeepers as usize],
+            defence_pairs: vec![DefencePair::default(); rules.defence_pairs as usize],
+            forward_lines: vec![ForwardLine::default(); rules.forward_lines as usize],
+        }
+    }
+
     // Make sure the lineup is filled.
     pub fn is_full(&self) -> bool {
-        if self.gk_ids.contains(&0) { return false; }
+        if self.gk_ids.is_empty() || self.gk_ids.contains(&0) { return false; }
 
         for pair in self.defence_pairs.iter() {
             if !pair.is_full() { return false; }
@@ -32,25 +49,54 @@ impl LineUp {
 
         return true;
     }
+
+    // Get the IDs of all players in the lineup.
+    pub fn get_player_ids(&self) -> Vec<PlayerId> {
+        let mut ids: Vec<PlayerId> = self.gk_ids.to_vec();
+        for pair in self.defence_pairs.iter() {
+            ids.push(pair.ld_id);
+            ids.push(pair.rd_id);
+        }
+        for line in self.forward_lines.iter() {
+            ids.push(line.lw_id);
+            ids.push(line.c_id);
+            ids.push(line.rw_id);
+        }
+
+        ids.retain(|id| *id != 0);
+        return ids;
+    }
 }
 
 impl LineUp {
     // Clear the lineup.
     pub fn clear(&mut self) {
-        *self = Self::default();
+        self.gk_ids.fill(0);
+        self.defence_pairs.iter_mut().for_each(|a| a.clear());
+        self.forward_lines.iter_mut().for_each(|a| a.clear());
     }
 }
 
 impl LineUp {
-    // Add players from a roster to the lineup.
-    pub fn auto_add(&mut self, players: Vec<Player>) {
+    // Add players from a roster to the lineup, as long as the rules allow it.
+    pub fn auto_add(&mut self, mut players: Vec<Player>, rules: &RosterRules) {
+        // Goalkeepers go first, so the import limit never leaves the team without one.
+        players.sort_by_key(|a| a.position_id != PositionId::Goalkeeper);
+
+        let mut imports = 0;
         for player in players {
-            self.auto_add_player(player);
+            let is_import = rules.is_import(&player);
+            if is_import && imports >= rules.max_imports.unwrap() { continue; }
+
+            if self.auto_add_player(player) && is_import {
+                imports += 1;
+            }
         }
     }
 
     // Add a player to the lineup.
-    fn auto_add_player(&mut self, player: Player) {
+    // Return whether there was room for the player.
+    fn auto_add_player(&mut self, player: Player) -> bool {
         match player.position_id {
             PositionId::Goalkeeper => self.auto_add_gk(player),
             PositionId::LeftDefender => self.auto_add_ld(player),
@@ -58,68 +104,80 @@ impl LineUp {
             PositionId::LeftWinger => self.auto_add_lw(player),
             PositionId::Centre => self.auto_add_c(player),
             PositionId::RightWinger => self.auto_add_rw(player),
-            _ => return
+            _ => false
         }
     }
 
     // Add a goalkeeper to the lineup.
-    fn auto_add_gk(&mut self, player: Player) {
+    fn auto_add_gk(&mut self, player: Player) -> bool {
         for id in self.gk_ids.iter_mut() {
             if *id == 0 {
                 *id = player.id;
-                return;
+                return true;
             }
         }
+
+        return false;
     }
 
     // Add a left defender to the lineup.
-    fn auto_add_ld(&mut self, player: Player) {
+    fn auto_add_ld(&mut self, player: Player) -> bool {
         for pair in self.defence_pairs.iter_mut() {
             if pair.ld_id == 0 {
                 pair.ld_id = player.id;
-                return;
+                return true;
             }
         }
+
+        return false;
     }
 
     // Add a left defender to the lineup.
-    fn auto_add_rd(&mut self, player: Player) {
+    fn auto_add_rd(&mut self, player: Player) -> bool {
         for pair in self.defence_pairs.iter_mut() {
             if pair.rd_id == 0 {
                 pair.rd_id = player.id;
-                return;
+                return true;
             }
         }
+
+        return false;
     }
 
     // Add a left winger to the lineup.
-    fn auto_add_lw(&mut self, player: Player) {
+    fn auto_add_lw(&mut self, player: Player) -> bool {
         for line in self.forward_lines.iter_mut() {
             if line.lw_id == 0 {
                 line.lw_id = player.id;
-                return;
+                return true;
             }
         }
+
+        return false;
     }
 
     // Add a centre to the lineup.
-    fn auto_add_c(&mut self, player: Player) {
+    fn auto_add_c(&mut self, player: Player) -> bool {
         for line in self.forward_lines.iter_mut() {
             if line.c_id == 0 {
                 line.c_id = player.id;
-                return;
+                return true;
             }
         }
+
+        return false;
     }
 
     // Add a right winger to the lineup.
-    fn auto_add_rw(&mut self, player: Player) {
+    fn auto_add_rw(&mut self, player: Player) -> bool {
         for line in self.forward_lines.iter_mut() {
             if line.rw_id == 0 {
                 line.rw_id = player.id;
-                return;
+                return true;
             }
         }
+
+        return false;
     }
 }
 
@@ -197,4 +255,17 @@ impl ForwardLine {
         self.c_id = 0;
         self.rw_id = 0;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_lineup_has_every_place() {
+        let lineup = LineUp::default();
+        assert_eq!(lineup.gk_ids.len(), 2);
+        assert_eq!(lineup.defence_pairs.len(), 4);
+        assert_eq!(lineup.forward_lines.len(), 4);
+        assert!(!lineup.is_full());
+    }
+}
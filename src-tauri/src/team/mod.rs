@@ -1,14 +1,21 @@
 pub mod lineup;
 pub mod ai;
+pub mod board;
+pub mod payroll;
+pub mod release;
+pub mod affiliate;
+pub mod registration;
+pub mod national;
+pub mod rating;
 
 use rand::{Rng, distr::Uniform, rngs::ThreadRng};
 use serde_json::json;
 use time::Date;
 use crate::{
-    competition::Competition, database::{TEAMS, TODAY}, person::{Contract, Gender, Person, manager::Manager, player::{
+    competition::{Competition, roster_rules::RosterRules}, country::Country, database::{self, IDS, IdKind, TEAMS, TODAY}, match_event::prediction::GameStrength, person::{Contract, ContractRole, Gender, Person, manager::Manager, player::{
         Player, position::PositionId
-    }}, team::ai::PlayerNeed, time::date_to_db_string, types::{
-        AttributeValue, CompetitionId, ManagerId, PlayerId, TeamId
+    }}, team::{ai::PlayerNeed, board::Board, lineup::cache::LineUpCache, payroll::DeadMoney, rating::Rating}, types::{
+        AttributeValue, CompetitionId, CountryId, ManagerId, PlayerId, TeamId, convert
     }
 };
 use self::lineup::LineUp;
@@ -23,12 +30,32 @@ pub struct Team {
     pub lineup: LineUp,
     pub primary_comp_id: CompetitionId,
 
+    // The country the team is based in.
+    pub country_id: CountryId,
+
+    // National teams pick their players from their country for each tournament, and have no contracts of their own.
+    pub is_national_team: bool,
+
     // Player-acquisition related.
     pub approached_players: Vec<PlayerId>,
     pub player_needs: Vec<PlayerNeed>,
 
     // Actions.
-    pub actions_remaining: u8
+    pub actions_remaining: u8,
+
+    // Hiring and firing of managers.
+    pub board: Board,
+
+    // How strong the team has shown to be in its games.
+    pub rating: Rating,
+
+    // Salaries still paid to players who have left.
+    pub dead_money: Vec<DeadMoney>,
+
+    // Affiliates and loans. 0 means no affiliate.
+    pub parent_team_id: TeamId,
+    pub farm_team_id: TeamId,
+    pub loaned_players: Vec<PlayerId>,
 }
 
 // Basics.
@@ -41,24 +68,36 @@ impl Team {
         };
     }
 
-    fn build(name: &str) -> Self {
+    fn build(name: &str, country_id: CountryId) -> Self {
         Self {
             name: name.to_string(),
+            country_id: country_id,
             ..Default::default()
         }
     }
 
     // Create a team and store it in the database. Return a clone of the Team.
-    pub fn build_and_save(name: &str) -> Self {
-        let mut team = Self::build(name);
-        team.create_id(TEAMS.lock().unwrap().len() + 1);
+    pub fn build_and_save(name: &str, country_id: CountryId) -> Self {
+        let mut team = Self::build(name, country_id);
+        team.create_id(IDS.lock().unwrap().next(IdKind::Team));
         team.save();
         return team;
     }
 
     pub fn fetch_from_db(id: &TeamId) -> Self {
-        TEAMS.lock().unwrap().get(id)
-            .expect(&format!("no Team with id {id:#?}")).clone()
+        Self::read_from_db(id, Self::clone)
+    }
+
+    // Look at the team in the database without cloning it.
+    // The teams stay locked meanwhile, so the function must not access them.
+    pub fn read_from_db<R>(id: &TeamId, f: impl FnOnce(&Self) -> R) -> R {
+        database::read_entry(&TEAMS, id, f).unwrap_or_else(|| panic!("no Team with id {id:#?}"))
+    }
+
+    // Change the team in the database in place, without cloning it or saving it afterwards.
+    // The teams stay locked meanwhile, so the function must not access them.
+    pub fn update_in_db<R>(id: &TeamId, f: impl FnOnce(&mut Self) -> R) -> R {
+        database::update_entry(&TEAMS, id, f).unwrap_or_else(|| panic!("no Team with id {id:#?}"))
     }
 
     // Update the Team to database.
@@ -66,6 +105,11 @@ impl Team {
         TEAMS.lock().unwrap().insert(self.id, self.clone());
     }
 
+    // Get how many club teams there are, leaving out the national teams.
+    pub fn get_club_amount() -> usize {
+        TEAMS.lock().unwrap().values().filter(|a| !a.is_national_team).count()
+    }
+
     // Delete the Team from the database.
     pub fn delete_from_db(&self) {
         TEAMS.lock().unwrap().remove(&self.id);
@@ -95,20 +139,52 @@ impl Team {
         }).collect()
     }
 
+    // Get the players who currently play for the team, including those on loan from other teams.
+    fn get_available_players(&self) -> Vec<Player> {
+        let mut players: Vec<Player> = self.get_players().into_iter().filter(|a| !a.is_on_loan()).collect();
+        players.append(&mut self.loaned_players.iter().map(|id| Player::fetch_from_db(id).unwrap()).collect());
+        return players;
+    }
+
+    // Get the players who are allowed to play for the team today.
+    // Players away with their national team cannot play for their club.
+    fn get_eligible_players(&self) -> Vec<Player> {
+        if self.is_national_team { return self.get_squad(); }
+
+        let today = TODAY.lock().unwrap().clone();
+        let rules = self.get_roster_rules();
+        self.get_available_players().into_iter().filter(|a| {
+            a.national_team_id == 0 && a.is_eligible_to_play_for(self.id, &today) && rules.allows_age(a.person.get_age_years(&today))
+        }).collect()
+    }
+
     // Get the players to whom the team has offered contracts.
     fn get_approached_players(&self) -> Vec<Player> {
         self.approached_players.iter().map(|id| Player::fetch_from_db(id).unwrap()).collect()
     }
 
-    fn get_primary_competition(&self) -> Competition {
+    // Get the country the team is based in.
+    pub fn get_country(&self) -> Country {
+        Country::fetch_from_db(&self.country_id)
+    }
+
+    pub fn get_primary_competition(&self) -> Competition {
         Competition::fetch_from_db(&self.primary_comp_id)
     }
 
+    // Get the roster rules of the league the team plays in.
+    pub fn get_roster_rules(&self) -> RosterRules {
+        self.get_primary_competition().roster_rules
+    }
+
     // Get info for a team screen in JSON.
     pub fn get_team_screen_package(&self, today: &Date) -> serde_json::Value {
+        if self.is_national_team { return self.get_national_team_screen_package(today); }
+
         let mut players = self.get_players();
         let mut approached_players = self.get_approached_players();
         players.append(&mut approached_players);
+        players.append(&mut self.loaned_players.iter().map(|id| Player::fetch_from_db(id).unwrap()).collect());
 
         players.sort_by(|a, b| (a.position_id.clone() as u8).cmp(&(b.position_id.clone() as u8)).then(b.ability.get_display().cmp(&a.ability.get_display())));
 
@@ -116,10 +192,18 @@ impl Team {
         json!({
             "id": self.id,
             "name": self.name,
+            "country": self.get_country().name,
+            "is_national_team": false,
             "manager": match self.get_manager() {
                 Some(manager) => Some(manager.get_team_screen_json()),
                 _ => None
             },
+            "expected_rank": self.board.expected_rank,
+            "rating": self.rating.get_package(),
+            "payroll": self.get_payroll_package(),
+            "registration": self.get_registration_package(today),
+            "parent_team": self.get_parent_team().map(|a| a.get_contract_package()),
+            "farm_team": self.get_farm_team().map(|a| a.get_contract_package()),
             "players": json_players
         })
     }
@@ -137,8 +221,11 @@ impl Team {
         json!({
             "id": self.id,
             "actions_remaining": self.actions_remaining,
+            "board": self.board.get_package(),
             "roster_overview": self.get_roster_overview_package(),
             "approached_players": self.approached_players,
+            "offer_sheets": Player::get_offer_sheets_for_team(self.id).iter().map(|a| a.id).collect::<Vec<PlayerId>>(),
+            "farm_team_id": self.farm_team_id,
         })
     }
 
@@ -162,13 +249,45 @@ impl Team {
 impl Team {
     // Build a lineup for the team from its roster.
     pub fn auto_build_lineup(&mut self) {
-        self.lineup.clear();
+        self.lineup = self.get_best_lineup();
+        self.save();
+    }
 
-        let mut players = self.get_players();
+    // Get the lineup with the best players of the roster, without touching the team's own lineup.
+    fn get_best_lineup(&self) -> LineUp {
+        let rules = self.get_roster_rules();
+        let mut lineup = LineUp::build(&rules);
+
+        let mut players = self.get_eligible_players();
         players.sort_by(|a, b| b.ability.get_display().cmp(&a.ability.get_display()));
 
-        self.lineup.auto_add(players);
-        self.save();
+        // Farm teams are there to give the prospects of their parent team playing time.
+        if self.parent_team_id != 0 {
+            players.sort_by_key(|a| !(a.is_on_loan_to(self.id) && a.is_under_contract_with(self.parent_team_id)));
+        }
+
+        lineup.auto_add(players, &rules);
+        return lineup;
+    }
+
+    // Get how many players of at most the given age there are in the best possible lineup.
+    pub fn get_juniors_in_lineup(&self, today: &Date, max_age: i8) -> u8 {
+        let lineup = self.get_best_lineup();
+        let juniors = lineup.get_player_ids().iter().filter(|id| {
+            Player::fetch_from_db(id).unwrap().person.get_age_years(today) <= max_age
+        }).count();
+
+        return convert::int::<usize, u8>(juniors);
+    }
+
+    // Get the average ability of the best possible lineup of the team.
+    pub fn get_lineup_strength(&self) -> f64 {
+        LineUpCache::build(&self.get_best_lineup()).get_average_ability()
+    }
+
+    // Get the abilities of the best possible lineup of the team that decide how it is expected to do in games.
+    pub fn get_game_strength(&self) -> GameStrength {
+        LineUpCache::build(&self.get_best_lineup()).get_game_strength()
     }
 
     // Give the team its full actions back.
@@ -177,6 +296,11 @@ impl Team {
         self.actions_remaining = 1;
     }
 
+    // Return whether this day is the season start date.
+    fn is_season_start_date(&self, today: &Date) -> bool {
+        return self.get_primary_competition().season_window.is_first_day(today);
+    }
+
     // Return whether this day is the season end date.
     fn is_season_end_date(&self, today: &Date) -> bool {
         return self.get_primary_competition().season_window.is_last_day(today);
@@ -196,9 +320,7 @@ impl Team {
     // Create a manager out of thin air.
     fn create_manager(&mut self, today: &Date, rng: &mut ThreadRng) {
         let mut manager = Manager::build_and_save_random(today, rng);
-        self.manager_id = manager.id;
-        manager.person.contract = Some(Contract::build(&date_to_db_string(today), &date_to_db_string(&Date::MAX), self.id));
-        manager.save();
+        self.hire_manager(&mut manager, today, rng);
     }
 
     // Set up the team when initialising a game.
@@ -209,11 +331,18 @@ impl Team {
         self.save();
     }
 
-    // Give a few junior players to the team at the end of the season.
+    // Give a few junior players from the team's own country to the team at the end of the season.
     fn promote_junior_players(&mut self, today: &Date, rng: &mut ThreadRng) {
         for _ in 0..rng.random_range(1..=3) {
-            let mut player = Player::create(today, rng, 16, 19);
-            let contract = Contract::build_from_years(self, today, 4);
+            let mut player = Player::create_with_country(today, rng, 16, 19, self.country_id);
+
+            // Juniors the team has no room for look for a team of their own.
+            if !self.can_register(&player, today) {
+                player.save();
+                continue;
+            }
+
+            let contract = Contract::build_with_terms(self, today, 4, player.get_market_value(), ContractRole::Depth);
             player.person.contract = Some(contract);
             self.roster.push(player.id);
             player.save();
@@ -0,0 +1,118 @@
+// National teams, picked from the players of their country for international tournaments.
+
+use serde_json::json;
+use time::Date;
+
+use crate::{
+    competition::season::Season, country::Country, database::{IDS, IdKind, PLAYERS}, person::player::{Player, position::PositionId}, team::Team
+};
+
+impl Team {
+    // How many spare goalkeepers a national team takes along on top of the ones dressed for a game.
+    const SPARE_GOALKEEPERS: usize = 1;
+
+    // Build a national team for the country and store it in the database. Return a clone of the Team.
+    pub fn build_and_save_national(country: &Country) -> Self {
+        let mut team = Self::build(&country.name, country.id);
+        team.is_national_team = true;
+        team.create_id(IDS.lock().unwrap().next(IdKind::Team));
+        team.save();
+        return team;
+    }
+
+    // Get the players currently called up to the national team.
+    pub fn get_squad(&self) -> Vec<Player> {
+        self.roster.iter().filter_map(Player::fetch_from_db).collect()
+    }
+
+    // Gather the squad when the tournament begins, and send the players back to their clubs once it is over.
+    pub fn national_team_checker(&mut self, today: &Date) {
+        let comp = self.get_primary_competition();
+        if comp.season_window.is_first_day(today) {
+            self.select_squad(today);
+        }
+        else if !self.roster.is_empty() && comp.is_current_season_over() {
+            self.release_squad();
+        }
+    }
+
+    // Call up the best players of the country.
+    // Clubs that are still playing in any competition keep their players, unless the lineup cannot be filled otherwise.
+    fn select_squad(&mut self, today: &Date) {
+        self.release_squad();
+
+        let rules = self.get_roster_rules();
+        let busy_teams = Season::get_teams_still_playing(today);
+        let mut players: Vec<Player> = PLAYERS.lock().unwrap().values().filter(|a| {
+            a.person.get_country_id() == self.country_id && a.national_team_id == 0
+        }).cloned().collect();
+        players.sort_by(|a, b| b.ability.get_display().cmp(&a.ability.get_display()));
+        let (busy_players, candidates): (Vec<Player>, Vec<Player>) = players.into_iter().partition(|a| busy_teams.contains(&a.get_club_id()));
+
+        // The lineup is filled first, and the rest of the squad is made up of the best players left.
+        let mut squad: Vec<Player> = Vec::new();
+        for position in PositionId::get_all() {
+            let lineup_places = rules.get_lineup_places(&position).max(0) as usize;
+            let places = match position {
+                PositionId::Goalkeeper => lineup_places + Self::SPARE_GOALKEEPERS,
+                _ => lineup_places
+            };
+
+            let mut players: Vec<Player> = candidates.iter().filter(|a| a.position_id == position).take(places).cloned().collect();
+
+            // The team must be able to play, so as a last resort the clubs still playing have to give up their players.
+            // A country without enough players for the position dresses a short lineup.
+            if players.len() < lineup_places {
+                let missing = lineup_places - players.len();
+                players.extend(busy_players.iter().filter(|a| a.position_id == position).take(missing).cloned());
+            }
+
+            squad.append(&mut players);
+        }
+
+        for player in candidates.into_iter() {
+            if squad.len() >= rules.max_roster_size as usize { break; }
+            if player.position_id == PositionId::Goalkeeper || squad.iter().any(|a| a.id == player.id) { continue; }
+            squad.push(player);
+        }
+
+        for mut player in squad {
+            player.national_team_id = self.id;
+            player.save();
+            self.roster.push(player.id);
+        }
+    }
+
+    // Let the players of the squad go back to their clubs.
+    fn release_squad(&mut self) {
+        for mut player in self.get_squad() {
+            player.national_team_id = 0;
+            player.save();
+        }
+
+        self.roster.clear();
+        self.lineup.clear();
+    }
+
+    // Get info for a national team screen in JSON.
+    pub fn get_national_team_screen_package(&self, today: &Date) -> serde_json::Value {
+        let mut players = self.get_squad();
+        players.sort_by(|a, b| (a.position_id.clone() as u8).cmp(&(b.position_id.clone() as u8)).then(b.ability.get_display().cmp(&a.ability.get_display())));
+
+        let json_players: Vec<serde_json::Value> = players.iter().map(|a| a.get_package(today)).collect();
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "country": self.get_country().name,
+            "is_national_team": true,
+            "manager": serde_json::Value::Null,
+            "expected_rank": 0,
+            "rating": self.rating.get_package(),
+            "payroll": serde_json::Value::Null,
+            "registration": serde_json::Value::Null,
+            "parent_team": serde_json::Value::Null,
+            "farm_team": serde_json::Value::Null,
+            "players": json_players
+        })
+    }
+}
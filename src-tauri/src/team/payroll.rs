@@ -0,0 +1,103 @@
+// Salaries the team pays, including to players who are no longer with the team.
+
+use serde_json::json;
+use time::Date;
+
+use crate::{person::{Contract, player::{Player, retirement::RetiredPlayer}}, team::Team, time::{date_to_db_string, db_string_to_date}, types::PlayerId};
+
+// Salary the team still has to pay to a player it has let go.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct DeadMoney {
+    pub player_id: PlayerId,
+
+    // Yearly amount in thousands.
+    pub salary: u32,
+    end_date: String,
+}
+
+impl DeadMoney {
+    fn build(player_id: PlayerId, salary: u32, end_date: &Date) -> Self {
+        Self {
+            player_id: player_id,
+            salary: salary,
+            end_date: date_to_db_string(end_date),
+        }
+    }
+
+    // Get relevant information for the frontend.
+    fn get_package(&self) -> serde_json::Value {
+        // The player may have retired since.
+        let (name, is_retired) = match Player::fetch_from_db(&self.player_id) {
+            Some(player) => (player.person.get_full_name(), false),
+            _ => match RetiredPlayer::fetch_from_db(&self.player_id) {
+                Some(player) => (player.person.get_full_name(), true),
+                _ => (String::new(), true)
+            }
+        };
+
+        json!({
+            "player_id": self.player_id,
+            "name": name,
+            "is_retired": is_retired,
+            "salary": self.salary,
+            "end_date": self.end_date,
+        })
+    }
+}
+
+impl Team {
+    // Share of the remaining salary paid when buying out a contract.
+    const BUYOUT_SHARE: f64 = 2.0 / 3.0;
+
+    // Get the total yearly salaries the team pays.
+    pub fn get_payroll(&self) -> u32 {
+        let salaries: u32 = self.get_players().iter().map(|a| a.person.contract.as_ref().unwrap().salary).sum();
+        let dead_money: u32 = self.dead_money.iter().map(|a| a.salary).sum();
+
+        return salaries + dead_money;
+    }
+
+    // Get the payroll together with the salaries offered to the players the team is negotiating with.
+    pub fn get_payroll_with_offers(&self) -> u32 {
+        let offers: u32 = self.approached_players.iter()
+            .filter_map(|id| Player::fetch_from_db(id)?.get_negotiation(self.id).filter(|a| a.is_open()).map(|a| a.offer.salary))
+            .sum();
+
+        return self.get_payroll() + offers;
+    }
+
+    // Get what a full roster of players of average value would cost in a year.
+    // The budget is based on this, so that teams can fill their rosters even when they have few players.
+    pub fn get_full_roster_cost(&self) -> u32 {
+        self.get_roster_rules().max_roster_size as u32 * Player::get_average_market_value()
+    }
+
+    // Keep paying the contract of a player who is no longer with the team.
+    // The share is between 0.0 and 1.0.
+    pub fn add_dead_money(&mut self, player_id: PlayerId, contract: &Contract, share: f64) {
+        let salary = (contract.salary as f64 * share) as u32;
+        if salary == 0 { return; }
+
+        self.dead_money.push(DeadMoney::build(player_id, salary, &contract.get_end_date()));
+    }
+
+    // Add the cost of buying out a contract.
+    pub fn add_buyout(&mut self, player_id: PlayerId, contract: &Contract) {
+        self.add_dead_money(player_id, contract, Self::BUYOUT_SHARE);
+    }
+
+    // Stop paying the contracts that have ended.
+    pub fn clear_expired_dead_money(&mut self, today: &Date) {
+        self.dead_money.retain(|a| db_string_to_date(&a.end_date) > *today);
+    }
+
+    // Get relevant information about the payroll for the frontend.
+    pub fn get_payroll_package(&self) -> serde_json::Value {
+        let dead_money: Vec<serde_json::Value> = self.dead_money.iter().map(|a| a.get_package()).collect();
+        json!({
+            "total": self.get_payroll(),
+            "dead_money": dead_money,
+        })
+    }
+}
@@ -0,0 +1,109 @@
+// Elo-style ratings of how strong the teams have shown to be in their games.
+
+use serde_json::json;
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Rating {
+    pub value: f64,
+
+    // The rating after each day the team has played, earliest first.
+    history: Vec<(String, f64)>,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            value: Self::DEFAULT_VALUE,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Rating {
+    const DEFAULT_VALUE: f64 = 1500.0;
+
+    // How much a single game can change the rating.
+    const K_FACTOR: f64 = 20.0;
+
+    // Get the score the team is expected to get against the opponent, between 0 and 1.
+    // The advantage is in rating points, such as from playing at home, and negative for the team playing away.
+    pub fn get_expected_score(&self, opponent: &Self, advantage: f64) -> f64 {
+        return 1.0 / (1.0 + 10_f64.powf((opponent.value - self.value - advantage) / 400.0));
+    }
+
+    // Update the rating with the score the team got in a game, and record the new rating for the date.
+    pub fn update(&mut self, expected_score: f64, score: f64, date: &str) {
+        self.value += Self::K_FACTOR * (score - expected_score);
+
+        match self.history.last_mut() {
+            Some((last_date, value)) if last_date == date => *value = self.value,
+            _ => self.history.push((date.to_string(), self.value))
+        };
+    }
+
+    // Get the rating and its history for a team screen.
+    pub fn get_package(&self) -> serde_json::Value {
+        let history: Vec<serde_json::Value> = self.history.iter().map(|(date, value)| json!({
+            "date": date,
+            "value": value.round()
+        })).collect();
+
+        json!({
+            "value": self.value.round(),
+            "history": history
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::match_event::Rules;
+
+    const TOLERANCE: f64 = 1e-9;
+
+    #[test]
+    fn expected_scores_add_up_to_one() {
+        let home = Rating { value: 1600.0, ..Default::default() };
+        let away = Rating::default();
+
+        let home_score = home.get_expected_score(&away, 20.0);
+        let away_score = away.get_expected_score(&home, -20.0);
+        assert!((home_score + away_score - 1.0).abs() < TOLERANCE);
+
+        // 120 points, home advantage included, is about a two-thirds chance.
+        assert!((home_score - 1.0 / (1.0 + 10_f64.powf(-0.3))).abs() < TOLERANCE);
+        assert!((Rating::default().get_expected_score(&Rating::default(), 0.0) - 0.5).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn home_advantage_follows_rules() {
+        let rating = Rating::default();
+
+        // No advantage in the rules is no advantage in the ratings either.
+        let advantage = Rules::build(3, 1200, 300, false, 0.0).get_home_rating_advantage();
+        assert!(advantage.abs() < TOLERANCE);
+
+        // Getting the puck 55% of the time is worth a 55% chance.
+        let advantage = Rules::build(3, 1200, 300, false, 0.05).get_home_rating_advantage();
+        assert!((rating.get_expected_score(&rating, advantage) - 0.55).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn update_moves_rating_by_surprise() {
+        let mut rating = Rating::default();
+        rating.update(0.5, 1.0, "2025-10-01");
+        assert!((rating.value - (Rating::DEFAULT_VALUE + Rating::K_FACTOR * 0.5)).abs() < TOLERANCE);
+
+        // A second game on the same day replaces the day's entry.
+        rating.update(0.75, 0.0, "2025-10-01");
+        assert!((rating.value - (Rating::DEFAULT_VALUE - Rating::K_FACTOR * 0.25)).abs() < TOLERANCE);
+        assert_eq!(rating.history.len(), 1);
+        assert!((rating.history[0].1 - rating.value).abs() < TOLERANCE);
+
+        rating.update(0.5, 0.5, "2025-10-02");
+        assert_eq!(rating.history.len(), 2);
+    }
+}
@@ -0,0 +1,98 @@
+// Registering players to the team within the roster rules of its league.
+
+use serde_json::json;
+use time::Date;
+
+use crate::{person::player::Player, team::Team, types::{PlayerId, convert}};
+
+// Ways in which a team can break the roster rules of its league.
+#[derive(Debug)]
+#[derive(Clone, PartialEq)]
+pub enum RosterViolation {
+    TooFewPlayers(u8),
+    TooManyPlayers(u8),
+    TooManyImports(u8),
+    OverAge(PlayerId),
+}
+
+impl RosterViolation {
+    // Get relevant information for the frontend.
+    fn get_package(&self) -> serde_json::Value {
+        match self {
+            Self::TooFewPlayers(min) => json!({ "kind": "TooFewPlayers", "limit": min }),
+            Self::TooManyPlayers(max) => json!({ "kind": "TooManyPlayers", "limit": max }),
+            Self::TooManyImports(max) => json!({ "kind": "TooManyImports", "limit": max }),
+            Self::OverAge(id) => {
+                let player = Player::fetch_from_db(id).unwrap();
+                json!({ "kind": "OverAge", "player_id": id, "name": player.person.get_full_name() })
+            }
+        }
+    }
+}
+
+impl Team {
+    // Get the players registered to play for the team, including those the team has approached.
+    // The player being registered is left out, so they are not counted twice.
+    fn get_registration_candidates(&self, player_id: PlayerId) -> Vec<Player> {
+        let mut players = self.get_available_players();
+        players.append(&mut self.get_approached_players());
+        players.retain(|a| a.id != player_id);
+
+        return players;
+    }
+
+    // Check if the team has room for the player in its roster under the rules of its league.
+    pub fn can_register(&self, player: &Player, today: &Date) -> bool {
+        let rules = self.get_roster_rules();
+        let players = self.get_registration_candidates(player.id);
+
+        if players.len() >= rules.max_roster_size as usize { return false; }
+        if !rules.allows_age(player.person.get_age_years(today)) { return false; }
+
+        if rules.is_import(player) {
+            let imports = players.iter().filter(|a| rules.is_import(a)).count();
+            if imports >= rules.max_imports.unwrap() as usize { return false; }
+        }
+
+        return true;
+    }
+
+    // Get the ways in which the roster currently breaks the rules of the league.
+    pub fn get_roster_violations(&self, today: &Date) -> Vec<RosterViolation> {
+        let rules = self.get_roster_rules();
+        let players = self.get_available_players();
+        let mut violations = Vec::new();
+
+        let size = convert::int::<usize, u8>(players.len());
+        if size < rules.min_roster_size {
+            violations.push(RosterViolation::TooFewPlayers(rules.min_roster_size));
+        }
+        else if size > rules.max_roster_size {
+            violations.push(RosterViolation::TooManyPlayers(rules.max_roster_size));
+        }
+
+        if let Some(max_imports) = rules.max_imports {
+            let imports = players.iter().filter(|a| rules.is_import(a)).count();
+            if imports > max_imports as usize {
+                violations.push(RosterViolation::TooManyImports(max_imports));
+            }
+        }
+
+        for player in players.iter() {
+            if !rules.allows_age(player.person.get_age_years(today)) {
+                violations.push(RosterViolation::OverAge(player.id));
+            }
+        }
+
+        return violations;
+    }
+
+    // Get relevant information about the roster rules and how the team follows them.
+    pub fn get_registration_package(&self, today: &Date) -> serde_json::Value {
+        let violations: Vec<serde_json::Value> = self.get_roster_violations(today).iter().map(|a| a.get_package()).collect();
+        json!({
+            "rules": self.get_roster_rules().get_package(),
+            "violations": violations,
+        })
+    }
+}
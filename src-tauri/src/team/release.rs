@@ -0,0 +1,56 @@
+// Letting players go: releases, buyouts and claiming players others have released.
+
+use time::Date;
+
+use crate::{person::player::{Player, free_agency::FreeAgentStatus}, team::Team};
+
+impl Team {
+    // Put a player of the roster on waivers.
+    // Return whether the player was released.
+    pub fn release_player(&mut self, player: &mut Player, today: &Date) -> bool {
+        if !self.roster.contains(&player.id) { return false; }
+
+        player.place_on_waivers(self, today);
+        player.save();
+
+        return true;
+    }
+
+    // Pay a player to leave the team right away.
+    // Return whether the player was bought out.
+    pub fn buy_out_player(&mut self, player: &mut Player) -> bool {
+        if !self.roster.contains(&player.id) { return false; }
+
+        self.roster.retain(|id| *id != player.id);
+        let contract = player.person.contract.take().unwrap();
+        self.add_buyout(player.id, &contract);
+        player.end_loan();
+
+        player.reject_contracts();
+        player.negotiations.clear();
+        player.has_qualifying_offer = false;
+        player.free_agent_status = FreeAgentStatus::Unrestricted;
+        player.save();
+
+        return true;
+    }
+
+    // Claim a player from waivers.
+    // Return whether the claim was made.
+    pub fn claim_waived_player(&self, player: &mut Player, today: &Date) -> bool {
+        if !self.can_register(player, today) { return false; }
+
+        let claimed = player.receive_waiver_claim(self.id);
+        player.save();
+
+        return claimed;
+    }
+
+    // Get the team's place in the waiver order. The bigger the number, the earlier the team gets to pick.
+    pub fn get_waiver_priority(&self) -> u8 {
+        match self.get_current_rank() {
+            Some(rank) => rank,
+            _ => self.board.expected_rank
+        }
+    }
+}
@@ -1,4 +1,3 @@
-
 // Functions to help with testing.
 
 use time::Date;
@@ -11,6 +10,92 @@ pub fn simulate_to_day(date: &str, today: &Date) {
             break;
         }
 
-        go_to_next_day();
+        go_to_next_day(None);
+    }
+}
+
+#[cfg(test)]
+mod simulations {
+    use super::*;
+    use crate::{database::{self, TEAMS, TODAY}, team::Team};
+
+    // Simulate the pre-season of the default world, and fail if a club does not have the players its league requires when the season starts.
+    // Slow, so only run on demand: cargo test --release rosters_are_full_at_season_start -- --ignored
+    #[test]
+    #[ignore]
+    fn rosters_are_full_at_season_start() {
+        database::initialise();
+
+        let season_start = db_string_to_date("2025-09-01");
+        while *TODAY.lock().unwrap() < season_start {
+            go_to_next_day(None);
+        }
+
+        let teams: Vec<Team> = TEAMS.lock().unwrap().values().filter(|a| !a.is_national_team).cloned().collect();
+        for team in teams {
+            let min_roster_size = team.get_roster_rules().min_roster_size as usize;
+            assert!(team.roster.len() >= min_roster_size, "{} has {} players, {min_roster_size} required", team.name, team.roster.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod benchmarks {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::database::{self, PLAYERS, SEASONS, TEAMS, TODAY};
+
+    // Get how many games have been played in the latest seasons of every competition.
+    fn get_played_games() -> usize {
+        SEASONS.lock().unwrap().values().filter_map(|a| a.last()).map(|a| a.played_games.len()).sum()
+    }
+
+    // Simulate the first weeks of the season in a world with extra leagues, and fail if a day with games takes too long on average.
+    // The days alternate between full and quick games, and the days without games are not counted.
+    // Prints how long the days took, along with the size of the world.
+    // Slow, so only run on demand: cargo test --release benchmark_days -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn benchmark_days() {
+        const EXTRA_LEAGUES: usize = 10;
+        const DAYS: u32 = 30;
+        const MAX_AVERAGE_SECONDS: f64 = 0.5;
+
+        database::initialise_with_extra_leagues(EXTRA_LEAGUES);
+
+        // The pre-season is not timed.
+        let season_start = db_string_to_date("2025-09-01");
+        while *TODAY.lock().unwrap() < season_start {
+            go_to_next_day(None);
+        }
+
+        let mut full_seconds = Vec::new();
+        let mut quick_seconds = Vec::new();
+        for day in 0..DAYS {
+            let is_quick = day % 2 == 1;
+            let games = get_played_games();
+
+            let start = Instant::now();
+            go_to_next_day(Some(is_quick));
+            let elapsed = start.elapsed().as_secs_f64();
+
+            if get_played_games() == games { continue; }
+            match is_quick {
+                true => quick_seconds.push(elapsed),
+                _ => full_seconds.push(elapsed)
+            };
+        }
+
+        println!("{} teams and {} players", TEAMS.lock().unwrap().len(), PLAYERS.lock().unwrap().len());
+        for (mode, seconds) in [("full", &full_seconds), ("quick", &quick_seconds)] {
+            assert!(!seconds.is_empty(), "no {mode} game days");
+
+            let average = seconds.iter().sum::<f64>() / seconds.len() as f64;
+            let slowest = seconds.iter().cloned().fold(0.0, f64::max);
+            println!("{} {mode} game days: {average:.3} s on average, {slowest:.3} s at the slowest", seconds.len());
+
+            assert!(average < MAX_AVERAGE_SECONDS, "a {mode} game day took {average:.3} s on average");
+        }
     }
-}
\ No newline at end of file
+}
@@ -29,12 +29,13 @@ impl AnnualWindow {
 
 impl AnnualWindow {
     // Check if the current date is between the start and the end date.
-    fn is_active(&self, today: &Date) -> bool {
+    pub fn is_active(&self, today: &Date) -> bool {
+        self.is_first_day(today) ||
         self.get_next_start_date(today) > self.get_next_end_date(today)
     }
 
     // Check if the current date is the first day of the window.
-    fn is_first_day(&self, today: &Date) -> bool {
+    pub fn is_first_day(&self, today: &Date) -> bool {
         self.start.day == today.day() &&
         self.start.month == today.month() as u8
     }
@@ -5,12 +5,22 @@ use std::collections::HashMap;
 use crate::{country::NamePool, person::Gender};
 
 // Database ID types.
-pub type CountryId = u8;
-pub type CompetitionId = u8;
+pub type CountryId = u16;
+pub type CompetitionId = u16;
 
-pub type TeamId = u8;
-pub type PlayerId = u16;
-pub type ManagerId = u8;
+pub type TeamId = u16;
+pub type PlayerId = u32;
+pub type ManagerId = u32;
+
+// Database ID types of older save files, which are converted to the current ones when loaded.
+pub mod legacy {
+    pub type CountryId = u8;
+    pub type CompetitionId = u8;
+
+    pub type TeamId = u8;
+    pub type PlayerId = u16;
+    pub type ManagerId = u8;
+}
 
 pub type CountryNamePool = HashMap<Gender, HashMap<String, NamePool>>;
 
@@ -27,6 +37,11 @@ pub mod convert {
         }
     }
 
+    // Convert an ID of an older save file to the current ID type, which is at least as wide.
+    pub fn legacy_id<N1: Into<N2>, N2>(id: N1) -> N2 {
+        id.into()
+    }
+
     // Convert usize to f64.
     pub fn usize_to_f64(num: usize) -> f64 {
         if num <= (f64::MAX as usize) {
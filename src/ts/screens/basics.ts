@@ -5,6 +5,7 @@ import { drawScreen as drawCompScreen } from "./competition.ts";
 import { onClickHomeScreen } from "./home.ts";
 import { drawScreen as drawHomeScreen } from "./home.ts";
 import { drawScreen as drawPlayerSearchScreen } from "./player_search.ts";
+import { drawScreen as drawHallOfFameScreen } from "./hall_of_fame.ts";
 import { HumanTeamPackage, TopBarPackage } from "../types/team.ts";
 import { Listener } from "../types/dom.ts";
 
@@ -16,12 +17,14 @@ const initialiseTopBar = () => {
     const continueButton = createElement("button", { "textContent": "Continue" }, []);
     const homeScreenButton = createElement("button", { "textContent": "Home Screen" }, []);
     const scoutButton = createElement("button", { "textContent": "Scouting" }, []);
+    const hallOfFameButton = createElement("button", { "textContent": "Hall of Fame" }, []);
 
     const topBar = createElement("div", { "id": "top-bar" }, [
         createElement("div", { "id": "date" }, []),
         continueButton,
         homeScreenButton,
         scoutButton,
+        hallOfFameButton,
         createElement("span", {}, [
             "Actions remaining: ",
             createElement("span", { "id": "actions-remaining" }, []),
@@ -34,6 +37,7 @@ const initialiseTopBar = () => {
     continueButton.addEventListener("click", toNextDay);
     homeScreenButton.addEventListener("click", onClickHomeScreen);
     scoutButton.addEventListener("click", drawPlayerSearchScreen);
+    hallOfFameButton.addEventListener("click", drawHallOfFameScreen);
 };
 
 const resetCompSelect = (comps: HTMLSelectElement) => {
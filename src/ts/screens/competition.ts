@@ -12,6 +12,8 @@ type Format = {
 
 type CompetitionType = "Null" | "Tournament";
 
+type MatchGenType = "Null" | "MatchCount" | "Random" | "Alternating";
+
 type RoundRobinFormat = {
     rounds: number,
     extra_matches: number,
@@ -20,10 +22,23 @@ type RoundRobinFormat = {
     points_for_draw: number,
     points_for_ot_loss: number,
     points_for_loss: number,
+    match_gen_type: MatchGenType,
 };
 
+type TieBreaker = "Overtime" | "AwayGoals";
+
+type SeriesType = "BestOf" | { TwoLegged: TieBreaker };
+
+type BracketType = "Draw" | "Fixed" | "Reseed" | "PickOpponent";
+
 type KnockoutRoundFormat = {
-    wins_required: number
+    wins_required: number,
+    series_type: SeriesType,
+    home_pattern: Array<number>,
+    neutral_venue: boolean,
+    bracket_type: BracketType,
+    entering_teams: number,
+    placement: number
 };
 
 type MatchRules = {
@@ -53,18 +68,38 @@ type Team = {
 };
 
 type KnockoutRound = {
-    pairs: Array<KnockoutPair>
+    name: string | undefined,
+    pairs: Array<KnockoutPair>,
+    placement: number
+};
+
+type PairId = {
+    comp_id: number,
+    index: number
+};
+
+type BracketSlot = {
+    pair_id: PairId,
+    is_home: boolean
 };
 
+type PairStatus = "NotStarted" | "InProgress" | "Over";
+
 type KnockoutPair = {
+    id: PairId,
     home: KnockoutTeam,
-    away: KnockoutTeam
+    away: KnockoutTeam,
+    status: PairStatus,
+    feeders: [PairId | null, PairId | null],
+    destination: BracketSlot | null,
+    games: Array<Game>
 };
 
 type KnockoutTeam = {
     id: number,
     name: string,
     wins: number,
+    score: number,
     seed: number
 };
 
@@ -73,7 +108,17 @@ type Game = {
     away: GameTeam,
     date: string,
     had_overtime: boolean,
-    is_over: boolean
+    had_shootout: boolean,
+    is_neutral_venue: boolean,
+    is_over: boolean,
+    prediction: Prediction | null
+};
+
+// Probabilities of the results after regular time.
+type Prediction = {
+    home_win: number,
+    draw: number,
+    away_win: number
 };
 
 type GameTeam = {
@@ -83,13 +128,60 @@ type GameTeam = {
     goals: number
 };
 
+type AnnualDate = {
+    month: number,
+    day: number
+};
+
+type AnnualWindow = {
+    start: AnnualDate,
+    end: AnnualDate
+};
+
+type ScheduleRules = {
+    game_days: Array<string>,
+    min_rest_days: number,
+    max_back_to_backs: number | null,
+    blackouts: Array<AnnualWindow>
+};
+
+type ScheduleReport = {
+    games: number,
+    off_game_days: number,
+    in_blackouts: number,
+    clashes: number,
+    short_rests: number,
+    back_to_backs: number,
+    excess_back_to_backs: number,
+    average_rest: string,
+    longest_break: number
+};
+
 type Season = {
     name: string,
     teams: Array<Team>,
     knockout_round: KnockoutRound | null,
     rounds: Array<KnockoutRound> | undefined,
+    paths: Record<string, Array<PairId>> | undefined,
     upcoming_games: Array<Game>,
-    played_games: Array<Game>
+    played_games: Array<Game>,
+    schedule_rules: ScheduleRules | null,
+    schedule_report: ScheduleReport | null
+};
+
+// Shares of simulations in which the team finished in each position, advanced from the stage and won the competition.
+type TeamProjection = {
+    id: number,
+    name: string,
+    positions: Array<number>,
+    advanced: number,
+    champion: number
+};
+
+type Projection = {
+    iterations: number,
+    advancing_spots: number,
+    teams: Array<TeamProjection>
 };
 
 type Competition = {
@@ -109,14 +201,14 @@ export const drawScreen = async (id: number) => {
 
     // If the competition is something like playoffs.
     if (comp.season.rounds !== undefined) {
-        drawScreenTournament(screen, comp, comp.season.rounds);
+        drawScreenTournament(screen, comp, comp.season.rounds, comp.season.paths as Record<string, Array<PairId>>);
     }
 
     else if (comp.format === null) {
         drawScreenParent(screen, comp);
     }
     else if (comp.format.type === "RoundRobin") {
-        drawScreenRoundRobin(screen, comp);
+        await drawScreenRoundRobin(screen, comp, id);
     }
 
     // Individual knockout rounds.
@@ -126,30 +218,70 @@ export const drawScreen = async (id: number) => {
 };
 
 // Draw a screen for tournament-type competitions.
-const drawScreenTournament = (screen: HTMLDivElement, comp: Competition, rounds: Array<KnockoutRound>) => {
+const drawScreenTournament = (screen: HTMLDivElement, comp: Competition, rounds: Array<KnockoutRound>, paths: Record<string, Array<PairId>>) => {
     createCompNav(screen, comp.comp_nav);
 
+    // The main bracket first, then the placement rounds of the eliminated teams, with each set of them as a bracket of its own.
+    const placements = [...new Set(rounds.map(a => a.placement))].sort((a, b) => a - b);
     screen.append(
-        drawTournamentTree(rounds),
+        ...placements.map(placement => drawTournamentTree(rounds.filter(a => a.placement === placement), paths)),
         drawSchedule(comp.season, true)
     );
 
 };
 
-// Draw a tournament tree for a round-robin competition.
-const drawTournamentTree = (rounds: Array<KnockoutRound>): HTMLTableElement => {
-    const row = document.createElement("tr");
+// Draw a tournament tree for a knockout competition.
+// Hovering over a team highlights its path through the bracket.
+const drawTournamentTree = (rounds: Array<KnockoutRound>, paths: Record<string, Array<PairId>>): HTMLTableElement => {
+    const header = document.createElement("tr");
     for (const round of rounds) {
+        header.appendChild(createElement("th", { "textContent": round.name ?? "" }, []));
+    }
+
+    const row = document.createElement("tr");
+    for (const pairs of orderBracketPairs(rounds)) {
         row.appendChild(
             createElement("td", {}, [
-                drawRoundPairs(round.pairs)
+                drawRoundPairs(pairs)
             ])
         );
     }
 
-    return createElement("table", {}, [
+    const table: HTMLTableElement = createElement("table", {}, [
+        createElement("thead", {}, [header]),
         createElement("tbody", {}, [row]),
     ]);
+
+    table.addEventListener("mouseover", (e) => {
+        const path = paths[(e.target as HTMLElement).dataset.teamId ?? ""] ?? [];
+        for (const element of table.querySelectorAll("[data-pair-id]")) {
+            const pairId = (element as HTMLElement).dataset.pairId;
+            element.classList.toggle("bracket-path", path.some(a => getPairKey(a) === pairId));
+        }
+    });
+
+    return table;
+};
+
+// Order the pairs of each round so that they are next to the pairs of the next round their winners went to.
+const orderBracketPairs = (rounds: Array<KnockoutRound>): Array<Array<KnockoutPair>> => {
+    const ordered: Array<Array<KnockoutPair>> = rounds.map(a => a.pairs);
+    for (let i = ordered.length - 2; i >= 0; i--) {
+        const feeders = ordered.slice(i + 1).flat().flatMap(a => a.feeders).map(a => a === null ? "" : getPairKey(a));
+        const getPosition = (pair: KnockoutPair): number => {
+            const position = feeders.indexOf(getPairKey(pair.id));
+            return position === -1 ? feeders.length : position;
+        };
+
+        ordered[i] = [...ordered[i]].sort((a, b) => getPosition(a) - getPosition(b));
+    }
+
+    return ordered;
+};
+
+// Get a key that identifies the pair.
+const getPairKey = (id: PairId): string => {
+    return `${id.comp_id}-${id.index}`;
 };
 
 // Draw a screen for parent competitions.
@@ -159,12 +291,15 @@ const drawScreenParent = (screen: HTMLDivElement, comp: Competition) => {
 };
 
 // Draw a screen for round robin competitions.
-const drawScreenRoundRobin = (screen: HTMLDivElement, comp: Competition) => {
+const drawScreenRoundRobin = async (screen: HTMLDivElement, comp: Competition, id: number) => {
+    const projection: Projection = await invoke("get_projection_package", { id: id });
     createCompNav(screen, comp.comp_nav);
 
     screen.append(
         drawRoundRobinStandings(comp.season.teams),
-        drawSchedule(comp.season, false)
+        drawProjection(projection),
+        drawSchedule(comp.season, false),
+        ...drawScheduleQuality(comp.season)
     );
 };
 
@@ -174,7 +309,8 @@ const drawScreenKnockoutRound = (screen: HTMLDivElement, comp: Competition) => {
 
     screen.append(
         drawRoundPairs((comp.season.knockout_round as KnockoutRound).pairs),
-        drawSchedule(comp.season, true)
+        drawSchedule(comp.season, true),
+        ...drawScheduleQuality(comp.season)
     );
 };
 
@@ -221,6 +357,48 @@ const drawRoundRobinStandings = (teams: Array<Team>): HTMLTableElement => {
     return table;
 };
 
+// Draw the projected final standings, with how likely each team is to finish in each position.
+const drawProjection = (projection: Projection): HTMLTableElement => {
+    const hasAdvancing = projection.advancing_spots > 0;
+    const percent = (share: number): string => share === 0 ? "-" : `${Math.round(share * 100)}%`;
+
+    const header = createElement("tr", {}, [createElement("th", { "textContent": "Team" }, [])]);
+    for (let i = 1; i <= projection.teams.length; i++) {
+        header.appendChild(createElement("th", { "textContent": i }, []));
+    }
+    if (hasAdvancing) {
+        header.appendChild(createElement("th", { "textContent": "Playoffs" }, []));
+    }
+    header.appendChild(createElement("th", { "textContent": "Champion" }, []));
+
+    const tbody = document.createElement("tbody");
+    for (const team of projection.teams) {
+        const row = createElement("tr", {}, [
+            createElement("td", {}, [createLink("span", "team", team.id, team.name)]),
+            ...team.positions.map(a => createElement("td", { "textContent": percent(a) }, []))
+        ]);
+
+        if (hasAdvancing) {
+            row.appendChild(createElement("td", { "textContent": percent(team.advanced) }, []));
+        }
+        row.appendChild(createElement("td", { "textContent": percent(team.champion) }, []));
+        tbody.appendChild(row);
+    }
+
+    return createElement("table", {}, [
+        createElement("thead", {}, [
+            createElement("tr", {}, [
+                createElement("th", {
+                    "textContent": `Projection from ${projection.iterations} simulations of the rest of the season.`,
+                    "colSpan": header.children.length
+                }, [])
+            ]),
+            header
+        ]),
+        tbody
+    ]);
+};
+
 // Draw a competition schedule.
 // Only previous and next matches for now.
 const drawSchedule = (season: Season, displaySeed: boolean): HTMLTableElement => {
@@ -234,6 +412,40 @@ const drawSchedule = (season: Season, displaySeed: boolean): HTMLTableElement =>
     ]);
 };
 
+// Draw the schedule rules of the competition, and how well the calendar of the season follows them.
+const drawScheduleQuality = (season: Season): Array<HTMLElement> => {
+    if (season.schedule_rules === null || season.schedule_report === null) return [];
+
+    const rules = season.schedule_rules;
+    let rulesText = `Games on ${rules.game_days.length > 0 ? rules.game_days.join(", ") : "any day"}, at least ${rules.min_rest_days} days of rest`;
+    if (rules.max_back_to_backs !== null) rulesText += `, max ${rules.max_back_to_backs} back-to-backs`;
+    for (const blackout of rules.blackouts) {
+        rulesText += `, no games ${blackout.start.day}.${blackout.start.month}.-${blackout.end.day}.${blackout.end.month}.`;
+    }
+
+    const report = season.schedule_report;
+    const rows: Array<[string, string | number]> = [
+        ["Games", report.games],
+        ["On other days", report.off_game_days],
+        ["During breaks", report.in_blackouts],
+        ["Clashes", report.clashes],
+        ["Too little rest", report.short_rests],
+        ["Back-to-backs", `${report.back_to_backs} (${report.excess_back_to_backs} too many)`],
+        ["Average rest", `${report.average_rest} days`],
+        ["Longest break", `${report.longest_break} days`],
+    ];
+
+    return [
+        createElement("div", { "textContent": rulesText }, []),
+        createElement("table", {}, [
+            createElement("tbody", {}, rows.map(([name, value]) => createElement("tr", {}, [
+                createElement("td", { "textContent": name }, []),
+                createElement("td", { "textContent": value }, []),
+            ])))
+        ])
+    ];
+};
+
 // Draw either a past or a future gameday.
 // Query can either be "previous" or "next".
 const drawGameDay = (isPast: boolean, gameList: Array<Game>, displaySeed: boolean): HTMLTableCellElement => {
@@ -242,6 +454,7 @@ const drawGameDay = (isPast: boolean, gameList: Array<Game>, displaySeed: boolea
 
     let dateColumns = 3;
     if (displaySeed) dateColumns = 5;
+    if (!isPast) dateColumns++;
 
     const matches = [];
     for (let i = gameList.length - 1; i >= 0; i--) {
@@ -297,6 +510,17 @@ const drawGame = (isPast: boolean, game: Game, displaySeed: boolean) => {
         row.appendChild(createElement("td", { "textContent": `(${game.away.seed}.)` }, []));
     }
 
+    if (!isPast) {
+        row.appendChild(createElement("td", {
+            "textContent": getPredictionString(game.prediction),
+            "title": "Home win / Draw / Away win in regular time"
+        }, []));
+    }
+
+    if (game.is_neutral_venue) {
+        row.appendChild(createElement("td", { "textContent": "(N)" }, []));
+    }
+
     return row;
 };
 
@@ -305,7 +529,10 @@ const getScoreString = (isPast: boolean, game: Game) => {
     let scoreString = "-";
     if (isPast) {
         let otString = "";
-        if (game.had_overtime) {
+        if (game.had_shootout) {
+            otString = " SO";
+        }
+        else if (game.had_overtime) {
             otString = " OT";
         }
         scoreString = `${game.home.goals} ${scoreString} ${game.away.goals}${otString}`;
@@ -313,6 +540,14 @@ const getScoreString = (isPast: boolean, game: Game) => {
     return scoreString;
 };
 
+// Return the predicted results of a game as percentages.
+const getPredictionString = (prediction: Prediction | null) => {
+    if (prediction === null) return "";
+
+    const percent = (probability: number) => `${Math.round(probability * 100)}%`;
+    return `${percent(prediction.home_win)} / ${percent(prediction.draw)} / ${percent(prediction.away_win)}`;
+};
+
 // Draw rankings for a competition.
 const drawRanking = (teams: Array<Team>): HTMLTableElement => {
     const tbody = createElement("tbody", {}, [
@@ -337,18 +572,29 @@ const drawRoundPairs = (pairs: Array<KnockoutPair>): HTMLTableElement => {
     const tbody = document.createElement("tbody");
     for (const pair of pairs) {
         tbody.append(
-            drawKnockoutPairTeam(pair.home),
-            drawKnockoutPairTeam(pair.away)
+            drawKnockoutPairTeam(pair, pair.home),
+            drawKnockoutPairTeam(pair, pair.away)
         );
     }
 
     return createElement("table", {}, [tbody]);
 };
 
-const drawKnockoutPairTeam = (team: KnockoutTeam): HTMLTableRowElement => {
-    return createElement("tr", {}, [
+const drawKnockoutPairTeam = (pair: KnockoutPair, team: KnockoutTeam): HTMLTableRowElement => {
+    const link = createLink("span", "team", team.id, team.name);
+    link.dataset.teamId = `${team.id}`;
+
+    const row = createElement("tr", {}, [
         createElement("td", { "textContent": `${team.seed}.` }, []),
-        createElement("td", {}, [createLink("span", "team", team.id, team.name)]),
-        createElement("td", { "textContent": `${team.wins}` }, []),
+        createElement("td", {}, [link]),
+        createElement("td", { "textContent": `${team.score}` }, []),
     ]);
+    row.dataset.pairId = getPairKey(pair.id);
+
+    // Dim the teams of pairs that have not started yet.
+    if (pair.status === "NotStarted") {
+        row.style.opacity = "0.6";
+    }
+
+    return row;
 }
\ No newline at end of file
@@ -0,0 +1,51 @@
+// Hall of fame and the other retired players.
+
+import { invoke } from "@tauri-apps/api/core";
+import { initialiseContentScreen } from "./basics";
+import { createElement, createLink } from "../helpers";
+import { Listener } from "../types/dom";
+import { RetiredPlayer } from "../types/player";
+
+// Draw the retired players screen.
+export const drawScreen: Listener = async (_e: Event) => {
+    const players: Array<RetiredPlayer> = await invoke("get_retired_players_package");
+    const screen = initialiseContentScreen();
+
+    screen.append(
+        createElement("h1", { "textContent": "Hall of Fame" }, []),
+        drawTable(players.filter(a => a.is_hall_of_famer)),
+        createElement("h1", { "textContent": "Retired Players" }, []),
+        drawTable(players.filter(a => !a.is_hall_of_famer)),
+    );
+};
+
+// Draw a table of retired players.
+const drawTable = (players: Array<RetiredPlayer>): HTMLTableElement => {
+    const tbody = createElement("tbody", {}, []);
+    for (const player of players) {
+        tbody.appendChild(createElement("tr", {}, [
+            createElement("td", { "textContent": player.name }, []),
+            createElement("td", { "textContent": player.country }, []),
+            createElement("td", { "textContent": player.position }, []),
+            createElement("td", {}, player.last_team === null ? [] : [createLink("span", "team", player.last_team.id, player.last_team.name)]),
+            createElement("td", { "textContent": player.peak_ability }, []),
+            createElement("td", { "textContent": player.games_played }, []),
+            createElement("td", { "textContent": player.retirement_date }, []),
+        ]));
+    }
+
+    return createElement("table", {}, [
+        createElement("thead", {}, [
+            createElement("tr", {}, [
+                createElement("th", { "textContent": "Name" }, []),
+                createElement("th", { "textContent": "Country" }, []),
+                createElement("th", { "textContent": "Position" }, []),
+                createElement("th", { "textContent": "Last Team" }, []),
+                createElement("th", { "textContent": "Peak Ability" }, []),
+                createElement("th", { "textContent": "Games" }, []),
+                createElement("th", { "textContent": "Retired" }, []),
+            ])
+        ]),
+        tbody
+    ]);
+};
@@ -2,7 +2,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { initialiseContentScreen, updateTopBar } from "./basics";
 import { createElement, createEventListener, createLink } from "../helpers";
 import { drawScreen as drawHomeScreen } from "./home";
-import { Contract, Player } from "../types/player";
+import { Contract, ContractRole, Negotiation, Player } from "../types/player";
 import { HumanPackage, HumanTeamPackage } from "../types/team";
 import { Listener } from "../types/dom";
 
@@ -26,12 +26,19 @@ export const drawScreen = async (id: number) => {
     screen.append(
         getTitle(player),
         createElement("div", {"textContent": `Birthday: ${player.birthday}`}, []),
+        createElement("div", {"textContent": `Games played: ${player.games_played}`}, []),
+        createElement("div", {"textContent": `International: ${player.international_caps} caps, ${player.international_goals} goals`}, []),
+        ...drawFreeAgentStatus(player),
+        ...drawWaivers(player),
+        ...drawLoan(player),
+        ...drawRetirement(player),
         drawContractTable(player),
     );
 
     // Contract offer can be made if...
     if (
         player.contract === null && // ...player does not have a contract,
+        !player.announced_retirement && // ...player is not about to retire,
         humanPackage.team !== null &&  // ...human is managing a team,
         !humanPackage.team.approached_players.includes(id) &&  // ...human's team has not approached the player,
         humanPackage.team.actions_remaining > 0    // ...and human team has actions remaining.
@@ -39,6 +46,147 @@ export const drawScreen = async (id: number) => {
         screen.appendChild(createElement("button", { "id": `offer-contract${id}`, "textContent": "Offer Contract" }, []));
         createEventListener(`#offer-contract${id}`, "click", drawNegotiationScreen);
     }
+
+    // Players of the human's team can be extended during their last season.
+    if (
+        player.contract !== null &&
+        humanPackage.team !== null &&
+        player.contract.team.id === humanPackage.team.id &&
+        player.contract.seasons_left <= 1 &&
+        !player.announced_retirement
+    ) {
+        if (getHumanNegotiation(player, humanPackage) === null && humanPackage.team.actions_remaining > 0) {
+            screen.appendChild(createElement("button", { "id": `offer-contract${id}`, "textContent": "Offer Extension" }, []));
+            createEventListener(`#offer-contract${id}`, "click", drawNegotiationScreen);
+        }
+
+        if (!player.has_qualifying_offer) {
+            screen.appendChild(createElement("button", { "id": `tender-qualifying-offer${id}`, "textContent": "Tender Qualifying Offer" }, []));
+            createEventListener(`#tender-qualifying-offer${id}`, "click", tenderQualifyingOffer);
+        }
+    }
+
+    // Players of the human's team can be let go.
+    if (
+        player.contract !== null &&
+        player.waivers === null &&
+        humanPackage.team !== null &&
+        player.contract.team.id === humanPackage.team.id
+    ) {
+        screen.append(
+            createElement("button", { "id": `release-player${id}`, "textContent": "Release" }, []),
+            createElement("button", { "id": `buy-out-player${id}`, "textContent": "Buy Out" }, []),
+        );
+        createEventListener(`#release-player${id}`, "click", releasePlayer);
+        createEventListener(`#buy-out-player${id}`, "click", buyOutPlayer);
+    }
+
+    // Players of the human's team can be moved between the team and its farm team.
+    if (
+        player.contract !== null &&
+        player.waivers === null &&
+        humanPackage.team !== null &&
+        humanPackage.team.farm_team_id !== 0 &&
+        player.contract.team.id === humanPackage.team.id
+    ) {
+        if (player.loan === null) {
+            screen.appendChild(createElement("button", { "id": `send-down-player${id}`, "textContent": "Send Down" }, []));
+            createEventListener(`#send-down-player${id}`, "click", sendDownPlayer);
+        }
+        else if (player.loan.team.id === humanPackage.team.farm_team_id) {
+            screen.appendChild(createElement("button", { "id": `call-up-player${id}`, "textContent": "Call Up" }, []));
+            createEventListener(`#call-up-player${id}`, "click", callUpPlayer);
+        }
+    }
+
+    // Players released by other teams can be claimed.
+    if (
+        player.waivers !== null &&
+        humanPackage.team !== null &&
+        player.waivers.team.id !== humanPackage.team.id
+    ) {
+        screen.appendChild(createElement("button", { "id": `claim-waived-player${id}`, "textContent": "Claim" }, []));
+        createEventListener(`#claim-waived-player${id}`, "click", claimWaivedPlayer);
+    }
+
+    // Another team has made an offer the human's team can match.
+    if (humanPackage.team !== null && humanPackage.team.offer_sheets.includes(id)) {
+        screen.append(
+            createElement("button", { "id": `match-offer-sheet${id}`, "textContent": "Match Offer" }, []),
+            createElement("button", { "id": `decline-offer-sheet${id}`, "textContent": "Let Go" }, []),
+        );
+        createEventListener(`#match-offer-sheet${id}`, "click", matchOfferSheet);
+        createEventListener(`#decline-offer-sheet${id}`, "click", declineOfferSheet);
+    }
+
+    // The player is waiting for an answer to their demands.
+    const negotiation = getHumanNegotiation(player, humanPackage);
+    if (negotiation !== null && (negotiation.status === "Countered" || negotiation.status === "Stalled")) {
+        screen.append(
+            createElement("button", { "id": `accept-demands${id}`, "textContent": "Accept Demands" }, []),
+            createElement("button", { "id": `counter-offer${id}`, "textContent": "Counter" }, []),
+            createElement("button", { "id": `withdraw-offer${id}`, "textContent": "Walk Away" }, []),
+        );
+        createEventListener(`#accept-demands${id}`, "click", acceptDemands);
+        createEventListener(`#counter-offer${id}`, "click", drawNegotiationScreen);
+        createEventListener(`#withdraw-offer${id}`, "click", withdrawOffer);
+    }
+};
+
+// Draw whose rights the player is under, if any.
+const drawFreeAgentStatus = (player: Player): Array<HTMLElement> => {
+    const status = player.free_agent_status;
+    if (!status.restricted || status.team === undefined) { return []; }
+
+    return [
+        createElement("div", {}, [
+            "Restricted free agent, rights held by ",
+            createLink("span", "team", status.team.id, status.team.name),
+            ` until ${status.until}`,
+        ])
+    ];
+};
+
+// Draw the waiver status of the player, if they have been released.
+const drawWaivers = (player: Player): Array<HTMLElement> => {
+    if (player.waivers === null) { return []; }
+
+    return [
+        createElement("div", {}, [
+            "On waivers, released by ",
+            createLink("span", "team", player.waivers.team.id, player.waivers.team.name),
+            ` on ${player.waivers.placed} (${player.waivers.claims} claims)`,
+        ])
+    ];
+};
+
+// Draw where the player is on loan, if anywhere.
+const drawLoan = (player: Player): Array<HTMLElement> => {
+    if (player.loan === null) { return []; }
+
+    return [
+        createElement("div", {}, [
+            "On loan to ",
+            createLink("span", "team", player.loan.team.id, player.loan.team.name),
+            ` from ${player.loan.start_date} to ${player.loan.end_date}`,
+        ])
+    ];
+};
+
+// Draw the player's plans to retire.
+const drawRetirement = (player: Player): Array<HTMLElement> => {
+    if (!player.announced_retirement) { return []; }
+
+    return [createElement("div", { "textContent": "Retiring at the end of the season" }, [])];
+};
+
+// Get the negotiation the human's team has with the player, if any.
+const getHumanNegotiation = (player: Player, humanPackage: HumanPackage): Negotiation | null => {
+    if (humanPackage.team === null) { return null; }
+
+    const teamId = humanPackage.team.id;
+    const negotiation = player.negotiations.find(a => a.offer.team.id === teamId);
+    return negotiation === undefined ? null : negotiation;
 };
 
 // Draw the contract table.
@@ -46,7 +194,7 @@ const drawContractTable = (player: Player) => {
     return createElement("table", {}, [
         createElement("tbody", {}, [
             ...drawContract(player.contract),
-            ...drawOffers(player.offers),
+            ...drawNegotiations(player.negotiations),
         ])
     ])
 }
@@ -73,46 +221,61 @@ const drawContract = (contract: Contract | null): Array<HTMLTableRowElement> =>
     ];
 };
 
-// Draw contract offers made for the player.
-const drawOffers = (offers: Array<Contract>): Array<HTMLTableRowElement> => {
-    if (offers.length === 0) { return []; }
+// Draw the contract negotiations the player is having.
+const drawNegotiations = (negotiations: Array<Negotiation>): Array<HTMLTableRowElement> => {
+    if (negotiations.length === 0) { return []; }
 
-    const offerElements = [];
-    offerElements.push(createElement("tr", {}, [
-        createElement("th", { "textContent": "Contract Offers" }, []),
-        createElement("th", { "textContent": "Date", "colSpan": 2 }, []),
-        createElement("th", { "textContent": "Seasons" }, []),
+    const negotiationElements = [];
+    negotiationElements.push(createElement("tr", {}, [
+        createElement("th", { "textContent": "Negotiations" }, []),
+        createElement("th", { "textContent": "Status" }, []),
+        createElement("th", { "textContent": "Offer" }, []),
+        createElement("th", { "textContent": "Demand" }, []),
     ]));
 
-    for (const offer of offers) {
-        offerElements.push(createElement("tr", {}, [
+    for (const negotiation of negotiations) {
+        const offer = negotiation.offer;
+        const demand = negotiation.demand;
+        negotiationElements.push(createElement("tr", {}, [
             createElement("td", {}, [createLink("span", "team", offer.team.id, offer.team.name)]),
-            createElement("td", { "textContent": offer.start_date, "colSpan": 2 }, []),
-            createElement("td", { "textContent": offer.seasons_left }, []),
+            createElement("td", { "textContent": negotiation.status }, []),
+            createElement("td", { "textContent": drawTerms(negotiation.years, offer.salary, offer.role) }, []),
+            createElement("td", { "textContent": demand === null ? "" : drawTerms(demand.years, demand.salary, demand.role) }, []),
         ]));
     }
 
-    return offerElements;
+    return negotiationElements;
+};
+
+// Get the terms of a contract as text.
+const drawTerms = (years: number, salary: number, role: ContractRole): string => {
+    return `${years} seasons, ${salary}k, ${role}`;
 };
 
 // Draw the negotiation screen and get that player!
 const drawNegotiationScreen: Listener = async (e: Event) => {
-    const playerId = getPlayerIdFromContractOfferButton(e.target);
+    const playerId = getPlayerIdFromButton(e.target);
     if (playerId === 0) {
         drawHomeScreen();
         return;
     }
 
+    // Offering for the first time or answering the player's demands?
+    const isCounter = (e.target as HTMLButtonElement).id.startsWith("counter-offer");
+    const buttonId = isCounter ? `counter-offer${playerId}` : `offer-contract${playerId}`;
+
     const screen = initialiseContentScreen();
     screen.append(
         ...drawYearSelection(),
+        ...drawSalaryInput(),
+        ...drawRoleSelection(),
         createElement("button", {
-            "id": `offer-contract${playerId}`,
+            "id": buttonId,
             "textContent": "Offer",
         }, [])
     );
 
-    createEventListener(`#offer-contract${playerId}`, "click", offerContractToPlayer);
+    createEventListener(`#${buttonId}`, "click", isCounter ? counterOfferToPlayer : offerContractToPlayer);
 };
 
 // Draw the year selection element.
@@ -137,8 +300,54 @@ const drawYearSelection = (): Array<HTMLElement> => {
     return elements;
 }
 
+// Draw the salary input element.
+const drawSalaryInput = (): Array<HTMLElement> => {
+    return [
+        createElement("label", {
+            "for": "salary",
+            "textContent": "Salary (k)"
+        }, []),
+        createElement("input", {
+            "id": "salary",
+            "type": "number",
+            "min": "0",
+            "value": "0",
+        }, []),
+    ];
+};
+
+// Draw the role selection element.
+const drawRoleSelection = (): Array<HTMLElement> => {
+    const elements = [
+        createElement("label", {
+            "for": "role",
+            "textContent": "Role"
+        }, []),
+    ];
+
+    const select = createElement("select", { "id": "role" }, []);
+    for (const role of ["Depth", "Regular", "Key"]) {
+        select.appendChild(createElement("option", {
+            "value": role,
+            "textContent": role,
+        }, []));
+    }
+
+    elements.push(select);
+    return elements;
+};
+
+// Get the contract terms from the negotiation screen.
+const getTerms = () => {
+    return {
+        years: Number((document.querySelector("#years") as HTMLSelectElement).value),
+        salary: Number((document.querySelector("#salary") as HTMLInputElement).value),
+        role: (document.querySelector("#role") as HTMLSelectElement).value as ContractRole,
+    };
+};
+
 const offerContractToPlayer: Listener = async (e: Event) => {
-    const playerId = getPlayerIdFromContractOfferButton(e.target);
+    const playerId = getPlayerIdFromButton(e.target);
     if (playerId === 0) {
 
         // Top bar should be updated here if actions remaining are displayed there...
@@ -147,16 +356,95 @@ const offerContractToPlayer: Listener = async (e: Event) => {
     }
 
     const humanPackage: HumanPackage = await invoke("get_human_package");
-    const years = Number((document.querySelector("#years") as HTMLSelectElement).value);
-    await invoke("offer_contract", { playerId: playerId, teamId: (humanPackage.team as HumanTeamPackage).id, years: years });
+    await invoke("offer_contract", { playerId: playerId, teamId: (humanPackage.team as HumanTeamPackage).id, ...getTerms() });
 
     updateTopBar(); // Needs to be updated as one action is used here.
     drawScreen(playerId);
 };
 
-const getPlayerIdFromContractOfferButton = (target: EventTarget | null): number => {
+const counterOfferToPlayer: Listener = async (e: Event) => {
+    const playerId = getPlayerIdFromButton(e.target);
+    if (playerId === 0) {
+        drawHomeScreen();
+        return;
+    }
+
+    const humanPackage: HumanPackage = await invoke("get_human_package");
+    await invoke("counter_offer", { playerId: playerId, teamId: (humanPackage.team as HumanTeamPackage).id, ...getTerms() });
+    drawScreen(playerId);
+};
+
+const acceptDemands: Listener = async (e: Event) => {
+    const playerId = getPlayerIdFromButton(e.target);
+    if (playerId === 0) {
+        drawHomeScreen();
+        return;
+    }
+
+    const humanPackage: HumanPackage = await invoke("get_human_package");
+    await invoke("accept_demands", { playerId: playerId, teamId: (humanPackage.team as HumanTeamPackage).id });
+    drawScreen(playerId);
+};
+
+const withdrawOffer: Listener = async (e: Event) => {
+    const playerId = getPlayerIdFromButton(e.target);
+    if (playerId === 0) {
+        drawHomeScreen();
+        return;
+    }
+
+    const humanPackage: HumanPackage = await invoke("get_human_package");
+    await invoke("withdraw_offer", { playerId: playerId, teamId: (humanPackage.team as HumanTeamPackage).id });
+    drawScreen(playerId);
+};
+
+const tenderQualifyingOffer: Listener = async (e: Event) => {
+    await invokeForHumanTeam("tender_qualifying_offer", getPlayerIdFromButton(e.target));
+};
+
+const matchOfferSheet: Listener = async (e: Event) => {
+    await invokeForHumanTeam("match_offer_sheet", getPlayerIdFromButton(e.target));
+};
+
+const declineOfferSheet: Listener = async (e: Event) => {
+    await invokeForHumanTeam("decline_offer_sheet", getPlayerIdFromButton(e.target));
+};
+
+const releasePlayer: Listener = async (e: Event) => {
+    await invokeForHumanTeam("release_player", getPlayerIdFromButton(e.target));
+};
+
+const buyOutPlayer: Listener = async (e: Event) => {
+    await invokeForHumanTeam("buy_out_player", getPlayerIdFromButton(e.target));
+};
+
+const claimWaivedPlayer: Listener = async (e: Event) => {
+    await invokeForHumanTeam("claim_waived_player", getPlayerIdFromButton(e.target));
+};
+
+const sendDownPlayer: Listener = async (e: Event) => {
+    await invokeForHumanTeam("send_down_player", getPlayerIdFromButton(e.target));
+};
+
+const callUpPlayer: Listener = async (e: Event) => {
+    await invokeForHumanTeam("call_up_player", getPlayerIdFromButton(e.target));
+};
+
+// Call a command that takes the player and the human's team, and redraw the player screen.
+const invokeForHumanTeam = async (command: string, playerId: number) => {
+    if (playerId === 0) {
+        drawHomeScreen();
+        return;
+    }
+
+    const humanPackage: HumanPackage = await invoke("get_human_package");
+    await invoke(command, { playerId: playerId, teamId: (humanPackage.team as HumanTeamPackage).id });
+    drawScreen(playerId);
+};
+
+const getPlayerIdFromButton = (target: EventTarget | null): number => {
     const elementId = (target as HTMLButtonElement).id;
-    const regexMatch = elementId.match(/offer-contract([0-9]+)/);
+    const regexMatch = elementId.match(/[a-z-]+([0-9]+)/);
 
     // This should never happen, but it calms TypeScript down.
     if (regexMatch === null) {
@@ -164,4 +452,4 @@ const getPlayerIdFromContractOfferButton = (target: EventTarget | null): number
     }
 
     return Number(regexMatch[1]);
-};
\ No newline at end of file
+};
@@ -52,7 +52,7 @@ const drawPlayer = (player: Player): HTMLTableRowElement => {
         createElement("td", { "textContent": player.position }, []),
         createElement("td", { "textContent": player.age }, []),
         createElement("td", { "textContent": player.ability }, []),
-        createElement("td", { "textContent": player.offers.length }, []),
+        createElement("td", { "textContent": player.negotiations.filter(a => a.status !== "BrokenDown").length }, []),
     ]);
 };
 
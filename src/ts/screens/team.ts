@@ -2,7 +2,7 @@
 import { invoke } from "@tauri-apps/api/core";
 import { initialiseContentScreen } from "./basics";
 import { createElement, createLink } from "../helpers";
-import { RosterSetting, Team } from "../types/team";
+import { Payroll, Registration, RosterSetting, Team } from "../types/team";
 import { Player } from "../types/player";
 import { Listener } from "../types/dom";
 
@@ -12,18 +12,80 @@ export const drawScreen = async (id: number) => {
     const team: Team = await invoke("get_team_screen_package", { id: id });
 
     const elements: Array<HTMLElement> = [
-        createElement("h1", {"textContent": team.name}, [])
+        createElement("h1", {"textContent": team.name}, []),
+        createElement("div", { "textContent": `Country: ${team.country}` }, []),
+        createElement("div", { "textContent": `Rating: ${team.rating.value}` }, [])
     ];
 
     if (team.manager !== null) {
         elements.push(createElement("div", { "textContent": `Manager: ${team.manager.name}` }, []));
     }
 
+    if (team.parent_team !== null) {
+        elements.push(createElement("div", {}, ["Farm team of ", createLink("span", "team", team.parent_team.id, team.parent_team.name)]));
+    }
+    if (team.farm_team !== null) {
+        elements.push(createElement("div", {}, ["Farm team: ", createLink("span", "team", team.farm_team.id, team.farm_team.name)]));
+    }
+
+    if (team.payroll !== null) {
+        elements.push(...drawPayroll(team.payroll));
+    }
+    if (team.registration !== null) {
+        elements.push(...drawRegistration(team.registration));
+    }
+
     const screen = initialiseContentScreen();
     screen.append(...elements);
     drawRoster(screen, team.players);
 };
 
+// Draw the payroll of a club.
+const drawPayroll = (payroll: Payroll): Array<HTMLElement> => {
+    const elements: Array<HTMLElement> = [];
+    elements.push(createElement("div", { "textContent": `Payroll: ${payroll.total}k` }, []));
+    for (const deadMoney of payroll.dead_money) {
+        // Retired players no longer have a screen of their own.
+        const name = deadMoney.is_retired ? deadMoney.name : createLink("span", "player", deadMoney.player_id, deadMoney.name);
+        elements.push(createElement("div", {}, [
+            "Paying ",
+            name,
+            ` ${deadMoney.salary}k until ${deadMoney.end_date}`,
+        ]));
+    }
+
+    return elements;
+};
+
+// Draw the roster rules of a club's league, and how the club breaks them.
+const drawRegistration = (registration: Registration): Array<HTMLElement> => {
+    const elements: Array<HTMLElement> = [];
+    const rules = registration.rules;
+    let rulesText = `Roster: ${rules.min_roster_size}-${rules.max_roster_size} players, ${rules.dressed_players} dressed`;
+    if (rules.max_imports !== null) rulesText += `, max ${rules.max_imports} imports`;
+    if (rules.max_age !== null) rulesText += `, max age ${rules.max_age}`;
+    elements.push(createElement("div", { "textContent": rulesText }, []));
+
+    for (const violation of registration.violations) {
+        switch (violation.kind) {
+            case "TooFewPlayers":
+                elements.push(createElement("div", { "textContent": `Too few players (at least ${violation.limit} needed)` }, []));
+                break;
+            case "TooManyPlayers":
+                elements.push(createElement("div", { "textContent": `Too many players (at most ${violation.limit} allowed)` }, []));
+                break;
+            case "TooManyImports":
+                elements.push(createElement("div", { "textContent": `Too many imports (at most ${violation.limit} allowed)` }, []));
+                break;
+            case "OverAge":
+                elements.push(createElement("div", {}, [createLink("span", "player", violation.player_id, violation.name), " is too old for the league"]));
+                break;
+        }
+    }
+
+    return elements;
+};
+
 // Draw the roster of a team.
 const drawRoster = (screen: HTMLDivElement, players: Array<Player>) => {
     const filterSelect = createElement("select", {}, [
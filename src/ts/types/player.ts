@@ -1,17 +1,58 @@
 export type Position = "GK" | "LD" | "RD" | "LW" | "C" | "RW";
 
-type ContractTeam = {
+export type ContractTeam = {
     name: string,
     id: number
 };
 
+export type ContractRole = "Depth" | "Regular" | "Key";
+
 export type Contract = {
     start_date: string,
     end_date: string,
     seasons_left: number,
+    salary: number,
+    role: ContractRole,
     team: ContractTeam
 };
 
+type Demand = {
+    years: number,
+    salary: number,
+    role: ContractRole
+};
+
+export type NegotiationStatus = "Offered" | "Countered" | "Stalled" | "Agreed" | "AwaitingMatch" | "BrokenDown";
+
+export type Negotiation = {
+    status: NegotiationStatus,
+    round: number,
+    years: number,
+    offer: Contract,
+    demand: Demand | null,
+    last_move: string
+};
+
+type FreeAgentStatus = {
+    restricted: boolean,
+    team?: ContractTeam,
+    until?: string
+};
+
+// A player released by their team, waiting for other teams to claim them.
+type Waivers = {
+    team: ContractTeam,
+    placed: string,
+    claims: number
+};
+
+// A player playing for another team while under contract with their own.
+type Loan = {
+    team: ContractTeam,
+    start_date: string,
+    end_date: string
+};
+
 export type Player = {
     id: number,
     name: string,
@@ -21,6 +62,28 @@ export type Player = {
     birthday: string,
     ability: number,
     real_ability: number,
+    has_agent: boolean,
     contract: Contract | null,
-    offers: Array<Contract>
-};
\ No newline at end of file
+    free_agent_status: FreeAgentStatus,
+    has_qualifying_offer: boolean,
+    waivers: Waivers | null,
+    loan: Loan | null,
+    announced_retirement: boolean,
+    games_played: number,
+    international_caps: number,
+    international_goals: number,
+    negotiations: Array<Negotiation>
+};
+// A player who has ended their career.
+export type RetiredPlayer = {
+    id: number,
+    name: string,
+    country: string,
+    position: Position,
+    birthday: string,
+    retirement_date: string,
+    last_team: ContractTeam | null,
+    peak_ability: number,
+    games_played: number,
+    is_hall_of_famer: boolean
+};
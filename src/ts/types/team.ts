@@ -1,4 +1,4 @@
-import { Player, Position } from "./player";
+import { ContractTeam, Player, Position } from "./player";
 
 export type RosterOverview = Array<{
     "position": Position,
@@ -7,30 +7,122 @@ export type RosterOverview = Array<{
 }>;
 
 type Manager = {
-    name: string
+    name: string,
+    reputation: number
 };
 
 
 export type HumanTeamPackage = {
     id: number,
     actions_remaining: number,
+    board: Board,
 
     // Roster overview includes approached players as well.
     // This is dupliocated information, but makes accessing it much easier.
     roster_overview: RosterOverview,
     approached_players: Array<number>,
+
+    // Restricted free agents whose offers from other teams the team can match.
+    offer_sheets: Array<number>,
+
+    // 0 if the team has no farm team.
+    farm_team_id: number,
+};
+
+type ObjectiveStatus = "OnTrack" | "Behind" | "Achieved" | "Failed";
+
+type Objective = {
+    description: string,
+    importance: number,
+    status: ObjectiveStatus
+};
+
+// What the board of the team thinks.
+export type Board = {
+    confidence: number,
+    expected_rank: number,
+    objectives: Array<Objective>
+};
+
+// The board's verdict after a season.
+export type SeasonReview = {
+    team: {
+        id: number,
+        name: string
+    },
+    date: string,
+    expected_rank: number,
+    final_rank: number,
+    objectives: Array<Objective>,
+    confidence: number,
+    outcome: "Continues" | "Renewed" | "NotRenewed" | "Fired"
 };
 
 // Important info of a human manager.
 export type HumanPackage = {
+    reputation: number,
+    is_active: boolean,
     team: HumanTeamPackage | null
 };
+
+// A team looking for a manager.
+export type Vacancy = {
+    id: number,
+    name: string,
+    competition: string,
+    expected_rank: number,
+    required_reputation: number,
+    has_applied: boolean
+};
+// Salaries the team pays.
+export type Payroll = {
+    total: number,
+    dead_money: Array<{
+        player_id: number,
+        name: string,
+        is_retired: boolean,
+        salary: number,
+        end_date: string
+    }>
+};
+
+// Roster rules of the team's league, and the ways the team breaks them.
+export type Registration = {
+    rules: {
+        min_roster_size: number,
+        max_roster_size: number,
+        dressed_players: number,
+        max_imports: number | null,
+        max_age: number | null
+    },
+    violations: Array<
+        { kind: "TooFewPlayers" | "TooManyPlayers" | "TooManyImports", limit: number } |
+        { kind: "OverAge", player_id: number, name: string }
+    >
+};
+
+// Elo-style rating, with its value after each day the team has played.
+export type Rating = {
+    value: number,
+    history: Array<{ date: string, value: number }>
+};
+
 export type RosterSetting = "roster" | "approached" | "both";
 
 export type Team = {
     id: number,
     name: string,
+    country: string,
+    is_national_team: boolean,
     manager: Manager | null,
+    expected_rank: number,
+    rating: Rating,
+
+    // National teams have neither.
+    payroll: Payroll | null,
+    registration: Registration | null,
+    parent_team: ContractTeam | null,
+    farm_team: ContractTeam | null,
     players: Array<Player>
 };
 